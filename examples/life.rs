@@ -1,117 +1,213 @@
-use rand::{
-    distributions::{Distribution, Standard},
-    Rng,
-};
+use rand::Rng;
 
-use std::{thread, time::Duration};
+use std::{
+    fmt::Write,
+    fs,
+    hint::black_box,
+    thread,
+    time::{Duration, Instant},
+};
 
 use tapestry::{
-    patterns::{Neighborhood, Rect},
-    Coord, VecGrid,
+    automata::{EdgeBehavior, LifeRule},
+    patterns::{Connectivity, Neighborhood, Rect},
+    Coord, Grid, GridError, IterCell, SimGrid, VecGrid,
 };
 
 const FPS: f32 = 30.0;
 const FRAME_MILLIS: f32 = 1000.0 / FPS;
 
 fn main() {
-    let mut life_board = LifeBoard::random((16, 16));
+    // Arguments can be any Life-like rule, e.g. "B36/S23", and the path of a
+    // pattern in RLE format to start from, in either order. Without a rule,
+    // the pattern's own rule is used, falling back to Conway's. `--bench`
+    // times neighbor counting, selections and passes over every cell on a
+    // large board instead.
+    let mut rule = None;
+    let mut pattern = None;
+    for arg in std::env::args().skip(1) {
+        if arg == "--bench" {
+            benchmark();
+            return;
+        }
+        match arg.parse::<LifeRule>() {
+            Ok(parsed) => rule = Some(parsed),
+            Err(_) => pattern = Some(load_pattern(&arg)),
+        }
+    }
+    let (mut board, pattern_rule) = match pattern {
+        Some((board, pattern_rule)) => (board, pattern_rule),
+        None => (random_board((16, 16)), None),
+    };
+    let rule = rule.or(pattern_rule).unwrap_or(LifeRule::CONWAY);
 
+    let mut frame = String::new();
     loop {
         // Clear terminal
         print!("\x1B[2J\x1B[1;1H");
-        // Map grid of characters into a grid of strings, addings spaces between the
-        // characters.
-        let display_grid = life_board.grid.map(|cell| {
-            format!("{} ", {
-                match cell {
-                    LifeState::Alive => '#',
-                    LifeState::Dead => '∙',
-                }
+        // Draw each cell followed by a space, reusing the frame's buffer.
+        frame.clear();
+        board
+            .front()
+            .render_to(&mut frame, |f, _, &alive| {
+                f.write_str(if alive { "# " } else { "∙ " })
             })
+            .unwrap();
+        println!("{}", frame);
+        // Count every cell's neighbors in one pass before stepping.
+        let neighbors =
+            board
+                .front()
+                .neighbor_counts(|&alive| alive, Connectivity::Eight, EdgeBehavior::Wrap);
+        board.step(|coord, grid| {
+            let alive = *grid.get(coord).unwrap();
+            rule.next_state(alive, *neighbors.get(coord).unwrap() as usize)
         });
-        println!("{}", display_grid);
-        life_board.step();
         thread::sleep(Duration::from_millis(FRAME_MILLIS as u64));
     }
 }
 
-struct LifeBoard {
-    grid: VecGrid<LifeState>,
+/// Creates a board with roughly 30% of cells alive. The board keeps the
+/// previous generation around, so stepping it doesn't allocate.
+fn random_board<C: Into<Coord>>(dimensions: C) -> SimGrid<bool> {
+    let mut rng = rand::thread_rng();
+    SimGrid::new(VecGrid::with_generator(
+        Rect::new(dimensions),
+        |(_x, _y)| rng.gen_bool(0.3),
+    ))
 }
 
-impl LifeBoard {
-    fn random<C: Into<Coord>>(dimensions: C) -> Self {
-        Self {
-            grid: VecGrid::with_generator(Rect::new(dimensions), |(_x, _y)| {
-                rand::random::<LifeState>()
-            }),
-        }
-    }
+/// Loads an RLE pattern, placing it in the middle of a board with some room
+/// to evolve.
+fn load_pattern(path: &str) -> (SimGrid<bool>, Option<LifeRule>) {
+    let text = fs::read_to_string(path).expect("couldn't read pattern");
+    let (pattern, rule) = VecGrid::from_rle(&text).expect("invalid pattern");
+    let margin = Coord::new(8, 8);
+    let dimensions = pattern.bounds.dimensions() + margin + margin;
+    let mut board = VecGrid::new(Rect::new((dimensions.x.max(16), dimensions.y.max(16))));
+    board.embed(pattern, margin);
+    (SimGrid::new(board), rule)
+}
 
-    fn step(&mut self) {
-        let neighbor_counts = self
-            .grid
-            .iter()
-            .map(|cell| self.live_neighbor_count(cell.0))
-            // `collect` to release the borrow on `self`.
-            .collect::<Vec<_>>();
+/// Compares counting the neighbors of each cell in turn against counting them
+/// all at once with `neighbor_counts`, on a 1024×1024 board. Run it in release
+/// mode: `cargo run --release --example life -- --bench`.
+fn benchmark() {
+    const RUNS: u32 = 5;
+    let board = random_board((1024, 1024));
+    let board = board.front();
 
-        for ((_coord, cell), neighbor_count) in self.grid.iter_mut().zip(neighbor_counts) {
-            *cell = LifeBoard::compute_state(*cell, neighbor_count)
-        }
+    let start = Instant::now();
+    let mut per_cell = None;
+    for _ in 0..RUNS {
+        per_cell = Some(VecGrid::with_generator(board.bounds, |coord| {
+            board.live_neighbors(coord, EdgeBehavior::Wrap) as u8
+        }));
     }
+    let per_cell_time = start.elapsed() / RUNS;
 
-    fn live_neighbor_count(&self, coord: Coord) -> usize {
-        self.grid
-            .selection_iter(Neighborhood::new(coord).iter())
-            .filter(|r_cell| {
-                if let Ok(cell) = r_cell {
-                    *cell.1 == LifeState::Alive
-                } else {
-                    false
-                }
+    let start = Instant::now();
+    let mut all_at_once = None;
+    for _ in 0..RUNS {
+        all_at_once =
+            Some(board.neighbor_counts(|&alive| alive, Connectivity::Eight, EdgeBehavior::Wrap));
+    }
+    let all_at_once_time = start.elapsed() / RUNS;
+
+    assert_eq!(per_cell, all_at_once);
+    let counts = all_at_once.unwrap();
+    println!("live_neighbors per cell: {:?}", per_cell_time);
+    println!("neighbor_counts:         {:?}", all_at_once_time);
+    println!(
+        "neighbor_counts is {:.1}x faster",
+        per_cell_time.as_secs_f64() / all_at_once_time.as_secs_f64()
+    );
+
+    benchmark_selections(board);
+    benchmark_values(&counts);
+}
+
+/// Compares selecting the Moore neighborhood of every cell from coords
+/// buffered in a `Vec` first, as callers had to before `selection_iter` took
+/// any `IntoIterator`, against passing the neighborhood's iterator directly.
+fn benchmark_selections(board: &VecGrid<bool>) {
+    const RUNS: u32 = 5;
+    let neighborhood = Neighborhood::moore();
+
+    let start = Instant::now();
+    let mut buffered = 0;
+    for _ in 0..RUNS {
+        buffered = board
+            .bounds
+            .iter()
+            .map(|coord| {
+                let coords = neighborhood.iter_around(coord).collect::<Vec<_>>();
+                count_alive(board.selection_iter(coords))
             })
-            .count()
+            .sum::<usize>();
     }
+    let buffered_time = start.elapsed() / RUNS;
 
-    fn compute_state(state: LifeState, neighbor_count: usize) -> LifeState {
-        match state {
-            LifeState::Alive => {
-                if neighbor_count == 2 || neighbor_count == 3 {
-                    LifeState::Alive
-                } else {
-                    LifeState::Dead
-                }
-            }
-            LifeState::Dead => {
-                if neighbor_count == 3 {
-                    LifeState::Alive
-                } else {
-                    LifeState::Dead
-                }
-            }
-        }
+    let start = Instant::now();
+    let mut lazy = 0;
+    for _ in 0..RUNS {
+        lazy = board
+            .bounds
+            .iter()
+            .map(|coord| count_alive(board.selection_iter(neighborhood.iter_around(coord))))
+            .sum::<usize>();
     }
-}
+    let lazy_time = start.elapsed() / RUNS;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-enum LifeState {
-    Dead,
-    Alive,
+    assert_eq!(buffered, lazy);
+    println!("selection_iter over a Vec: {:?}", buffered_time);
+    println!("selection_iter over coords: {:?}", lazy_time);
+    println!(
+        "passing coords directly is {:.1}x faster",
+        buffered_time.as_secs_f64() / lazy_time.as_secs_f64()
+    );
 }
 
-impl Default for LifeState {
-    fn default() -> Self {
-        LifeState::Dead
+/// Compares summing every cell's neighbor count through `iter`, which pairs
+/// each value with its coord, against summing them through `values`, a plain
+/// pass over the backing slice. The grid goes through `black_box` on every run
+/// so the sum can't be hoisted out of the loop.
+fn benchmark_values(counts: &VecGrid<u8>) {
+    const RUNS: u32 = 5;
+
+    let start = Instant::now();
+    let mut with_coords = 0;
+    for _ in 0..RUNS {
+        with_coords = black_box(counts)
+            .iter()
+            .map(|(_, &count)| u32::from(count))
+            .sum::<u32>();
     }
-}
+    let with_coords_time = start.elapsed() / RUNS;
 
-// Allows us to randomly generate LifeState values.
-impl Distribution<LifeState> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> LifeState {
-        match rng.gen_bool(0.3) {
-            true => LifeState::Alive,
-            false => LifeState::Dead,
-        }
+    let start = Instant::now();
+    let mut values = 0;
+    for _ in 0..RUNS {
+        values = black_box(counts)
+            .values()
+            .map(|&count| u32::from(count))
+            .sum::<u32>();
     }
+    let values_time = start.elapsed() / RUNS;
+
+    assert_eq!(with_coords, values);
+    println!("sum over iter:   {:?}", with_coords_time);
+    println!("sum over values: {:?}", values_time);
+    println!(
+        "values is {:.1}x faster",
+        with_coords_time.as_secs_f64() / values_time.as_secs_f64()
+    );
+}
+
+/// Counts the live cells among the selected ones.
+fn count_alive<'a, I>(cells: I) -> usize
+where
+    I: Iterator<Item = Result<IterCell<'a, bool>, GridError>>,
+{
+    cells.filter(|cell| matches!(cell, Ok((_, &true)))).count()
 }
@@ -0,0 +1,40 @@
+//! Builds against the crate without the standard library, to check that the
+//! `alloc`-only subset keeps compiling:
+//!
+//! ```
+//! cargo build --example no_std --no-default-features
+//! ```
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use tapestry::{
+    patterns::{Line, Neighborhood, Rect},
+    Coord, Grid, GridMut, VecGrid,
+};
+
+/// Draws a line across an empty grid, and returns the number of live
+/// neighbors of each cell.
+pub fn neighbor_counts() -> VecGrid<usize> {
+    let mut grid = VecGrid::filled(Rect::new((8, 8)), false);
+    for coord in Line::new((0, 1), (7, 6)).iter() {
+        grid.set(coord, true);
+    }
+    let neighborhood = Neighborhood::moore();
+    VecGrid::with_generator(grid.bounds, |coord: Coord| {
+        neighborhood
+            .iter_around(coord)
+            .filter(|&neighbor| grid.get(neighbor) == Some(&true))
+            .count()
+    })
+}
+
+/// Returns the coordinates of every cell with exactly two live neighbors.
+pub fn on_the_line() -> Vec<Coord> {
+    neighbor_counts()
+        .coords_where(|_, &count| count == 2)
+        .collect()
+}
@@ -0,0 +1,166 @@
+use alloc::{borrow::Cow, string::String};
+use core::fmt::Write;
+
+use crate::{coord::Coord, vecgrid::VecGrid};
+
+/// A terminal color, for [`CellStyle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnsiColor {
+    /// An entry of the 256-color palette, where the first 16 are the
+    /// terminal's configurable standard colors.
+    Palette(u8),
+    /// A 24-bit color, which most modern terminals support.
+    Rgb(u8, u8, u8),
+}
+
+impl AnsiColor {
+    /// Appends the parameters selecting this color to an SGR escape sequence,
+    /// where `base` is 38 for the foreground and 48 for the background.
+    fn write_params(self, params: &mut String, base: u8) {
+        match self {
+            AnsiColor::Palette(index) => write!(params, ";{};5;{}", base, index),
+            AnsiColor::Rgb(r, g, b) => write!(params, ";{};2;{};{};{}", base, r, g, b),
+        }
+        .unwrap();
+    }
+}
+
+/// How to draw one cell with [`VecGrid::render_ansi`]: the text to print, and
+/// the colors to print it in. Colors left as `None` use the terminal's
+/// default.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CellStyle {
+    pub text: Cow<'static, str>,
+    pub fg: Option<AnsiColor>,
+    pub bg: Option<AnsiColor>,
+}
+
+impl CellStyle {
+    /// Constructs an uncolored style printing `text`.
+    pub fn new<S: Into<Cow<'static, str>>>(text: S) -> Self {
+        Self {
+            text: text.into(),
+            fg: None,
+            bg: None,
+        }
+    }
+
+    pub fn with_fg(mut self, fg: AnsiColor) -> Self {
+        self.fg = Some(fg);
+        self
+    }
+
+    pub fn with_bg(mut self, bg: AnsiColor) -> Self {
+        self.bg = Some(bg);
+        self
+    }
+}
+
+impl<T> VecGrid<T> {
+    /// Renders the grid for a terminal, calling `style` to get the text and
+    /// colors of each cell. Each row is printed on its own line.
+    ///
+    /// Escape sequences are only written where the colors change from one
+    /// cell to the next, and colors are reset at the end of each row, so
+    /// nothing leaks into the following output. When `colors` is false the
+    /// colors are ignored, and only the text is written, which keeps logs
+    /// readable when output is redirected to a file.
+    ///
+    /// Rows are printed from the top down, unless `y_up` is true, in which
+    /// case they're printed from the bottom up for code that treats +y as up.
+    pub fn render_ansi<F>(&self, colors: bool, y_up: bool, style: F) -> String
+    where
+        F: Fn(Coord, &T) -> CellStyle,
+    {
+        let mut output = String::new();
+        let mut current = (None, None);
+        let cells = self.rows_oriented(y_up).flat_map(|(y, row)| {
+            (self.bounds.left..)
+                .zip(row)
+                .map(move |(x, cell)| (Coord::new(x, y), cell))
+        });
+        for (coord, cell) in cells {
+            let style = style(coord, cell);
+            let colors_of_cell = (style.fg, style.bg);
+            if colors && colors_of_cell != current {
+                // Only a reset switches back to a default color, so reset
+                // whenever the previous cell had any, in the same sequence as
+                // the new colors.
+                let mut params = String::new();
+                if current != (None, None) {
+                    params.push_str(";0");
+                }
+                if let Some(fg) = style.fg {
+                    fg.write_params(&mut params, 38);
+                }
+                if let Some(bg) = style.bg {
+                    bg.write_params(&mut params, 48);
+                }
+                write!(output, "\x1b[{}m", &params[1..]).unwrap();
+                current = colors_of_cell;
+            }
+            output.push_str(&style.text);
+            if coord.x + 1 == self.bounds.right {
+                if current != (None, None) {
+                    output.push_str("\x1b[0m");
+                    current = (None, None);
+                }
+                output.push('\n');
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::Rect;
+
+    fn terrain(coord: Coord, &height: &i32) -> CellStyle {
+        match height {
+            0 => CellStyle::new("~")
+                .with_fg(AnsiColor::Palette(15))
+                .with_bg(AnsiColor::Palette(4)),
+            1 => CellStyle::new("."),
+            _ => CellStyle::new(if coord.x % 2 == 0 { "^" } else { "A" })
+                .with_fg(AnsiColor::Rgb(120, 80, 40)),
+        }
+    }
+
+    #[test]
+    fn coalesces_runs() {
+        let heights =
+            VecGrid::from_raw_parts(Rect::new((4, 2)), vec![0, 0, 1, 2, 2, 2, 0, 1]).unwrap();
+        let ocean = "\x1b[38;5;15;48;5;4m";
+        let hills = "\x1b[38;2;120;80;40m";
+        assert_eq!(
+            heights.render_ansi(true, false, terrain),
+            format!(
+                "{}~~\x1b[0m.{}A\x1b[0m\n{}^A\x1b[0;38;5;15;48;5;4m~\x1b[0m.\n",
+                ocean, hills, hills
+            )
+        );
+        assert_eq!(heights.render_ansi(false, false, terrain), "~~.A\n^A~.\n");
+        assert_eq!(
+            heights.render_ansi(true, true, terrain),
+            format!(
+                "{}^A\x1b[0;38;5;15;48;5;4m~\x1b[0m.\n{}~~\x1b[0m.{}A\x1b[0m\n",
+                hills, ocean, hills
+            )
+        );
+    }
+
+    #[test]
+    fn uncolored_cells_write_no_escapes() {
+        let grid = VecGrid::filled(Rect::new((3, 2)), ());
+        assert_eq!(
+            grid.render_ansi(true, false, |coord, _| CellStyle::new(coord.to_string())),
+            "(0, 0)(1, 0)(2, 0)\n(0, 1)(1, 1)(2, 1)\n"
+        );
+        assert_eq!(
+            grid.render_ansi(true, true, |coord, _| CellStyle::new(coord.to_string())),
+            "(0, 1)(1, 1)(2, 1)\n(0, 0)(1, 0)(2, 0)\n"
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Conversions between grids and `ndarray` arrays.
+//!
+//! A grid maps onto a 2D array of shape `(height, width)`, indexed by row and
+//! then by column, so the cell at `(x, y)` is the element at
+//! `[[y - top, x - left]]`. Row-major grids match the array's standard layout
+//! and column-major grids match its Fortran layout, so either can be viewed as
+//! an array without copying.
+
+use core::convert::TryFrom;
+
+use ndarray::{Array2, ArrayView2, ArrayViewMut2, ShapeBuilder};
+
+use crate::{
+    coord::Coord,
+    grid::GridError,
+    layout::{ColumnMajor, Layout},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+impl<T> VecGrid<T> {
+    /// Borrows the grid as an array of shape `(height, width)`, with the cell at
+    /// `(x, y)` at index `[[y - top, x - left]]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid's bounds don't match the number of its cells.
+    pub fn as_array_view(&self) -> ArrayView2<'_, T> {
+        ArrayView2::from_shape(shape(self), &self.cells).unwrap()
+    }
+
+    /// Mutable version of [`as_array_view`](VecGrid::as_array_view).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid's bounds don't match the number of its cells.
+    pub fn as_array_view_mut(&mut self) -> ArrayViewMut2<'_, T> {
+        ArrayViewMut2::from_shape(shape(self), &mut self.cells).unwrap()
+    }
+}
+
+impl<T> VecGrid<T, ColumnMajor> {
+    /// Borrows the grid as an array of shape `(height, width)` in Fortran
+    /// layout, with the cell at `(x, y)` at index `[[y - top, x - left]]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid's bounds don't match the number of its cells.
+    pub fn as_array_view(&self) -> ArrayView2<'_, T> {
+        ArrayView2::from_shape(shape(self).f(), &self.cells).unwrap()
+    }
+
+    /// Mutable version of [`as_array_view`](VecGrid::as_array_view).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid's bounds don't match the number of its cells.
+    pub fn as_array_view_mut(&mut self) -> ArrayViewMut2<'_, T> {
+        ArrayViewMut2::from_shape(shape(self).f(), &mut self.cells).unwrap()
+    }
+}
+
+/// Converts the grid into an array of shape `(height, width)`, without
+/// copying its cells. The grid's position is lost, so the cell at `(x, y)`
+/// ends up at index `[[y - top, x - left]]`.
+///
+/// # Panics
+///
+/// Panics if the grid's bounds don't match the number of its cells.
+impl<T> From<VecGrid<T>> for Array2<T> {
+    fn from(grid: VecGrid<T>) -> Self {
+        Array2::from_shape_vec(shape(&grid), grid.cells).unwrap()
+    }
+}
+
+/// Converts the grid into an array of shape `(height, width)` in Fortran
+/// layout, without copying its cells. The grid's position is lost, so the
+/// cell at `(x, y)` ends up at index `[[y - top, x - left]]`.
+///
+/// # Panics
+///
+/// Panics if the grid's bounds don't match the number of its cells.
+impl<T> From<VecGrid<T, ColumnMajor>> for Array2<T> {
+    fn from(grid: VecGrid<T, ColumnMajor>) -> Self {
+        Array2::from_shape_vec(shape(&grid).f(), grid.cells).unwrap()
+    }
+}
+
+/// Converts an array of shape `(height, width)` into a grid with its origin
+/// at `(0, 0)`, so the element at `[[y, x]]` becomes the cell at `(x, y)`.
+///
+/// Fails with [`GridError::TooLarge`] if the array's dimensions don't fit in
+/// the grid's `i32` coordinates.
+impl<T> TryFrom<Array2<T>> for VecGrid<T> {
+    type Error = GridError;
+
+    fn try_from(array: Array2<T>) -> Result<Self, Self::Error> {
+        let (height, width) = array.dim();
+        let too_large = || GridError::TooLarge {
+            width: u32::try_from(width).unwrap_or(u32::MAX),
+            height: u32::try_from(height).unwrap_or(u32::MAX),
+        };
+        let dimensions = Coord::new(
+            i32::try_from(width).map_err(|_| too_large())?,
+            i32::try_from(height).map_err(|_| too_large())?,
+        );
+        dimensions
+            .x
+            .checked_mul(dimensions.y)
+            .ok_or_else(too_large)?;
+        // Iterating by value visits the elements in logical order, whatever
+        // the array's memory layout, which is the grid's row-major order.
+        VecGrid::from_raw_parts(Rect::new(dimensions), array.into_iter().collect())
+    }
+}
+
+/// Returns the `(height, width)` shape of the grid's array, treating inverted
+/// bounds as empty.
+fn shape<T, L: Layout>(grid: &VecGrid<T, L>) -> (usize, usize) {
+    let dimensions = grid.bounds.dimensions();
+    (dimensions.y.max(0) as usize, dimensions.x.max(0) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use ndarray::{array, s};
+
+    use super::*;
+    use crate::{grid::Grid, layout::RowMajor};
+
+    fn grid() -> VecGrid<i32> {
+        VecGrid::with_generator(Rect::with_corners((-1, 2), (2, 4)), |coord: Coord| {
+            coord.x * 10 + coord.y
+        })
+    }
+
+    #[test]
+    fn round_trips_through_arrays() {
+        let array = Array2::from(grid());
+        assert_eq!(array, array![[-8, 2, 12], [-7, 3, 13]]);
+        let round_trip = VecGrid::try_from(array).unwrap();
+        assert_eq!(round_trip.bounds, Rect::new((3, 2)));
+        assert_eq!(round_trip.cells, grid().cells);
+
+        let column_major = grid().into_layout(ColumnMajor);
+        assert_eq!(Array2::from(column_major), Array2::from(grid()));
+    }
+
+    #[test]
+    fn maps_coordinates_to_rows_and_columns() {
+        let grid = grid();
+        let view = grid.as_array_view();
+        assert_eq!(view.dim(), (2, 3));
+        for (coord, value) in grid.iter() {
+            let index = [(coord.y - 2) as usize, (coord.x + 1) as usize];
+            assert_eq!(view[index], *value);
+        }
+
+        let array = array![[1, 2, 3], [4, 5, 6]];
+        let grid = VecGrid::try_from(array.clone()).unwrap();
+        for (coord, value) in grid.iter() {
+            assert_eq!(array[[coord.y as usize, coord.x as usize]], *value);
+        }
+
+        let column_major = grid.clone().into_layout(ColumnMajor);
+        assert_eq!(column_major.as_array_view(), array);
+        assert_eq!(column_major.get((2, 1)), Some(&6));
+    }
+
+    #[test]
+    fn edits_cells_through_views() {
+        let mut grid = grid();
+        grid.as_array_view_mut()[[1, 0]] = 0;
+        assert_eq!(grid.get((-1, 3)), Some(&0));
+
+        let mut column_major = grid.into_layout(ColumnMajor);
+        column_major.as_array_view_mut().row_mut(0).fill(1);
+        assert_eq!(
+            column_major.into_layout(RowMajor).cells,
+            [1, 1, 1, 0, 3, 13]
+        );
+    }
+
+    #[test]
+    fn converts_arrays_in_any_layout() {
+        let array = array![[1, 2, 3], [4, 5, 6]];
+        let transposed = array.t().to_owned();
+        let fortran = Array2::from_shape_vec((2, 3).f(), alloc::vec![1, 4, 2, 5, 3, 6]).unwrap();
+        let mut flipped = array.clone();
+        flipped.invert_axis(ndarray::Axis(1));
+        let sliced = array![[0, 0, 0], [1, 2, 3], [4, 5, 6]].slice_move(s![1.., ..]);
+
+        let cells = |array| VecGrid::try_from(array).unwrap().cells;
+        assert_eq!(cells(transposed), [1, 4, 2, 5, 3, 6]);
+        assert_eq!(cells(fortran), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(cells(flipped), [3, 2, 1, 6, 5, 4]);
+        assert_eq!(cells(sliced), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(cells(Array2::<i32>::zeros((0, 4))), Vec::<i32>::new());
+    }
+}
@@ -0,0 +1,236 @@
+use core::{array, fmt};
+
+use crate::{
+    coord::Coord,
+    grid::{Grid, GridMut, IterCell, IterCellMut},
+    patterns::Rect,
+};
+
+/// A fixed-size `W`×`H` grid stored inline, without any allocation, for small
+/// boards like chess or match-3.
+///
+/// The grid always covers `Rect::new((W, H))`, so `(0, 0)` is the first cell.
+/// Coordinates are still runtime values, and are bounds checked like those of
+/// a [`VecGrid`](crate::VecGrid).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArrayGrid<T, const W: usize, const H: usize> {
+    /// The cells, indexed as `cells[y][x]`.
+    pub cells: [[T; W]; H],
+}
+
+impl<T, const W: usize, const H: usize> Grid<T> for ArrayGrid<T, W, H> {
+    fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        let (x, y) = Self::index(coord.into())?;
+        Some(&self.cells[y][x])
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::new((W as i32, H as i32))
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+    {
+        ArrayGrid::iter(self)
+    }
+}
+
+impl<T, const W: usize, const H: usize> GridMut<T> for ArrayGrid<T, W, H> {
+    fn get_mut<C: Into<Coord>>(&mut self, coord: C) -> Option<&mut T> {
+        let (x, y) = Self::index(coord.into())?;
+        Some(&mut self.cells[y][x])
+    }
+
+    fn copy<C1, C2>(&mut self, src: C1, dest: C2) -> bool
+    where
+        T: Copy,
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        match (Self::index(src.into()), Self::index(dest.into())) {
+            (Some((src_x, src_y)), Some((dest_x, dest_y))) => {
+                self.cells[dest_y][dest_x] = self.cells[src_y][src_x];
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn swap<C1, C2>(&mut self, coord1: C1, coord2: C2) -> bool
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        match (Self::index(coord1.into()), Self::index(coord2.into())) {
+            (Some((x1, y1)), Some((x2, y2))) => {
+                self.cells.as_flattened_mut().swap(x1 + y1 * W, x2 + y2 * W);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn mov(&mut self, src: Coord, dest: Coord) -> Option<T>
+    where
+        T: Default,
+    {
+        // Make sure both coordinates are in bounds before mutating things.
+        if !(self.contains(src) && self.contains(dest)) {
+            return None;
+        }
+        let src_value = self.take(src).unwrap();
+        self.replace(dest, src_value)
+    }
+}
+
+impl<T, const W: usize, const H: usize> ArrayGrid<T, W, H> {
+    /// Constructs a grid by calling `f` with the coordinate of each cell, in
+    /// row-major order.
+    pub fn from_fn<F>(mut f: F) -> Self
+    where
+        F: FnMut(Coord) -> T,
+    {
+        Self {
+            cells: array::from_fn(|y| array::from_fn(|x| f(Coord::new(x as i32, y as i32)))),
+        }
+    }
+
+    /// Constructs a grid with every cell set to a copy of `value`.
+    pub fn filled(value: T) -> Self
+    where
+        T: Copy,
+    {
+        Self {
+            cells: [[value; W]; H],
+        }
+    }
+
+    /// Returns an iterator over all cells in the grid, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = IterCell<'_, T>> {
+        self.cells.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .map(move |(x, cell)| (Coord::new(x as i32, y as i32), cell))
+        })
+    }
+
+    /// Returns a mutable iterator over all cells in the grid, in row-major
+    /// order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        self.cells.iter_mut().enumerate().flat_map(|(y, row)| {
+            row.iter_mut()
+                .enumerate()
+                .map(move |(x, cell)| (Coord::new(x as i32, y as i32), cell))
+        })
+    }
+
+    /// Returns a grid of the same size, with each cell mapped through `f`.
+    pub fn map<U, F>(&self, mut f: F) -> ArrayGrid<U, W, H>
+    where
+        F: FnMut(&T) -> U,
+    {
+        ArrayGrid {
+            cells: array::from_fn(|y| array::from_fn(|x| f(&self.cells[y][x]))),
+        }
+    }
+
+    /// Consumes the grid, returning its rows.
+    pub fn into_inner(self) -> [[T; W]; H] {
+        self.cells
+    }
+
+    /// Returns the cells as one row-major slice.
+    pub fn as_slice(&self) -> &[T] {
+        self.cells.as_flattened()
+    }
+
+    /// Returns the cells as one row-major mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.cells.as_flattened_mut()
+    }
+
+    /// Converts `coord` into column and row indices, if it's in bounds.
+    fn index(coord: Coord) -> Option<(usize, usize)> {
+        let (x, y) = (coord.x as usize, coord.y as usize);
+        if coord.x < 0 || coord.y < 0 || x >= W || y >= H {
+            return None;
+        }
+        Some((x, y))
+    }
+}
+
+impl<T: Default, const W: usize, const H: usize> Default for ArrayGrid<T, W, H> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl<T, const W: usize, const H: usize> From<[[T; W]; H]> for ArrayGrid<T, W, H> {
+    fn from(cells: [[T; W]; H]) -> Self {
+        Self { cells }
+    }
+}
+
+impl<T, const W: usize, const H: usize> From<ArrayGrid<T, W, H>> for [[T; W]; H] {
+    fn from(grid: ArrayGrid<T, W, H>) -> Self {
+        grid.cells
+    }
+}
+
+/// Writes each row of cells on its own line, with no separators.
+impl<T: fmt::Display, const W: usize, const H: usize> fmt::Display for ArrayGrid<T, W, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.cells.iter() {
+            for cell in row.iter() {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_fn_and_get() {
+        let grid = ArrayGrid::<_, 3, 2>::from_fn(|coord| coord.x + coord.y * 10);
+        assert_eq!(grid.cells, [[0, 1, 2], [10, 11, 12]]);
+        assert_eq!(grid.get((2, 1)), Some(&12));
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.get((0, -1)), None);
+        assert_eq!(grid.bounds(), Rect::new((3, 2)));
+        assert_eq!(
+            grid.selection_iter(vec![Coord::new(1, 1), Coord::new(0, 2)])
+                .map(|result| result.map(|(_, &cell)| cell).ok())
+                .collect::<Vec<_>>(),
+            vec![Some(11), None]
+        );
+        assert_eq!(grid.iter().map(|(_, &cell)| cell).sum::<i32>(), 36);
+    }
+
+    #[test]
+    fn writes() {
+        let mut grid = ArrayGrid::from([[1, 2], [3, 4], [5, 6]]);
+        assert!(grid.swap((0, 0), (1, 2)));
+        assert!(grid.copy((1, 0), (0, 1)));
+        assert!(!grid.copy((1, 0), (2, 1)));
+        assert_eq!(grid.mov(Coord::new(1, 1), Coord::new(0, 2)), Some(5));
+        assert!(grid.set((1, 0), 9));
+        for (coord, cell) in grid.iter_mut().filter(|(coord, _)| coord.y == 2) {
+            *cell += coord.x * 100;
+        }
+        assert_eq!(grid.into_inner(), [[6, 9], [2, 0], [4, 101]]);
+    }
+
+    #[test]
+    fn map_and_display() {
+        let board = ArrayGrid::<bool, 4, 2>::from_fn(|coord| (coord.x + coord.y) % 2 == 0);
+        let text = board.map(|&dark| if dark { '#' } else { '.' });
+        assert_eq!(text.to_string(), "#.#.\n.#.#\n");
+        assert_eq!(ArrayGrid::<u8, 2, 2>::default().as_slice(), &[0; 4]);
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use crate::{patterns::Rect, vecgrid::VecGrid};
+
+/// How [`VecGrid::from_ascii_map`] treats lines shorter than the longest line
+/// of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaggedLines {
+    /// Short lines are padded with the default cell. Without a default, this
+    /// behaves like `Reject`.
+    Pad,
+    /// Every line must be as long as the first, or the map is rejected.
+    Reject,
+}
+
+/// An error produced while loading a map with
+/// [`VecGrid::from_ascii_map`](crate::VecGrid::from_ascii_map). Lines and
+/// columns are numbered from 1, the way text editors show them.
+#[derive(Debug, PartialEq, Eq)]
+pub enum AsciiMapError {
+    /// A character has no entry in the legend, and there's no default.
+    UnknownChar { c: char, line: usize, column: usize },
+    /// A line's length differs from the width of the map.
+    RaggedLine {
+        line: usize,
+        expected: usize,
+        received: usize,
+    },
+}
+
+impl<T: Clone> VecGrid<T> {
+    /// Builds a grid from a text map, one row per line, looking up each
+    /// character in `legend`. The grid's origin is `(0, 0)`, and lengths are
+    /// counted in characters.
+    ///
+    /// Characters missing from the legend become `default`, or produce an
+    /// [`AsciiMapError::UnknownChar`] if there's no default. How lines of
+    /// different lengths are handled depends on `ragged`.
+    pub fn from_ascii_map(
+        map: &str,
+        legend: &HashMap<char, T>,
+        default: Option<T>,
+        ragged: RaggedLines,
+    ) -> Result<VecGrid<T>, AsciiMapError> {
+        let lines = map.lines().collect::<Vec<_>>();
+        let lengths = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .collect::<Vec<_>>();
+        let width = match (ragged, &default) {
+            (RaggedLines::Pad, Some(_)) => lengths.iter().copied().max().unwrap_or(0),
+            _ => lengths.first().copied().unwrap_or(0),
+        };
+
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (y, (line, &len)) in lines.iter().zip(lengths.iter()).enumerate() {
+            if len != width && (ragged == RaggedLines::Reject || default.is_none()) {
+                return Err(AsciiMapError::RaggedLine {
+                    line: y + 1,
+                    expected: width,
+                    received: len,
+                });
+            }
+            for (x, c) in line.chars().enumerate() {
+                let cell =
+                    legend
+                        .get(&c)
+                        .or(default.as_ref())
+                        .ok_or(AsciiMapError::UnknownChar {
+                            c,
+                            line: y + 1,
+                            column: x + 1,
+                        })?;
+                cells.push(cell.clone());
+            }
+            if let Some(default) = &default {
+                cells.extend((len..width).map(|_| default.clone()));
+            }
+        }
+        let bounds = Rect::new((width as i32, lines.len() as i32));
+        Ok(VecGrid::from_raw_parts(bounds, cells).unwrap())
+    }
+}
+
+impl<T> VecGrid<T> {
+    /// Writes the grid as a text map, one line per row with each line ending
+    /// in a newline, calling `legend` to get the character of each cell. Rows
+    /// are written from the top down, or from the bottom up if `y_up` is true,
+    /// for code that treats +y as up.
+    pub fn to_ascii_map<F>(&self, y_up: bool, legend: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        let mut map =
+            String::with_capacity(self.cells.len() + self.bounds.height().max(0) as usize);
+        for (_, row) in self.rows_oriented(y_up) {
+            map.extend(row.iter().map(&legend));
+            map.push('\n');
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Tile {
+        Wall,
+        Floor,
+        Door,
+    }
+
+    fn legend() -> HashMap<char, Tile> {
+        vec![('#', Tile::Wall), ('.', Tile::Floor), ('+', Tile::Door)]
+            .into_iter()
+            .collect()
+    }
+
+    fn symbol(tile: &Tile) -> char {
+        match tile {
+            Tile::Wall => '#',
+            Tile::Floor => '.',
+            Tile::Door => '+',
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let map = "#####\n#...+\n#####\n";
+        let level = VecGrid::from_ascii_map(map, &legend(), None, RaggedLines::Reject).unwrap();
+        assert_eq!(level.bounds, Rect::new((5, 3)));
+        assert_eq!(level.get((4, 1)), Some(&Tile::Door));
+        assert_eq!(level.to_ascii_map(false, symbol), map);
+
+        let empty = VecGrid::from_ascii_map("", &legend(), None, RaggedLines::Reject).unwrap();
+        assert_eq!(empty.bounds, Rect::new((0, 0)));
+    }
+
+    #[test]
+    fn unknown_chars() {
+        let map = "###\n#@#\n###";
+        assert_eq!(
+            VecGrid::from_ascii_map(map, &legend(), None, RaggedLines::Reject),
+            Err(AsciiMapError::UnknownChar {
+                c: '@',
+                line: 2,
+                column: 2,
+            })
+        );
+        let level = VecGrid::from_ascii_map(map, &legend(), Some(Tile::Floor), RaggedLines::Reject)
+            .unwrap();
+        assert_eq!(level.get((1, 1)), Some(&Tile::Floor));
+    }
+
+    #[test]
+    fn ragged_lines() {
+        let map = "###\n#.\n####\n";
+        assert_eq!(
+            VecGrid::from_ascii_map(map, &legend(), Some(Tile::Wall), RaggedLines::Reject),
+            Err(AsciiMapError::RaggedLine {
+                line: 2,
+                expected: 3,
+                received: 2,
+            })
+        );
+        // Without a default there's nothing to pad with.
+        assert_eq!(
+            VecGrid::from_ascii_map(map, &legend(), None, RaggedLines::Pad),
+            Err(AsciiMapError::RaggedLine {
+                line: 2,
+                expected: 3,
+                received: 2,
+            })
+        );
+        let level =
+            VecGrid::from_ascii_map(map, &legend(), Some(Tile::Wall), RaggedLines::Pad).unwrap();
+        assert_eq!(level.to_ascii_map(false, symbol), "####\n#.##\n####\n");
+    }
+
+    #[test]
+    fn y_up_maps() {
+        let map = "#.\n.+\n##\n";
+        let level = VecGrid::from_ascii_map(map, &legend(), None, RaggedLines::Reject).unwrap();
+        assert_eq!(level.to_ascii_map(true, symbol), "##\n.+\n#.\n");
+        assert_eq!(level.get((1, 1)), Some(&Tile::Door));
+    }
+}
@@ -0,0 +1,683 @@
+//! Runners for Life-like cellular automata over grids of `bool`, where `true`
+//! marks a live cell.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{fmt, str::FromStr};
+
+use crate::{
+    coord::Coord,
+    grid::{Grid, GridMut},
+    patterns::{Connectivity, Neighborhood, Rect},
+    vecgrid::VecGrid,
+};
+
+/// A Life-like rule, which decides whether a cell is alive in the next
+/// generation from whether it's alive now and how many of its eight neighbors
+/// are.
+///
+/// Rules are usually written in B/S notation, listing the neighbor counts that
+/// cause a dead cell to be born and those that let a live cell survive. For
+/// example, Conway's Game of Life is `"B3/S23"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LifeRule {
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+impl LifeRule {
+    /// Conway's Game of Life, B3/S23.
+    pub const CONWAY: Self = Self::new(&[3], &[2, 3]);
+    /// HighLife, B36/S23, which adds a replicator to Conway's rules.
+    pub const HIGH_LIFE: Self = Self::new(&[3, 6], &[2, 3]);
+
+    /// Constructs a rule from the neighbor counts causing birth and those
+    /// allowing survival. Counts above 8 are ignored.
+    pub const fn new(birth: &[u8], survival: &[u8]) -> Self {
+        Self {
+            birth: Self::counts_to_table(birth),
+            survival: Self::counts_to_table(survival),
+        }
+    }
+
+    /// Returns whether a cell is alive in the next generation.
+    pub fn next_state(&self, alive: bool, live_neighbors: usize) -> bool {
+        let table = if alive { &self.survival } else { &self.birth };
+        table.get(live_neighbors).copied().unwrap_or(false)
+    }
+
+    const fn counts_to_table(counts: &[u8]) -> [bool; 9] {
+        let mut table = [false; 9];
+        let mut i = 0;
+        while i < counts.len() {
+            if counts[i] <= 8 {
+                table[counts[i] as usize] = true;
+            }
+            i += 1;
+        }
+        table
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseLifeRuleError {
+    /// The rule isn't of the form `B<counts>/S<counts>`.
+    InvalidFormat,
+    /// A neighbor count isn't a digit from 0 to 8.
+    InvalidCount(char),
+}
+
+impl FromStr for LifeRule {
+    type Err = ParseLifeRuleError;
+
+    /// Parses a rule in B/S notation, such as `"B36/S23"`. The letters are
+    /// case-insensitive and either list of counts may be empty.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (birth, survival) = s.split_once('/').ok_or(ParseLifeRuleError::InvalidFormat)?;
+        let parse_counts = |counts: &str, prefix: char| {
+            let mut chars = counts.chars();
+            if !chars
+                .next()
+                .is_some_and(|c| c.eq_ignore_ascii_case(&prefix))
+            {
+                return Err(ParseLifeRuleError::InvalidFormat);
+            }
+            let mut table = [false; 9];
+            for c in chars {
+                match c.to_digit(10) {
+                    Some(count) if count <= 8 => table[count as usize] = true,
+                    _ => return Err(ParseLifeRuleError::InvalidCount(c)),
+                }
+            }
+            Ok(table)
+        };
+        Ok(Self {
+            birth: parse_counts(birth, 'B')?,
+            survival: parse_counts(survival, 'S')?,
+        })
+    }
+}
+
+impl fmt::Display for LifeRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = |table: &[bool; 9]| {
+            (0..9)
+                .filter(|&count| table[count])
+                .map(|count| count.to_string())
+                .collect::<String>()
+        };
+        write!(f, "B{}/S{}", counts(&self.birth), counts(&self.survival))
+    }
+}
+
+/// What the cells just past the edge of the grid count as when counting
+/// neighbors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeBehavior {
+    /// Cells past the edge are always dead.
+    Dead,
+    /// Cells past the edge are always alive.
+    Alive,
+    /// The grid wraps around, so cells past one edge are the cells on the
+    /// opposite edge.
+    Wrap,
+}
+
+impl VecGrid<bool> {
+    /// Advances the grid by one generation of `rule`, using `scratch` as the
+    /// buffer for the next generation. Just like
+    /// [`step_automaton`](VecGrid::step_automaton), `scratch` is left holding
+    /// the previous generation and no allocation takes place once its bounds
+    /// match.
+    pub fn step_life(&mut self, scratch: &mut VecGrid<bool>, rule: &LifeRule, edges: EdgeBehavior) {
+        self.step_automaton(scratch, |coord, &alive, grid| {
+            rule.next_state(alive, grid.live_neighbors(coord, edges))
+        });
+    }
+
+    /// Counts the live cells in the Moore neighborhood of `coord`, treating
+    /// cells past the edge of the grid according to `edges`.
+    pub fn live_neighbors(&self, coord: Coord, edges: EdgeBehavior) -> usize {
+        Connectivity::Eight
+            .neighbors(coord)
+            .filter(|&neighbor| match self.get(neighbor) {
+                Some(&alive) => alive,
+                None => match edges {
+                    EdgeBehavior::Dead => false,
+                    EdgeBehavior::Alive => true,
+                    EdgeBehavior::Wrap => {
+                        self.wrap_coord(neighbor).and_then(|coord| self.get(coord)) == Some(&true)
+                    }
+                },
+            })
+            .count()
+    }
+}
+
+impl<T> VecGrid<T> {
+    /// Counts, for every cell at once, how many of its neighbors satisfy
+    /// `predicate`, treating cells past the edge of the grid according to
+    /// `edges`. `neighborhood` can be a [`Connectivity`] or any
+    /// [`Neighborhood`]; counts past 255 saturate.
+    ///
+    /// This evaluates `predicate` once per cell and then sums shifted copies
+    /// of the matches in a few sequential passes, rather than looking up each
+    /// neighbor of each cell, which makes it much faster than calling
+    /// [`live_neighbors`](VecGrid::live_neighbors) for every cell of a large
+    /// grid: about three times faster on a 1024×1024 board, as measured by
+    /// `cargo run --release --example life -- --bench`.
+    pub fn neighbor_counts<F, N>(
+        &self,
+        predicate: F,
+        neighborhood: N,
+        edges: EdgeBehavior,
+    ) -> VecGrid<u8>
+    where
+        F: Fn(&T) -> bool,
+        N: AsRef<Neighborhood>,
+    {
+        if self.cells.is_empty() {
+            return VecGrid::from_raw_parts(self.bounds, Vec::new()).unwrap();
+        }
+        let neighborhood = neighborhood.as_ref();
+        let width = self.bounds.width() as usize;
+        let height = self.bounds.height() as usize;
+
+        // The matches, with a border as wide as the neighborhood reaches
+        // holding whatever lies past each edge, so that every neighbor of a
+        // cell can be read without bounds checks.
+        let reach = neighborhood.reach() as usize;
+        let padded_width = width + 2 * reach;
+        let padded_height = height + 2 * reach;
+        let border = match edges {
+            EdgeBehavior::Alive => 1,
+            EdgeBehavior::Dead | EdgeBehavior::Wrap => 0,
+        };
+        let mut padded = vec![border; padded_width * padded_height];
+        for (y, row) in self.cells.chunks_exact(width).enumerate() {
+            let start = (y + reach) * padded_width + reach;
+            for (padded, cell) in padded[start..start + width].iter_mut().zip(row) {
+                *padded = predicate(cell) as u8;
+            }
+        }
+        if edges == EdgeBehavior::Wrap {
+            // Each border cell copies the interior cell it wraps onto, which
+            // may be more than a whole grid away when the neighborhood
+            // reaches further than the grid is wide or high.
+            let wrap = |padded: usize, len: usize| {
+                (padded as isize - reach as isize).rem_euclid(len as isize) as usize + reach
+            };
+            for y in 0..padded_height {
+                let interior_row = (reach..reach + height).contains(&y);
+                for x in 0..padded_width {
+                    if interior_row && (reach..reach + width).contains(&x) {
+                        continue;
+                    }
+                    padded[y * padded_width + x] =
+                        padded[wrap(y, height) * padded_width + wrap(x, width)];
+                }
+            }
+        }
+
+        let mut counts = Vec::with_capacity(width * height);
+        if neighborhood.is_moore() {
+            // Sum each cell with its left and right neighbors, then sum
+            // those triples vertically, leaving out the cell itself. A Moore
+            // neighborhood of reach 1 covers at most 9 cells, so these sums
+            // can't overflow a `u8` and don't need to saturate.
+            debug_assert!(reach == 1);
+            let triples = padded
+                .chunks_exact(padded_width)
+                .flat_map(|row| row.windows(3).map(|window| window.iter().sum::<u8>()))
+                .collect::<Vec<_>>();
+            for y in 1..=height {
+                let row = y * width;
+                counts.extend((0..width).map(|x| {
+                    triples[row - width + x] + triples[row + x] + triples[row + width + x]
+                        - padded[y * padded_width + x + 1]
+                }));
+            }
+        } else {
+            let shifts = neighborhood
+                .offsets()
+                .iter()
+                .map(|offset| offset.y as isize * padded_width as isize + offset.x as isize)
+                .collect::<Vec<_>>();
+            for y in 0..height {
+                let row = (y + reach) * padded_width + reach;
+                counts.extend((0..width).map(|x| {
+                    let center = (row + x) as isize;
+                    shifts.iter().fold(0u8, |count, &shift| {
+                        count.saturating_add(padded[(center + shift) as usize])
+                    })
+                }));
+            }
+        }
+        VecGrid::from_raw_parts(self.bounds, counts).unwrap()
+    }
+}
+
+/// An error produced while parsing a pattern in RLE format.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RleParseError {
+    /// The `x = <width>, y = <height>` header line is missing or malformed.
+    InvalidHeader,
+    /// The header declares a rule which isn't in B/S notation.
+    InvalidRule(ParseLifeRuleError),
+    /// The body contains something other than run counts, `b`, `o`, `$` and
+    /// whitespace.
+    InvalidTag(char),
+    /// A live cell lies outside of the dimensions declared in the header.
+    OutOfBounds(Coord),
+    /// The body ended without a `!`.
+    MissingTerminator,
+}
+
+/// The longest line written by [`VecGrid::to_rle`], as recommended by the
+/// format.
+const RLE_LINE_LENGTH: usize = 70;
+
+impl VecGrid<bool> {
+    /// Parses a pattern in the run length encoded format used to share Life
+    /// patterns, along with the rule declared in its header, if any.
+    ///
+    /// Lines starting with `#` are comments. The header is followed by the
+    /// body, where `b` is a dead cell, `o` a live one and `$` ends a row, each
+    /// optionally preceded by a run count, up to a closing `!`. Rows may end
+    /// early, leaving the rest of the row dead. The grid covers the declared
+    /// dimensions, with its origin at `(0, 0)`.
+    pub fn from_rle(s: &str) -> Result<(VecGrid<bool>, Option<LifeRule>), RleParseError> {
+        let mut lines = s.lines().filter(|line| !line.trim_start().starts_with('#'));
+        let (dimensions, rule) = Self::parse_rle_header(lines.next().unwrap_or(""))?;
+        let mut grid = VecGrid::filled(Rect::new(dimensions), false);
+
+        let mut cursor = Coord::new(0, 0);
+        let mut count: Option<i32> = None;
+        for c in lines.flat_map(str::chars) {
+            if let Some(digit) = c.to_digit(10) {
+                let run = count.unwrap_or(0).saturating_mul(10);
+                count = Some(run.saturating_add(digit as i32));
+                continue;
+            }
+            if c.is_whitespace() {
+                continue;
+            }
+            let run = count.take().unwrap_or(1);
+            match c {
+                'b' => cursor.x = cursor.x.saturating_add(run),
+                'o' => {
+                    for _ in 0..run {
+                        if !grid.set(cursor, true) {
+                            return Err(RleParseError::OutOfBounds(cursor));
+                        }
+                        cursor.x += 1;
+                    }
+                }
+                '$' => cursor = Coord::new(0, cursor.y.saturating_add(run)),
+                '!' => return Ok((grid, rule)),
+                c => return Err(RleParseError::InvalidTag(c)),
+            }
+        }
+        Err(RleParseError::MissingTerminator)
+    }
+
+    /// Writes the grid in RLE format, declaring `rule` in the header if one is
+    /// given. See [`from_rle`](VecGrid::from_rle).
+    ///
+    /// Dead cells at the end of a row and empty rows at the bottom are left
+    /// out, and the body is wrapped to lines of at most 70 characters.
+    pub fn to_rle(&self, rule: Option<&LifeRule>) -> String {
+        let dimensions = self.bounds.dimensions();
+        let mut rle = format!("x = {}, y = {}", dimensions.x, dimensions.y);
+        if let Some(rule) = rule {
+            rle += &format!(", rule = {}", rule);
+        }
+        rle.push('\n');
+
+        let run = |count: usize, tag: char| match count {
+            1 => tag.to_string(),
+            count => format!("{}{}", count, tag),
+        };
+        let mut tokens = Vec::new();
+        let mut last_row = 0;
+        for (y, row) in self.rows().enumerate() {
+            let len = match row.iter().rposition(|&alive| alive) {
+                Some(last) => last + 1,
+                None => continue,
+            };
+            if y > last_row {
+                tokens.push(run(y - last_row, '$'));
+            }
+            last_row = y;
+            let mut x = 0;
+            while x < len {
+                let alive = row[x];
+                let count = row[x..len]
+                    .iter()
+                    .take_while(|&&cell| cell == alive)
+                    .count();
+                tokens.push(run(count, if alive { 'o' } else { 'b' }));
+                x += count;
+            }
+        }
+        tokens.push("!".to_string());
+
+        let mut line_len = 0;
+        for token in tokens {
+            if line_len + token.len() > RLE_LINE_LENGTH {
+                rle.push('\n');
+                line_len = 0;
+            }
+            line_len += token.len();
+            rle += &token;
+        }
+        rle.push('\n');
+        rle
+    }
+
+    fn parse_rle_header(line: &str) -> Result<(Coord, Option<LifeRule>), RleParseError> {
+        let (mut width, mut height, mut rule) = (None, None, None);
+        for field in line.split(',') {
+            let (key, value) = field.split_once('=').ok_or(RleParseError::InvalidHeader)?;
+            let value = value.trim();
+            match key.trim() {
+                "x" => width = value.parse::<i32>().ok(),
+                "y" => height = value.parse::<i32>().ok(),
+                "rule" => rule = Some(value.parse().map_err(RleParseError::InvalidRule)?),
+                _ => return Err(RleParseError::InvalidHeader),
+            }
+        }
+        match (width, height) {
+            (Some(width), Some(height)) if width >= 0 && height >= 0 => {
+                Ok((Coord::new(width, height), rule))
+            }
+            _ => Err(RleParseError::InvalidHeader),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::GridMut, patterns::Rect};
+
+    #[test]
+    fn parse_rule() {
+        assert_eq!("B3/S23".parse(), Ok(LifeRule::CONWAY));
+        assert_eq!("b36/s23".parse(), Ok(LifeRule::HIGH_LIFE));
+        assert_eq!("B/S".parse(), Ok(LifeRule::new(&[], &[])));
+        assert_eq!(LifeRule::HIGH_LIFE.to_string(), "B36/S23");
+
+        assert_eq!(
+            "B3S23".parse::<LifeRule>(),
+            Err(ParseLifeRuleError::InvalidFormat)
+        );
+        assert_eq!(
+            "S23/B3".parse::<LifeRule>(),
+            Err(ParseLifeRuleError::InvalidFormat)
+        );
+        assert_eq!(
+            "B39/S23".parse::<LifeRule>(),
+            Err(ParseLifeRuleError::InvalidCount('9'))
+        );
+        assert_eq!(
+            "B3/S2,3".parse::<LifeRule>(),
+            Err(ParseLifeRuleError::InvalidCount(','))
+        );
+    }
+
+    #[test]
+    fn glider_wraps() {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mut grid = VecGrid::filled(Rect::new((6, 6)), false);
+        for &coord in glider.iter() {
+            grid.set(coord, true);
+        }
+        let original = grid.clone();
+        let mut scratch = VecGrid::new(Rect::new((0, 0)));
+
+        // A glider moves by one cell diagonally every four generations.
+        for _ in 0..4 {
+            grid.step_life(&mut scratch, &LifeRule::CONWAY, EdgeBehavior::Wrap);
+        }
+        let mut expected = original.clone();
+        expected.shift((1, 1), crate::ShiftMode::Wrap);
+        assert_eq!(grid, expected);
+
+        // After crossing the seam it ends up back where it started.
+        for _ in 4..24 {
+            grid.step_life(&mut scratch, &LifeRule::CONWAY, EdgeBehavior::Wrap);
+        }
+        assert_eq!(grid, original);
+    }
+
+    #[test]
+    fn edge_behavior() {
+        let rule = LifeRule::new(&[5], &[]);
+        let mut scratch = VecGrid::new(Rect::new((0, 0)));
+        let mut grid = VecGrid::filled(Rect::new((1, 1)), false);
+        grid.step_life(&mut scratch, &rule, EdgeBehavior::Dead);
+        assert_eq!(grid.cells, vec![false]);
+
+        // A corner cell has five neighbors past the edge.
+        let mut grid = VecGrid::filled(Rect::new((2, 2)), false);
+        grid.step_life(&mut scratch, &rule, EdgeBehavior::Alive);
+        assert_eq!(grid.cells, vec![true; 4]);
+    }
+
+    const GOSPER_GLIDER_GUN: &str = "\
+#N Gosper glider gun
+#O Bill Gosper
+#C A true period 30 glider gun.
+#C The first known gun and the first known finite pattern with unbounded growth.
+x = 36, y = 9, rule = B3/S23
+24bo11b$22bobo11b$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o14b$2o8b
+o3bob2o4bobo11b$10bo5bo7bo11b$11bo3bo20b$12b2o22b!
+";
+
+    const GLIDER: &str = "\
+#N Glider
+#C The smallest, most common, and first discovered spaceship.
+x = 3, y = 3
+bob$2bo$3o!
+";
+
+    #[test]
+    fn parse_rle() {
+        let (glider, rule) = VecGrid::from_rle(GLIDER).unwrap();
+        assert_eq!(rule, None);
+        assert_eq!(
+            glider.coords_where(|_, &alive| alive).collect::<Vec<_>>(),
+            vec![
+                Coord::new(1, 0),
+                Coord::new(2, 1),
+                Coord::new(0, 2),
+                Coord::new(1, 2),
+                Coord::new(2, 2)
+            ]
+        );
+        assert_eq!(glider.to_rle(None), "x = 3, y = 3\nbo$2bo$3o!\n");
+
+        let (gun, rule) = VecGrid::from_rle(GOSPER_GLIDER_GUN).unwrap();
+        assert_eq!(rule, Some(LifeRule::CONWAY));
+        assert_eq!(gun.bounds, Rect::new((36, 9)));
+        assert_eq!(gun.cells.iter().filter(|&&alive| alive).count(), 36);
+
+        // Every 30 generations the gun returns to its starting state, having
+        // fired one more glider.
+        let mut board = VecGrid::filled(Rect::new((40, 20)), false);
+        board.embed(gun.clone(), (1, 1));
+        let mut scratch = VecGrid::new(Rect::new((0, 0)));
+        for _ in 0..30 {
+            board.step_life(&mut scratch, &LifeRule::CONWAY, EdgeBehavior::Dead);
+        }
+        assert_eq!(board.cells.iter().filter(|&&alive| alive).count(), 41);
+        let mut region = board.clone();
+        region.crop(gun.bounds.translate((1, 1))).unwrap();
+        region.set_origin((0, 0));
+        assert_eq!(region, gun);
+    }
+
+    #[test]
+    fn rle_round_trips() {
+        let (gun, _) = VecGrid::from_rle(GOSPER_GLIDER_GUN).unwrap();
+        let rle = gun.to_rle(Some(&LifeRule::CONWAY));
+        assert!(rle.lines().all(|line| line.len() <= 70));
+        assert_eq!(VecGrid::from_rle(&rle), Ok((gun, Some(LifeRule::CONWAY))));
+
+        // Empty rows, at the top and in the middle, and trailing ones.
+        let mut grid = VecGrid::filled(Rect::new((5, 7)), false);
+        grid.set((4, 1), true);
+        grid.set((0, 4), true);
+        grid.set((1, 4), true);
+        let rle = grid.to_rle(Some(&LifeRule::HIGH_LIFE));
+        assert_eq!(rle, "x = 5, y = 7, rule = B36/S23\n$4bo3$2o!\n");
+        assert_eq!(
+            VecGrid::from_rle(&rle),
+            Ok((grid, Some(LifeRule::HIGH_LIFE)))
+        );
+        let empty = VecGrid::filled(Rect::new((2, 2)), false);
+        assert_eq!(VecGrid::from_rle(&empty.to_rle(None)), Ok((empty, None)));
+    }
+
+    #[test]
+    fn rle_errors() {
+        assert_eq!(
+            VecGrid::from_rle("#C nothing else"),
+            Err(RleParseError::InvalidHeader)
+        );
+        assert_eq!(
+            VecGrid::from_rle("x = 3\nbo!"),
+            Err(RleParseError::InvalidHeader)
+        );
+        assert_eq!(
+            VecGrid::from_rle("x = 3, y = 3, rule = 23/3\nbo!"),
+            Err(RleParseError::InvalidRule(
+                ParseLifeRuleError::InvalidFormat
+            ))
+        );
+        assert_eq!(
+            VecGrid::from_rle("x = 3, y = 2\nb2o$2bo$o!"),
+            Err(RleParseError::OutOfBounds(Coord::new(0, 2)))
+        );
+        assert_eq!(
+            VecGrid::from_rle("x = 3, y = 2\nb4o!"),
+            Err(RleParseError::OutOfBounds(Coord::new(3, 0)))
+        );
+        assert_eq!(
+            VecGrid::from_rle("x = 3, y = 2\nbxo!"),
+            Err(RleParseError::InvalidTag('x'))
+        );
+        assert_eq!(
+            VecGrid::from_rle("x = 3, y = 2\nbo$\n2o"),
+            Err(RleParseError::MissingTerminator)
+        );
+    }
+
+    #[test]
+    fn neighbor_counts_match_live_neighbors() {
+        let (gun, _) = VecGrid::from_rle(GOSPER_GLIDER_GUN).unwrap();
+        let mut grid = VecGrid::filled(Rect::new((38, 10)).translate((-3, 2)), false);
+        grid.embed(gun, (-2, 2));
+        // Put live cells on every edge and corner, so that they wrap.
+        for &corner in [(-3, 2), (34, 2), (-3, 11), (34, 11), (10, 2), (-3, 6)].iter() {
+            grid.set(corner, true);
+        }
+        for &edges in [EdgeBehavior::Dead, EdgeBehavior::Alive, EdgeBehavior::Wrap].iter() {
+            let counts = grid.neighbor_counts(|&alive| alive, Connectivity::Eight, edges);
+            assert_eq!(counts.bounds, grid.bounds);
+            for (coord, &count) in counts.iter() {
+                assert_eq!(
+                    count as usize,
+                    grid.live_neighbors(coord, edges),
+                    "{:?} {:?}",
+                    edges,
+                    coord
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn neighbor_counts_ortho() {
+        let grid = VecGrid::with_generator(Rect::new((3, 2)), |(x, y)| x + y * 3);
+        let even = |&cell: &i32| cell % 2 == 0;
+        assert_eq!(
+            grid.neighbor_counts(even, Connectivity::Four, EdgeBehavior::Dead)
+                .cells,
+            vec![0, 3, 0, 2, 0, 2]
+        );
+        assert_eq!(
+            grid.neighbor_counts(even, Connectivity::Four, EdgeBehavior::Alive)
+                .cells,
+            vec![2, 4, 2, 4, 1, 4]
+        );
+        assert_eq!(
+            grid.neighbor_counts(even, Connectivity::Four, EdgeBehavior::Wrap)
+                .cells,
+            vec![1, 4, 1, 3, 0, 3]
+        );
+        // A single wrapped cell is all eight of its own neighbors.
+        let single = VecGrid::filled(Rect::new((1, 1)), true);
+        assert_eq!(
+            single
+                .neighbor_counts(|&alive| alive, Connectivity::Eight, EdgeBehavior::Wrap)
+                .cells,
+            vec![8]
+        );
+        let empty = VecGrid::filled(Rect::new((0, 4)), true);
+        let counts = empty.neighbor_counts(|&alive| alive, Connectivity::Eight, EdgeBehavior::Wrap);
+        assert_eq!(counts.bounds, empty.bounds);
+    }
+
+    #[test]
+    fn neighbor_counts_custom_neighborhoods() {
+        let grid = VecGrid::with_generator(Rect::new((7, 5)).translate((2, -1)), |(x, y)| {
+            (x * 3 + y * y) % 4 == 0
+        });
+        let knight = Neighborhood::custom([
+            (1, 2),
+            (2, 1),
+            (2, -1),
+            (1, -2),
+            (-1, -2),
+            (-2, -1),
+            (-2, 1),
+            (-1, 2),
+        ]);
+        let neighborhoods = [
+            knight.clone(),
+            knight.with_radius(2),
+            Neighborhood::von_neumann()
+                .with_radius(3)
+                .including_center(),
+            Neighborhood::custom([(-9, 0)]),
+        ];
+        for neighborhood in neighborhoods.iter() {
+            for &edges in [EdgeBehavior::Dead, EdgeBehavior::Alive, EdgeBehavior::Wrap].iter() {
+                let counts = grid.neighbor_counts(|&alive| alive, neighborhood, edges);
+                for (coord, &count) in counts.iter() {
+                    let expected = neighborhood
+                        .iter_around(coord)
+                        .filter(|&neighbor| match grid.get(neighbor) {
+                            Some(&alive) => alive,
+                            None => match edges {
+                                EdgeBehavior::Dead => false,
+                                EdgeBehavior::Alive => true,
+                                EdgeBehavior::Wrap => {
+                                    grid.get(neighbor.wrapping_add_in(Coord::ZERO, grid.bounds))
+                                        == Some(&true)
+                                }
+                            },
+                        })
+                        .count();
+                    assert_eq!(count as usize, expected, "{:?} {:?}", edges, coord);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,417 @@
+//! Reading and writing grids in a compact binary format, for save files and
+//! chunk caches that need a stable layout.
+//!
+//! The format is described in the docs of [`GridCodec`].
+
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt,
+    io::{self, Read, Write},
+    marker::PhantomData,
+};
+
+use crate::{coord::Coord, patterns::Rect, vecgrid::VecGrid};
+
+const MAGIC: [u8; 4] = *b"TPGR";
+const VERSION: u16 = 1;
+const HEADER_SIZE: usize = 24;
+/// The number of cells encoded or decoded at a time, bounding the memory used
+/// by a header claiming a huge grid before its data is found to be missing.
+const BLOCK_CELLS: usize = 4096;
+
+/// A cell type with a fixed size binary encoding, for
+/// [`VecGrid::write_binary`] and [`VecGrid::read_binary`].
+///
+/// Implementations should encode numbers as little-endian, so that files can
+/// be read on any platform. They're provided for the primitive integer and
+/// float types, and for `bool`.
+///
+/// # Format
+///
+/// Every number is little-endian. A grid starts with a 24 byte header:
+///
+/// | Offset | Size | Contents                                   |
+/// |--------|------|--------------------------------------------|
+/// | 0      | 4    | The magic number `TPGR`                    |
+/// | 4      | 2    | The format version, currently 1            |
+/// | 6      | 2    | The size of each cell in bytes, as a `u16` |
+/// | 8      | 4    | The left edge of the bounds, as an `i32`   |
+/// | 12     | 4    | The top edge of the bounds, as an `i32`    |
+/// | 16     | 4    | The width, as a `u32`                      |
+/// | 20     | 4    | The height, as a `u32`                     |
+///
+/// followed by the cells in row-major order, each encoded by its
+/// `GridCodec` in exactly the cell size. Nothing follows the cells, so
+/// several grids can be written one after the other, and the cell at any
+/// coordinate lies at a fixed offset from the start of its grid.
+pub trait GridCodec: Sized {
+    /// The number of bytes every cell is encoded in.
+    const SIZE: u16;
+
+    /// Writes the cell into `bytes`, which is exactly [`SIZE`](GridCodec::SIZE)
+    /// bytes long.
+    fn encode(&self, bytes: &mut [u8]);
+
+    /// Reads a cell from `bytes`, which is exactly
+    /// [`SIZE`](GridCodec::SIZE) bytes long, or returns `None` if they don't
+    /// hold a valid cell.
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_grid_codec {
+    ($($t:ty),*) => {
+        $(
+            impl GridCodec for $t {
+                const SIZE: u16 = core::mem::size_of::<$t>() as u16;
+
+                fn encode(&self, bytes: &mut [u8]) {
+                    bytes.copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(bytes: &[u8]) -> Option<Self> {
+                    Some(<$t>::from_le_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+
+impl_grid_codec!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+/// Encoded as a single byte, 0 or 1. Any other byte is invalid.
+impl GridCodec for bool {
+    const SIZE: u16 = 1;
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = *self as u8;
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        match bytes[0] {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// An error produced while reading a grid with
+/// [`VecGrid::read_binary`](crate::VecGrid::read_binary).
+#[derive(Debug)]
+pub enum BinaryGridError {
+    /// Reading from the source failed.
+    Io(io::Error),
+    /// The data doesn't start with the magic number, so it isn't a grid.
+    BadMagic([u8; 4]),
+    /// The grid was written in a version of the format this one can't read.
+    UnsupportedVersion(u16),
+    /// The grid's cells are a different size than the cell type being read.
+    CellSizeMismatch { expected: u16, received: u16 },
+    /// The bounds in the header extend past the coordinate limits, or hold
+    /// more bytes of cells than can be addressed.
+    InvalidDimensions {
+        left: i32,
+        top: i32,
+        width: u32,
+        height: u32,
+    },
+    /// The data ended before the number of bytes the header calls for, which
+    /// includes the header itself.
+    Truncated { expected: u64, received: u64 },
+    /// The bytes of a cell aren't a valid value of the cell type.
+    InvalidCell(Coord),
+}
+
+impl From<io::Error> for BinaryGridError {
+    fn from(error: io::Error) -> Self {
+        BinaryGridError::Io(error)
+    }
+}
+
+impl fmt::Display for BinaryGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryGridError::Io(error) => write!(f, "failed to read the grid: {}", error),
+            BinaryGridError::BadMagic(magic) => {
+                write!(f, "expected the magic number TPGR, found {:?}", magic)
+            }
+            BinaryGridError::UnsupportedVersion(version) => write!(
+                f,
+                "format version {} isn't supported, expected {}",
+                version, VERSION
+            ),
+            BinaryGridError::CellSizeMismatch { expected, received } => write!(
+                f,
+                "expected cells of {} bytes, the grid has cells of {} bytes",
+                expected, received
+            ),
+            BinaryGridError::InvalidDimensions {
+                left,
+                top,
+                width,
+                height,
+            } => write!(
+                f,
+                "a {}x{} grid at ({}, {}) is too large",
+                width, height, left, top
+            ),
+            BinaryGridError::Truncated { expected, received } => write!(
+                f,
+                "expected {} bytes, the data ended after {}",
+                expected, received
+            ),
+            BinaryGridError::InvalidCell(coord) => {
+                write!(f, "the cell at {} isn't a valid value", coord)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BinaryGridError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BinaryGridError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl<T: GridCodec> VecGrid<T> {
+    /// Writes the grid in the binary format described in the docs of
+    /// [`GridCodec`], header first.
+    ///
+    /// Cells are encoded a block at a time, so there's no need to wrap
+    /// `writer` in a `BufWriter`.
+    pub fn write_binary<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        header.extend_from_slice(&MAGIC);
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&T::SIZE.to_le_bytes());
+        header.extend_from_slice(&self.bounds.left.to_le_bytes());
+        header.extend_from_slice(&self.bounds.top.to_le_bytes());
+        header.extend_from_slice(&(self.bounds.width().max(0) as u32).to_le_bytes());
+        header.extend_from_slice(&(self.bounds.height().max(0) as u32).to_le_bytes());
+        writer.write_all(&header)?;
+
+        let size = T::SIZE as usize;
+        let mut block = vec![0; BLOCK_CELLS * size];
+        for cells in self.cells.chunks(BLOCK_CELLS) {
+            for (i, cell) in cells.iter().enumerate() {
+                cell.encode(&mut block[i * size..(i + 1) * size]);
+            }
+            writer.write_all(&block[..cells.len() * size])?;
+        }
+        Ok(())
+    }
+
+    /// Reads a grid written by [`write_binary`](VecGrid::write_binary),
+    /// leaving `reader` just past its last cell.
+    ///
+    /// Every part of the header is checked before any cells are read, and
+    /// data that doesn't describe a valid grid of `T` produces an error
+    /// rather than a wrong grid.
+    pub fn read_binary<R: Read>(mut reader: R) -> Result<VecGrid<T>, BinaryGridError> {
+        let mut header = [0; HEADER_SIZE];
+        let read = read_full(&mut reader, &mut header)?;
+        let field = |start: usize, len: usize| &header[start..start + len];
+        if read < MAGIC.len() || field(0, 4) != MAGIC {
+            let mut magic = [0; 4];
+            magic[..read.min(4)].copy_from_slice(&header[..read.min(4)]);
+            return Err(BinaryGridError::BadMagic(magic));
+        }
+        if read < HEADER_SIZE {
+            return Err(BinaryGridError::Truncated {
+                expected: HEADER_SIZE as u64,
+                received: read as u64,
+            });
+        }
+        let version = u16::from_le_bytes(field(4, 2).try_into().unwrap());
+        if version != VERSION {
+            return Err(BinaryGridError::UnsupportedVersion(version));
+        }
+        let cell_size = u16::from_le_bytes(field(6, 2).try_into().unwrap());
+        if cell_size != T::SIZE {
+            return Err(BinaryGridError::CellSizeMismatch {
+                expected: T::SIZE,
+                received: cell_size,
+            });
+        }
+        let left = i32::from_le_bytes(field(8, 4).try_into().unwrap());
+        let top = i32::from_le_bytes(field(12, 4).try_into().unwrap());
+        let width = u32::from_le_bytes(field(16, 4).try_into().unwrap());
+        let height = u32::from_le_bytes(field(20, 4).try_into().unwrap());
+
+        let invalid = || BinaryGridError::InvalidDimensions {
+            left,
+            top,
+            width,
+            height,
+        };
+        let right = i32::try_from(left as i64 + width as i64).map_err(|_| invalid())?;
+        let bottom = i32::try_from(top as i64 + height as i64).map_err(|_| invalid())?;
+        let count = (width as u64) * (height as u64);
+        let data_size = count.checked_mul(cell_size as u64).ok_or_else(invalid)?;
+        let count = usize::try_from(count).map_err(|_| invalid())?;
+        usize::try_from(data_size).map_err(|_| invalid())?;
+        let bounds = Rect {
+            top,
+            bottom,
+            left,
+            right,
+        };
+
+        let size = cell_size as usize;
+        let mut cells = Vec::with_capacity(count.min(BLOCK_CELLS));
+        let mut block = vec![0; BLOCK_CELLS * size];
+        while cells.len() < count {
+            let block_cells = (count - cells.len()).min(BLOCK_CELLS);
+            let bytes = &mut block[..block_cells * size];
+            let read = read_full(&mut reader, bytes)?;
+            if read < bytes.len() {
+                return Err(BinaryGridError::Truncated {
+                    expected: HEADER_SIZE as u64 + data_size,
+                    received: (HEADER_SIZE + cells.len() * size + read) as u64,
+                });
+            }
+            for i in 0..block_cells {
+                let index = cells.len();
+                let bytes = &block[i * size..(i + 1) * size];
+                let cell = T::decode(bytes).ok_or_else(|| {
+                    BinaryGridError::InvalidCell(VecGrid::<T>::index_to_coord_with_bounds(
+                        bounds, index,
+                    ))
+                })?;
+                cells.push(cell);
+            }
+        }
+        Ok(VecGrid {
+            cells,
+            bounds,
+            layout: PhantomData,
+        })
+    }
+}
+
+/// Reads into `buffer` until it's full or the reader runs out, returning the
+/// number of bytes read.
+fn read_full<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3x2 grid of `i16` at (-1, 5), as written by version 1 of the format.
+    /// This must never change, or files written by earlier releases would no
+    /// longer load.
+    #[rustfmt::skip]
+    const GOLDEN: [u8; 36] = [
+        b'T', b'P', b'G', b'R', 1, 0, 2, 0,
+        0xff, 0xff, 0xff, 0xff, 5, 0, 0, 0,
+        3, 0, 0, 0, 2, 0, 0, 0,
+        0x00, 0x00, 0x01, 0x00, 0xff, 0xff,
+        0x00, 0x01, 0x34, 0x12, 0x00, 0x80,
+    ];
+
+    fn golden_grid() -> VecGrid<i16> {
+        let bounds = Rect::new((3, 2)).translate((-1, 5));
+        VecGrid::from_raw_parts(bounds, vec![0, 1, -1, 256, 0x1234, i16::MIN]).unwrap()
+    }
+
+    #[test]
+    fn matches_golden_bytes() {
+        let mut bytes = Vec::new();
+        golden_grid().write_binary(&mut bytes).unwrap();
+        assert_eq!(bytes, GOLDEN);
+        assert_eq!(VecGrid::read_binary(&GOLDEN[..]).unwrap(), golden_grid());
+    }
+
+    #[test]
+    fn round_trips() {
+        let bounds = Rect::new((70, 90)).translate((-30, 12));
+        let grid = VecGrid::with_generator(bounds, |(x, y)| (x * 31 + y * 7) as f64 / 3.0);
+        let flags = VecGrid::with_generator(Rect::new((5, 1)), |(x, _)| x % 2 == 0);
+        let empty = VecGrid::<u64>::new(Rect::new((0, 4)).translate((2, 2)));
+
+        let mut bytes = Vec::new();
+        grid.write_binary(&mut bytes).unwrap();
+        flags.write_binary(&mut bytes).unwrap();
+        empty.write_binary(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), HEADER_SIZE * 3 + 70 * 90 * 8 + 5);
+
+        // Grids written one after the other read back in order.
+        let mut reader = &bytes[..];
+        assert_eq!(VecGrid::read_binary(&mut reader).unwrap(), grid);
+        assert_eq!(VecGrid::read_binary(&mut reader).unwrap(), flags);
+        assert_eq!(VecGrid::read_binary(&mut reader).unwrap(), empty);
+        assert!(reader.is_empty());
+    }
+
+    fn read_error(bytes: &[u8]) -> String {
+        VecGrid::<i16>::read_binary(bytes).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn rejects_corrupt_data() {
+        assert_eq!(
+            read_error(b"PNG\x89 and so on"),
+            "expected the magic number TPGR, found [80, 78, 71, 137]"
+        );
+        assert_eq!(
+            read_error(b"TP"),
+            "expected the magic number TPGR, found [84, 80, 0, 0]"
+        );
+        assert_eq!(
+            read_error(&GOLDEN[..10]),
+            "expected 24 bytes, the data ended after 10"
+        );
+        assert_eq!(
+            read_error(&GOLDEN[..35]),
+            "expected 36 bytes, the data ended after 35"
+        );
+
+        let corrupt = |offset: usize, bytes: &[u8]| {
+            let mut corrupt = GOLDEN;
+            corrupt[offset..offset + bytes.len()].copy_from_slice(bytes);
+            corrupt
+        };
+        assert_eq!(
+            read_error(&corrupt(4, &[2, 0])),
+            "format version 2 isn't supported, expected 1"
+        );
+        assert_eq!(
+            read_error(&corrupt(6, &[4, 0])),
+            "expected cells of 2 bytes, the grid has cells of 4 bytes"
+        );
+        // A header claiming a huge grid fails once its data runs out, rather
+        // than allocating space for all of it up front.
+        assert_eq!(
+            read_error(&corrupt(16, &[0xff, 0xff, 0xff, 0x0f])),
+            "expected 1073741844 bytes, the data ended after 36"
+        );
+        assert_eq!(
+            read_error(&corrupt(8, &[0xff, 0xff, 0xff, 0x7f])),
+            "a 3x2 grid at (2147483647, 5) is too large"
+        );
+        // Read as one byte cells, the low byte of -1 is the first that isn't a
+        // bool.
+        assert!(matches!(
+            VecGrid::<bool>::read_binary(&corrupt(6, &[1, 0])[..30]),
+            Err(BinaryGridError::InvalidCell(coord)) if coord == Coord::new(0, 6)
+        ));
+        assert!(matches!(
+            VecGrid::<i16>::read_binary(&GOLDEN[..]),
+            Ok(grid) if grid == golden_grid()
+        ));
+    }
+}
@@ -0,0 +1,298 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    coord::Coord,
+    grid::{Grid, GridError, IterCell},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A grid of booleans packed one bit per cell, for masks, visibility maps and
+/// collision layers.
+///
+/// Cells are indexed row-major exactly like a [`VecGrid`], with no padding
+/// between rows: the cell at linear index `i` is bit `i % 64` of word `i / 64`.
+/// Any bits of the last word past the final cell are always zero, which lets
+/// whole-grid operations work a word at a time.
+///
+/// Since there's no `bool` in memory to borrow, `BitGrid` implements [`Grid`]
+/// but not [`GridMut`](crate::GridMut); use [`set`](BitGrid::set) to write
+/// cells.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    words: Vec<u64>,
+    bounds: Rect,
+}
+
+impl Grid<bool> for BitGrid {
+    fn get<C: Into<Coord>>(&self, coord: C) -> Option<&bool> {
+        BitGrid::get(self, coord).map(|bit| if bit { &true } else { &false })
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = IterCell<'a, bool>>
+    where
+        bool: 'a,
+    {
+        BitGrid::iter(self).map(|(coord, bit)| (coord, if bit { &true } else { &false }))
+    }
+}
+
+impl BitGrid {
+    /// Constructs a grid with every cell set to `false`.
+    pub fn new(bounds: Rect) -> Self {
+        Self::filled(bounds, false)
+    }
+
+    /// Constructs a grid with every cell set to `value`.
+    pub fn filled(bounds: Rect, value: bool) -> Self {
+        let len = bounds.area().max(0) as usize;
+        let mut grid = Self {
+            words: vec![0; len.div_ceil(WORD_BITS)],
+            bounds,
+        };
+        grid.fill(value);
+        grid
+    }
+
+    /// Constructs a grid by calling `generator` for each cell.
+    pub fn with_generator<F>(bounds: Rect, mut generator: F) -> Self
+    where
+        F: FnMut(Coord) -> bool,
+    {
+        let mut grid = Self::new(bounds);
+        for index in 0..grid.len() {
+            if generator(grid.index_to_coord(index)) {
+                grid.words[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+            }
+        }
+        grid
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Returns the packed cells. See the [type level docs](BitGrid) for the
+    /// layout.
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Returns the cell at `coord`, or `None` if it's out of bounds.
+    pub fn get<C: Into<Coord>>(&self, coord: C) -> Option<bool> {
+        let index = self.coord_to_index(coord.into())?;
+        Some(self.words[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0)
+    }
+
+    /// Sets the cell at `coord`, returning false if it's out of bounds.
+    pub fn set<C: Into<Coord>>(&mut self, coord: C, value: bool) -> bool {
+        match self.coord_to_index(coord.into()) {
+            Some(index) => {
+                let mask = 1 << (index % WORD_BITS);
+                if value {
+                    self.words[index / WORD_BITS] |= mask;
+                } else {
+                    self.words[index / WORD_BITS] &= !mask;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns an iterator over all cells in the grid, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, bool)> + '_ {
+        (0..self.len()).map(move |index| {
+            let bit = self.words[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0;
+            (self.index_to_coord(index), bit)
+        })
+    }
+
+    /// Returns the number of cells set to `true`.
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Sets every cell to `value`.
+    pub fn fill(&mut self, value: bool) {
+        let word = if value { u64::MAX } else { 0 };
+        self.words.iter_mut().for_each(|w| *w = word);
+        self.clear_padding();
+    }
+
+    /// Sets each cell to whether it and the matching cell of `other` are both
+    /// `true`.
+    ///
+    /// Returns [`GridError::BoundsMismatch`] if the grids have different
+    /// bounds, in which case nothing is changed.
+    pub fn and(&mut self, other: &BitGrid) -> Result<(), GridError> {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /// Sets each cell to whether it or the matching cell of `other` is `true`.
+    /// See [`and`](BitGrid::and).
+    pub fn or(&mut self, other: &BitGrid) -> Result<(), GridError> {
+        self.combine(other, |a, b| a | b)
+    }
+
+    /// Sets each cell to whether exactly one of it and the matching cell of
+    /// `other` is `true`. See [`and`](BitGrid::and).
+    pub fn xor(&mut self, other: &BitGrid) -> Result<(), GridError> {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    /// Inverts every cell.
+    pub fn not(&mut self) {
+        self.words.iter_mut().for_each(|word| *word = !*word);
+        self.clear_padding();
+    }
+
+    fn combine<F>(&mut self, other: &BitGrid, f: F) -> Result<(), GridError>
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        if self.bounds != other.bounds {
+            return Err(GridError::BoundsMismatch {
+                expected: self.bounds,
+                received: other.bounds,
+            });
+        }
+        for (word, &other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word = f(*word, other);
+        }
+        Ok(())
+    }
+
+    /// Zeroes the bits of the last word which lie past the final cell.
+    fn clear_padding(&mut self) {
+        let used = self.len() % WORD_BITS;
+        if let (Some(last), true) = (self.words.last_mut(), used != 0) {
+            *last &= (1 << used) - 1;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.bounds.area().max(0) as usize
+    }
+
+    fn coord_to_index(&self, coord: Coord) -> Option<usize> {
+        if !self.bounds.contains(coord) {
+            return None;
+        }
+        let local = coord - self.bounds.offset();
+        Some(local.x as usize + local.y as usize * self.bounds.width() as usize)
+    }
+
+    fn index_to_coord(&self, index: usize) -> Coord {
+        let width = self.bounds.width() as usize;
+        self.bounds.offset() + Coord::new((index % width) as i32, (index / width) as i32)
+    }
+}
+
+impl From<&VecGrid<bool>> for BitGrid {
+    fn from(grid: &VecGrid<bool>) -> Self {
+        let mut words = vec![0; grid.cells.len().div_ceil(WORD_BITS)];
+        for (index, _) in grid.cells.iter().enumerate().filter(|(_, &cell)| cell) {
+            words[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+        }
+        Self {
+            words,
+            bounds: grid.bounds,
+        }
+    }
+}
+
+impl From<&BitGrid> for VecGrid<bool> {
+    fn from(grid: &BitGrid) -> Self {
+        let cells = grid.iter().map(|(_, bit)| bit).collect();
+        VecGrid::from_raw_parts(grid.bounds, cells).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_and_set() {
+        let bounds = Rect::new((9, 8)).translate((-3, 2));
+        let mut grid = BitGrid::new(bounds);
+        assert_eq!(grid.as_words().len(), 2);
+        assert!(grid.set((-3, 2), true));
+        assert!(grid.set((5, 9), true));
+        assert!(grid.set((0, 5), true));
+        assert!(!grid.set((6, 2), true));
+        assert!(grid.set((0, 5), false));
+        assert_eq!(grid.get((-3, 2)), Some(true));
+        assert_eq!(grid.get((5, 9)), Some(true));
+        assert_eq!(grid.get((0, 5)), Some(false));
+        assert_eq!(grid.get((0, 0)), None);
+        assert_eq!(grid.count_ones(), 2);
+        assert_eq!(
+            grid.iter()
+                .filter(|&(_, bit)| bit)
+                .map(|(coord, _)| coord)
+                .collect::<Vec<_>>(),
+            vec![Coord::new(-3, 2), Coord::new(5, 9)]
+        );
+    }
+
+    #[test]
+    fn word_ops() {
+        let bounds = Rect::new((10, 7));
+        let mut evens = BitGrid::with_generator(bounds, |coord| coord.x % 2 == 0);
+        let left = BitGrid::with_generator(bounds, |coord| coord.x < 3);
+        assert_eq!(evens.count_ones(), 35);
+
+        let mut both = evens.clone();
+        both.and(&left).unwrap();
+        assert_eq!(both.count_ones(), 14);
+        let mut either = evens.clone();
+        either.or(&left).unwrap();
+        assert_eq!(either.count_ones(), 42);
+        evens.xor(&left).unwrap();
+        assert_eq!(evens.count_ones(), 28);
+        evens.not();
+        assert_eq!(evens.count_ones(), 42);
+
+        // The 6 padding bits of the last word must stay clear.
+        let mut full = BitGrid::filled(bounds, true);
+        assert_eq!(full.count_ones(), 70);
+        full.fill(false);
+        full.not();
+        assert_eq!(full.count_ones(), 70);
+
+        assert_eq!(
+            full.and(&BitGrid::new(Rect::new((7, 10)))),
+            Err(GridError::BoundsMismatch {
+                expected: bounds,
+                received: Rect::new((7, 10)),
+            })
+        );
+    }
+
+    #[test]
+    fn vec_grid_conversions() {
+        let grid = VecGrid::with_generator(Rect::new((13, 11)), |(x, y)| (x * y) % 3 == 1);
+        let bits = BitGrid::from(&grid);
+        assert_eq!(
+            bits.count_ones(),
+            grid.cells.iter().filter(|&&cell| cell).count()
+        );
+        assert_eq!(VecGrid::from(&bits), grid);
+
+        let mut values = VecGrid::filled(grid.bounds, 0);
+        let visited = values.apply_masked(&bits, |_, cell| *cell = 1).unwrap();
+        assert_eq!(visited, bits.count_ones());
+        assert_eq!(values.map(|&cell| cell == 1), grid);
+    }
+}
@@ -0,0 +1,336 @@
+use std::{collections::HashMap, mem};
+
+use crate::{
+    coord::Coord,
+    grid::{Grid, GridMut, IterCell, IterCellMut},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+/// An effectively unbounded grid, stored as square `CHUNK`×`CHUNK` blocks
+/// which are only allocated once a cell inside them is written to.
+///
+/// Every coordinate has a cell. Cells in chunks that have never been written
+/// to hold the grid's default value, and reading them allocates nothing.
+///
+/// Chunk `(cx, cy)` covers the cells from `(cx * CHUNK, cy * CHUNK)` up to (but
+/// excluding) `((cx + 1) * CHUNK, (cy + 1) * CHUNK)`, so the chunk containing a
+/// cell is found by flooring division; cell `(-1, -1)` lives in chunk
+/// `(-1, -1)`, not `(0, 0)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkedGrid<T, const CHUNK: usize = 32> {
+    chunks: HashMap<Coord, VecGrid<T>>,
+    default: T,
+}
+
+impl<T, const CHUNK: usize> Grid<T> for ChunkedGrid<T, CHUNK> {
+    /// Returns the cell at `coord`. This never returns `None`, since every
+    /// coordinate has a cell.
+    fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        let coord = coord.into();
+        match self.chunks.get(&Self::chunk_of(coord)) {
+            Some(chunk) => chunk.get(coord),
+            None => Some(&self.default),
+        }
+    }
+
+    /// Returns the [`loaded_bounds`](ChunkedGrid::loaded_bounds), or an empty
+    /// rect at the origin if no chunk is loaded.
+    fn bounds(&self) -> Rect {
+        self.loaded_bounds().unwrap_or_else(|| Rect::new((0, 0)))
+    }
+
+    /// Returns an iterator over the cells of every loaded chunk, one chunk at a
+    /// time, with chunks in no particular order.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+    {
+        self.chunks.values().flat_map(|chunk| chunk.iter())
+    }
+
+    fn contains<C: Into<Coord>>(&self, _coord: C) -> bool {
+        true
+    }
+}
+
+impl<T: Clone, const CHUNK: usize> GridMut<T> for ChunkedGrid<T, CHUNK> {
+    /// Returns the cell at `coord`, loading its chunk if needed. This never
+    /// returns `None`.
+    fn get_mut<C: Into<Coord>>(&mut self, coord: C) -> Option<&mut T> {
+        let coord = coord.into();
+        self.load_chunk(Self::chunk_of(coord)).get_mut(coord)
+    }
+
+    fn copy<C1, C2>(&mut self, src: C1, dest: C2) -> bool
+    where
+        T: Copy,
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        let value = self.get_copied(src).unwrap();
+        self.set(dest, value)
+    }
+
+    fn swap<C1, C2>(&mut self, coord1: C1, coord2: C2) -> bool
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        let coord1 = coord1.into();
+        let value1 = self.get(coord1).unwrap().clone();
+        let value2 = self.replace(coord2, value1).unwrap();
+        self.set(coord1, value2)
+    }
+
+    /// Moves the contents of `src` into `dest`, leaving the grid's default
+    /// value (rather than `T::default()`) behind in `src`.
+    fn mov(&mut self, src: Coord, dest: Coord) -> Option<T>
+    where
+        T: Default,
+    {
+        let default = self.default.clone();
+        let src_value = self.replace(src, default).unwrap();
+        self.replace(dest, src_value)
+    }
+}
+
+impl<T, const CHUNK: usize> ChunkedGrid<T, CHUNK> {
+    /// Constructs a grid with every cell set to `T::default()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `CHUNK` is zero, or too large for a chunk's coordinates to
+    /// fit in an `i32`.
+    pub fn new() -> Self
+    where
+        T: Default,
+    {
+        Self::with_default(T::default())
+    }
+
+    /// Constructs a grid with every cell set to `default`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `CHUNK` is zero, or too large for a chunk's coordinates to
+    /// fit in an `i32`.
+    pub fn with_default(default: T) -> Self {
+        assert!(
+            CHUNK > 0 && CHUNK <= i32::MAX as usize,
+            "chunk size {} is out of range",
+            CHUNK
+        );
+        Self {
+            chunks: HashMap::new(),
+            default,
+        }
+    }
+
+    /// Returns the value of cells in chunks that haven't been loaded.
+    pub fn default_value(&self) -> &T {
+        &self.default
+    }
+
+    /// Returns the coordinate of the chunk containing the cell at `coord`.
+    pub fn chunk_of<C: Into<Coord>>(coord: C) -> Coord {
+        let coord = coord.into();
+        let size = CHUNK as i32;
+        Coord::new(coord.x.div_euclid(size), coord.y.div_euclid(size))
+    }
+
+    /// Returns the area covered by the chunk at `chunk_coord`.
+    pub fn chunk_bounds<C: Into<Coord>>(chunk_coord: C) -> Rect {
+        let chunk_coord = chunk_coord.into();
+        let size = CHUNK as i32;
+        Rect::new((size, size)).translate(Coord::new(chunk_coord.x * size, chunk_coord.y * size))
+    }
+
+    /// Returns the number of loaded chunks.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Returns the smallest rect covering every loaded chunk, or `None` if no
+    /// chunk is loaded.
+    pub fn loaded_bounds(&self) -> Option<Rect> {
+        let mut chunk_coords = self.chunks.keys();
+        let first = *chunk_coords.next()?;
+        let (min, max) = chunk_coords.fold((first, first), |(min, max), coord| {
+            (
+                Coord::new(min.x.min(coord.x), min.y.min(coord.y)),
+                Coord::new(max.x.max(coord.x), max.y.max(coord.y)),
+            )
+        });
+        let min = Self::chunk_bounds(min);
+        let max = Self::chunk_bounds(max);
+        Some(Rect::with_corners(
+            (min.left, min.top),
+            (max.right, max.bottom),
+        ))
+    }
+
+    /// Returns the chunk at `chunk_coord`, if it's loaded. The chunk's bounds
+    /// are in grid coordinates, so it can be indexed with the same coords as
+    /// the grid itself.
+    pub fn chunk<C: Into<Coord>>(&self, chunk_coord: C) -> Option<&VecGrid<T>> {
+        self.chunks.get(&chunk_coord.into())
+    }
+
+    /// Returns an iterator over every loaded chunk and its chunk coordinate,
+    /// in no particular order.
+    pub fn iter_chunks(&self) -> impl Iterator<Item = (Coord, &VecGrid<T>)> {
+        self.chunks.iter().map(|(&coord, chunk)| (coord, chunk))
+    }
+
+    /// Returns a mutable iterator over every loaded chunk and its chunk
+    /// coordinate, in no particular order.
+    pub fn iter_chunks_mut(&mut self) -> impl Iterator<Item = (Coord, &mut VecGrid<T>)> {
+        self.chunks.iter_mut().map(|(&coord, chunk)| (coord, chunk))
+    }
+
+    /// Returns a mutable iterator over the cells of every loaded chunk.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        self.chunks.values_mut().flat_map(|chunk| chunk.iter_mut())
+    }
+
+    /// Loads the chunk at `chunk_coord`, filling it with the default value if
+    /// it wasn't already loaded.
+    pub fn load_chunk<C: Into<Coord>>(&mut self, chunk_coord: C) -> &mut VecGrid<T>
+    where
+        T: Clone,
+    {
+        let chunk_coord = chunk_coord.into();
+        let default = &self.default;
+        self.chunks
+            .entry(chunk_coord)
+            .or_insert_with(|| VecGrid::filled(Self::chunk_bounds(chunk_coord), default.clone()))
+    }
+
+    /// Inserts a chunk, for restoring one that was saved or streamed out,
+    /// returning the chunk it replaces.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the chunk's bounds aren't those of
+    /// [`chunk_bounds(chunk_coord)`](ChunkedGrid::chunk_bounds).
+    pub fn insert_chunk<C: Into<Coord>>(
+        &mut self,
+        chunk_coord: C,
+        chunk: VecGrid<T>,
+    ) -> Option<VecGrid<T>> {
+        let chunk_coord = chunk_coord.into();
+        assert_eq!(
+            chunk.bounds,
+            Self::chunk_bounds(chunk_coord),
+            "chunk doesn't cover chunk {:?}",
+            chunk_coord
+        );
+        self.chunks.insert(chunk_coord, chunk)
+    }
+
+    /// Unloads the chunk at `chunk_coord`, returning it. Its cells read as the
+    /// default value afterwards.
+    pub fn remove_chunk<C: Into<Coord>>(&mut self, chunk_coord: C) -> Option<VecGrid<T>> {
+        self.chunks.remove(&chunk_coord.into())
+    }
+
+    /// Unloads every chunk whose cells all hold the default value.
+    pub fn unload_default_chunks(&mut self)
+    where
+        T: PartialEq,
+    {
+        let default = &self.default;
+        self.chunks
+            .retain(|_, chunk| chunk.cells.iter().any(|cell| cell != default));
+    }
+
+    /// Replaces the default value, returning the old one. Cells in unloaded
+    /// chunks take on the new value, while loaded cells are left as they are.
+    pub fn set_default_value(&mut self, default: T) -> T {
+        mem::replace(&mut self.default, default)
+    }
+}
+
+impl<T: Default, const CHUNK: usize> Default for ChunkedGrid<T, CHUNK> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Grid4 = ChunkedGrid<i32, 4>;
+
+    #[test]
+    fn chunk_indexing() {
+        assert_eq!(Grid4::chunk_of((0, 0)), Coord::new(0, 0));
+        assert_eq!(Grid4::chunk_of((3, 4)), Coord::new(0, 1));
+        assert_eq!(Grid4::chunk_of((-1, -4)), Coord::new(-1, -1));
+        assert_eq!(Grid4::chunk_of((-5, -4)), Coord::new(-2, -1));
+        assert_eq!(
+            Grid4::chunk_bounds((-2, 1)),
+            Rect::with_corners((-8, 4), (-4, 8))
+        );
+        for coord in Rect::with_corners((-9, -9), (9, 9)).iter() {
+            assert!(Grid4::chunk_bounds(Grid4::chunk_of(coord)).contains(coord));
+        }
+    }
+
+    #[test]
+    fn lazy_chunks() {
+        let mut grid = Grid4::with_default(-1);
+        assert_eq!(grid.get((1_000_000, -1_000_000)), Some(&-1));
+        assert_eq!(grid.chunk_count(), 0);
+        assert_eq!(grid.loaded_bounds(), None);
+
+        // Straddle the origin, touching four chunks.
+        for coord in Rect::with_corners((-1, -1), (1, 1)).iter() {
+            grid.set(coord, coord.x * 10 + coord.y * 100);
+        }
+        assert_eq!(grid.chunk_count(), 4);
+        assert_eq!(grid.get((-1, 0)), Some(&-10));
+        assert_eq!(grid.get((0, -1)), Some(&-100));
+        assert_eq!(grid.get((2, 2)), Some(&-1));
+        assert_eq!(
+            grid.loaded_bounds(),
+            Some(Rect::with_corners((-4, -4), (4, 4)))
+        );
+        assert_eq!(grid.iter().count(), 64);
+
+        grid.set((-1, 0), -1);
+        grid.unload_default_chunks();
+        assert_eq!(grid.chunk_count(), 3);
+        let chunk = grid.remove_chunk((0, 0)).unwrap();
+        assert_eq!(chunk.get((0, 0)), Some(&0));
+        assert_eq!(grid.chunk((0, 0)), None);
+        grid.insert_chunk((0, 0), chunk);
+        assert_eq!(
+            grid.iter_chunks()
+                .map(|(coord, _)| coord)
+                .max_by_key(|c| (c.x, c.y)),
+            Some(Coord::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn neighbors_across_seams() {
+        let mut grid = ChunkedGrid::<bool, 4>::new();
+        for &coord in &[(3, 3), (4, 4), (3, 4), (-1, -1)] {
+            grid.set(coord, true);
+        }
+        let alive = |coord| grid.neighbors(coord).filter(|(_, &alive)| alive).count();
+        assert_eq!(grid.neighbors((4, 3)).count(), 8);
+        assert_eq!(alive((4, 3)), 3);
+        assert_eq!(alive((0, 0)), 1);
+        assert_eq!(alive((-4, -4)), 0);
+        assert_eq!(grid.chunk_count(), 4);
+
+        assert!(grid.swap((3, 3), (-5, -5)));
+        assert_eq!(grid.get((-5, -5)), Some(&true));
+        assert_eq!(grid.mov(Coord::new(4, 4), Coord::new(20, 20)), Some(false));
+        assert_eq!(grid.get((4, 4)), Some(&false));
+    }
+}
@@ -1,9 +1,13 @@
-use std::{
+use alloc::vec::Vec;
+use core::{
+    convert::TryFrom,
     fmt,
     ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign},
     str::FromStr,
 };
 
+use crate::patterns::Rect;
+
 /// The coordinate key to a specific [`Grid`](crate::grid::Grid) cell.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Coord {
@@ -49,6 +53,46 @@ impl Coord {
         Self::new(self.x, -self.y)
     }
 
+    /// Adds `rhs` to each component, or returns `None` if either would
+    /// overflow.
+    pub const fn checked_add(self, rhs: Coord) -> Option<Self> {
+        match (self.x.checked_add(rhs.x), self.y.checked_add(rhs.y)) {
+            (Some(x), Some(y)) => Some(Self::new(x, y)),
+            _ => None,
+        }
+    }
+
+    /// Adds `rhs` to each component, stopping at `i32::MIN` or `i32::MAX`
+    /// instead of overflowing.
+    pub const fn saturating_add(self, rhs: Coord) -> Self {
+        Self::new(self.x.saturating_add(rhs.x), self.y.saturating_add(rhs.y))
+    }
+
+    /// Adds `delta`, then wraps the result back into `bounds` as if its
+    /// opposite edges were joined, so that stepping off the right edge lands
+    /// on the left one. `delta` may span any number of times around the
+    /// bounds, and `self` doesn't have to start inside them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bounds` has zero area, since there's nothing to wrap into.
+    pub fn wrapping_add_in(self, delta: Coord, bounds: Rect) -> Self {
+        assert!(
+            bounds.area() > 0,
+            "cannot wrap a coord into a zero area rect"
+        );
+        // Work in i64 so that neither the sum nor its distance from the
+        // bounds can overflow, whatever the inputs.
+        let wrap = |value: i32, delta: i32, start: i32, len: i32| {
+            let offset = (value as i64 + delta as i64 - start as i64).rem_euclid(len as i64);
+            i32::try_from(start as i64 + offset).unwrap()
+        };
+        Self::new(
+            wrap(self.x, delta.x, bounds.left, bounds.width()),
+            wrap(self.y, delta.y, bounds.top, bounds.height()),
+        )
+    }
+
     pub fn lerp(from: Self, to: Self, progress: f32) -> Self {
         let diff = to - from;
         from + Coord::new(
@@ -109,6 +153,12 @@ impl From<(i32, i32)> for Coord {
     }
 }
 
+impl From<&Coord> for Coord {
+    fn from(coord: &Coord) -> Self {
+        *coord
+    }
+}
+
 impl From<Coord> for (i32, i32) {
     fn from(Coord { x, y }: Coord) -> Self {
         (x, y)
@@ -200,4 +250,78 @@ mod tests {
         let b = Coord::new(10, 10);
         assert_eq!(Coord::lerp(a, b, 0.5), Coord::new(5, 5));
     }
+
+    #[test]
+    fn checked_and_saturating_add() {
+        let max = Coord::new(i32::MAX, i32::MAX);
+        let min = Coord::new(i32::MIN, i32::MIN);
+        assert_eq!(max.checked_add(Coord::ONE), None);
+        assert_eq!(max.checked_add(Coord::NORTH), None);
+        assert_eq!(min.checked_add(Coord::SOUTH), None);
+        assert_eq!(
+            max.checked_add(Coord::new(-1, -2)),
+            Some(Coord::new(i32::MAX - 1, i32::MAX - 2))
+        );
+        assert_eq!(
+            Coord::new(i32::MAX, i32::MIN).saturating_add(Coord::SOUTH_EAST),
+            Coord::new(i32::MAX, i32::MIN)
+        );
+        assert_eq!(
+            min.saturating_add(Coord::new(-5, 3)),
+            Coord::new(i32::MIN, i32::MIN + 3)
+        );
+    }
+
+    #[test]
+    fn wrapping_add_in() {
+        let world = Rect::new((64, 32));
+        assert_eq!(
+            Coord::new(63, 5).wrapping_add_in(Coord::EAST, world),
+            Coord::new(0, 5)
+        );
+        assert_eq!(
+            Coord::new(0, 0).wrapping_add_in(Coord::SOUTH_WEST, world),
+            Coord::new(63, 31)
+        );
+        // Deltas spanning the world several times over.
+        assert_eq!(
+            Coord::new(10, 10).wrapping_add_in(Coord::new(64 * 3 + 2, -32 * 5 - 1), world),
+            Coord::new(12, 9)
+        );
+        // Coords starting outside the bounds still land inside.
+        assert_eq!(
+            Coord::new(-130, 100).wrapping_add_in(Coord::ZERO, world),
+            Coord::new(62, 4)
+        );
+        assert_eq!(
+            Coord::new(i32::MAX, i32::MIN).wrapping_add_in(Coord::new(i32::MAX, i32::MIN), world),
+            Coord::new(62, 0)
+        );
+    }
+
+    #[test]
+    fn wrapping_add_in_negative_origins() {
+        let world = Rect::new((5, 3)).translate((-7, -4));
+        assert_eq!(world.left, -7);
+        assert_eq!(
+            Coord::new(-3, -2).wrapping_add_in(Coord::EAST, world),
+            Coord::new(-7, -2)
+        );
+        assert_eq!(
+            Coord::new(-7, -4).wrapping_add_in(Coord::new(-11, -7), world),
+            Coord::new(-3, -2)
+        );
+        for delta in -20..20 {
+            let wrapped = Coord::new(-5, -3).wrapping_add_in(Coord::new(delta, delta), world);
+            assert!(world.contains(wrapped), "{:?}", wrapped);
+            assert_eq!((wrapped.x + 5 - delta).rem_euclid(5), 0);
+            assert_eq!((wrapped.y + 3 - delta).rem_euclid(3), 0);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrapping_add_in_empty_bounds() {
+        Coord::ZERO.wrapping_add_in(Coord::ONE, Rect::new((0, 4)));
+    }
 }
@@ -0,0 +1,247 @@
+use alloc::{sync::Arc, vec::Vec};
+
+use crate::{
+    coord::Coord,
+    grid::{Grid, GridMut, IterCell, IterCellMut},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+/// A grid whose cells are shared between clones until one of them is written
+/// to, for undo histories and search trees that clone whole boards.
+///
+/// Cloning only bumps a reference count. The first write to a grid whose cells
+/// are shared copies them, so that the clones never observe each other's
+/// changes; writes to a grid that isn't shared happen in place. Use
+/// [`make_unique`](CowGrid::make_unique) to pay for the copy up front instead.
+///
+/// Reads go through the [`Grid`] trait, and writes through [`GridMut`], which
+/// needs `T: Clone` to be able to copy shared cells.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct CowGrid<T> {
+    cells: Arc<Vec<T>>,
+    bounds: Rect,
+}
+
+impl<T> Grid<T> for CowGrid<T> {
+    fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        let index = VecGrid::<T>::coord_to_index_with_bounds(self.bounds, coord.into())?;
+        self.cells.get(index)
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+    {
+        CowGrid::iter(self)
+    }
+
+    fn contains<C: Into<Coord>>(&self, coord: C) -> bool {
+        self.bounds.contains(coord)
+    }
+}
+
+impl<T: Clone> GridMut<T> for CowGrid<T> {
+    fn get_mut<C: Into<Coord>>(&mut self, coord: C) -> Option<&mut T> {
+        let index = VecGrid::<T>::coord_to_index_with_bounds(self.bounds, coord.into())?;
+        self.as_mut_slice().get_mut(index)
+    }
+
+    fn copy<C1, C2>(&mut self, src: C1, dest: C2) -> bool
+    where
+        T: Copy,
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        match (self.index(src.into()), self.index(dest.into())) {
+            (Some(src), Some(dest)) => {
+                self.as_mut_slice().copy_within(src..(src + 1), dest);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn swap<C1, C2>(&mut self, coord1: C1, coord2: C2) -> bool
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        match (self.index(coord1.into()), self.index(coord2.into())) {
+            (Some(index1), Some(index2)) => {
+                self.as_mut_slice().swap(index1, index2);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn mov(&mut self, src: Coord, dest: Coord) -> Option<T>
+    where
+        T: Default,
+    {
+        // Make sure both coordinates are in bounds before mutating things.
+        if !(self.bounds.contains(src) && self.bounds.contains(dest)) {
+            return None;
+        }
+        let src_value = self.take(src).unwrap();
+        self.replace(dest, src_value)
+    }
+}
+
+impl<T> CowGrid<T> {
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Returns whether any clones of this grid are still sharing its cells, in
+    /// which case the next write will copy them.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.cells) > 1
+    }
+
+    /// Copies the cells if they're shared, so that later writes don't have to.
+    pub fn make_unique(&mut self)
+    where
+        T: Clone,
+    {
+        Arc::make_mut(&mut self.cells);
+    }
+
+    /// Returns the cells as a row-major slice.
+    pub fn as_slice(&self) -> &[T] {
+        &self.cells
+    }
+
+    /// Returns the cells as a row-major mutable slice, copying them first if
+    /// they're shared.
+    pub fn as_mut_slice(&mut self) -> &mut [T]
+    where
+        T: Clone,
+    {
+        Arc::make_mut(&mut self.cells).as_mut_slice()
+    }
+
+    /// Returns an iterator over all cells in the grid, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = IterCell<'_, T>> {
+        let bounds = self.bounds;
+        self.cells.iter().enumerate().map(move |(index, cell)| {
+            (
+                VecGrid::<T>::index_to_coord_with_bounds(bounds, index),
+                cell,
+            )
+        })
+    }
+
+    /// Returns a mutable iterator over all cells in the grid, in row-major
+    /// order, copying them first if they're shared.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = IterCellMut<'_, T>>
+    where
+        T: Clone,
+    {
+        let bounds = self.bounds;
+        self.as_mut_slice()
+            .iter_mut()
+            .enumerate()
+            .map(move |(index, cell)| {
+                (
+                    VecGrid::<T>::index_to_coord_with_bounds(bounds, index),
+                    cell,
+                )
+            })
+    }
+
+    /// Converts back into a [`VecGrid`], only copying the cells if they're
+    /// shared.
+    pub fn into_vec_grid(self) -> VecGrid<T>
+    where
+        T: Clone,
+    {
+        let cells = Arc::try_unwrap(self.cells).unwrap_or_else(|cells| (*cells).clone());
+        VecGrid::from_raw_parts(self.bounds, cells).unwrap()
+    }
+
+    fn index(&self, coord: Coord) -> Option<usize> {
+        VecGrid::<T>::coord_to_index_with_bounds(self.bounds, coord)
+    }
+}
+
+/// Shares the cells with the new grid, without copying them.
+impl<T> Clone for CowGrid<T> {
+    fn clone(&self) -> Self {
+        Self {
+            cells: Arc::clone(&self.cells),
+            bounds: self.bounds,
+        }
+    }
+}
+
+impl<T> From<VecGrid<T>> for CowGrid<T> {
+    fn from(grid: VecGrid<T>) -> Self {
+        let (bounds, cells) = grid.into_raw_parts();
+        Self {
+            cells: Arc::new(cells),
+            bounds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_after_clone_are_private() {
+        let bounds = Rect::new((4, 3)).translate((-1, -1));
+        let original = CowGrid::from(VecGrid::with_generator(bounds, |(x, y)| x + y * 10));
+        let mut undo = vec![original.clone()];
+        assert!(original.is_shared());
+        assert_eq!(undo[0].as_slice().as_ptr(), original.as_slice().as_ptr());
+
+        let mut board = original.clone();
+        assert!(board.set((2, 1), 100));
+        assert!(board.swap((-1, -1), (0, -1)));
+        for (_, cell) in board.iter_mut().filter(|(coord, _)| coord.y == 0) {
+            *cell *= 2;
+        }
+        undo.push(board.clone());
+
+        assert_eq!(original.get((2, 1)), Some(&12));
+        assert_eq!(undo[0], original);
+        assert_eq!(undo[1].get((2, 1)), Some(&100));
+        assert_eq!(undo[1].get((-1, -1)), Some(&-10));
+        assert_eq!(undo[1].get((1, 0)), Some(&2));
+        assert_eq!(board.into_vec_grid().row_sums(), vec![-38, 4, 130]);
+    }
+
+    #[test]
+    fn unshared_writes_happen_in_place() {
+        let mut grid = CowGrid::from(VecGrid::filled(Rect::new((16, 16)), 0u8));
+        let cells = grid.as_slice().as_ptr();
+        assert!(!grid.is_shared());
+        grid.set((3, 3), 1);
+        assert_eq!(grid.as_slice().as_ptr(), cells);
+
+        // Dropping the last clone makes the grid unique again.
+        let snapshot = grid.clone();
+        assert!(grid.is_shared());
+        drop(snapshot);
+        assert!(!grid.is_shared());
+        grid.set((4, 4), 1);
+        assert_eq!(grid.as_slice().as_ptr(), cells);
+
+        let snapshot = grid.clone();
+        grid.make_unique();
+        assert!(!grid.is_shared() && !snapshot.is_shared());
+        let copied = grid.as_slice().as_ptr();
+        assert_ne!(copied, cells);
+        grid.set((5, 5), 1);
+        assert_eq!(grid.as_slice().as_ptr(), copied);
+        assert_eq!(snapshot.as_slice().as_ptr(), cells);
+        assert_eq!(grid.iter().filter(|(_, &cell)| cell == 1).count(), 3);
+    }
+}
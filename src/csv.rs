@@ -0,0 +1,176 @@
+//! Reading and writing grids as comma-separated values, for editing them in a
+//! spreadsheet.
+//!
+//! The format is deliberately minimal: each grid row is one line, and fields
+//! are split on every comma. There's no quoting or escaping, so cells whose
+//! text contains a comma or a line break can't be represented.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use crate::{coord::Coord, grid::CellError, patterns::Rect, vecgrid::VecGrid};
+
+/// An error produced while reading a grid with
+/// [`VecGrid::from_csv`](crate::VecGrid::from_csv).
+#[derive(Debug)]
+pub enum CsvGridError<E> {
+    /// Reading from the source failed.
+    Io(io::Error),
+    /// A line has a different number of fields than the first line. Lines are
+    /// numbered from 1.
+    Ragged {
+        line: usize,
+        expected: usize,
+        received: usize,
+    },
+    /// A field couldn't be parsed into a cell.
+    Cell(CellError<E>),
+}
+
+impl<E> From<io::Error> for CsvGridError<E> {
+    fn from(error: io::Error) -> Self {
+        CsvGridError::Io(error)
+    }
+}
+
+impl<T> VecGrid<T> {
+    /// Writes the grid as CSV, one line per row, calling `cell` to get the text
+    /// of each field.
+    ///
+    /// Fields are written as they are, with no quoting or escaping, so their
+    /// text mustn't contain commas or line breaks.
+    pub fn to_csv<W, F>(&self, mut writer: W, cell: F) -> io::Result<()>
+    where
+        W: Write,
+        F: Fn(&T) -> String,
+    {
+        for row in self.rows() {
+            for (x, value) in row.iter().enumerate() {
+                if x > 0 {
+                    writer.write_all(b",")?;
+                }
+                writer.write_all(cell(value).as_bytes())?;
+            }
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Reads a grid from CSV, calling `cell` with the text of each field and
+    /// its coordinate. The grid's origin is `(0, 0)`, with one row per line.
+    /// Fields are split on every comma, since quoting isn't supported.
+    ///
+    /// Every line must have as many fields as the first, or a
+    /// [`CsvGridError::Ragged`] is returned naming the first line that
+    /// doesn't. Parsing stops at the first field `cell` rejects.
+    pub fn from_csv<R, E, F>(reader: R, cell: F) -> Result<VecGrid<T>, CsvGridError<E>>
+    where
+        R: Read,
+        F: Fn(&str, Coord) -> Result<T, E>,
+    {
+        let mut cells = Vec::new();
+        let mut width = 0;
+        let mut height = 0;
+        for (y, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let row_start = cells.len();
+            for (x, field) in line.split(',').enumerate() {
+                let coord = Coord::new(x as i32, y as i32);
+                let value = cell(field, coord)
+                    .map_err(|error| CsvGridError::Cell(CellError { coord, error }))?;
+                cells.push(value);
+            }
+            let row_width = cells.len() - row_start;
+            if y == 0 {
+                width = row_width;
+            } else if row_width != width {
+                return Err(CsvGridError::Ragged {
+                    line: y + 1,
+                    expected: width,
+                    received: row_width,
+                });
+            }
+            height += 1;
+        }
+        let bounds = Rect::new((width as i32, height));
+        Ok(VecGrid::from_raw_parts(bounds, cells).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+    use std::num::ParseIntError;
+
+    #[test]
+    fn integers_round_trip() {
+        let grid = VecGrid::with_generator(Rect::new((4, 3)), |(x, y)| x * 10 - y * 7);
+        let mut bytes = Vec::new();
+        grid.to_csv(&mut bytes, |cell| cell.to_string()).unwrap();
+        assert_eq!(
+            String::from_utf8(bytes.clone()).unwrap(),
+            "0,10,20,30\n-7,3,13,23\n-14,-4,6,16\n"
+        );
+        let read = VecGrid::from_csv(&bytes[..], |field, _| field.parse::<i32>()).unwrap();
+        assert_eq!(read, grid);
+
+        let read = VecGrid::from_csv("".as_bytes(), |field, _| field.parse::<i32>()).unwrap();
+        assert_eq!(read.bounds, Rect::new((0, 0)));
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Terrain {
+        Grass,
+        Water,
+        Rock,
+    }
+
+    fn parse_terrain(field: &str, _: Coord) -> Result<Terrain, String> {
+        match field.trim() {
+            "grass" => Ok(Terrain::Grass),
+            "water" => Ok(Terrain::Water),
+            "rock" => Ok(Terrain::Rock),
+            other => Err(other.to_string()),
+        }
+    }
+
+    #[test]
+    fn enums_round_trip() {
+        // Spreadsheets tend to write `\r\n` line endings.
+        let text = "grass, water, grass\r\nrock, rock, water\r\n";
+        let grid = VecGrid::from_csv(text.as_bytes(), parse_terrain).unwrap();
+        assert_eq!(grid.bounds, Rect::new((3, 2)));
+        assert_eq!(grid.get((2, 1)), Some(&Terrain::Water));
+
+        let mut bytes = Vec::new();
+        grid.to_csv(&mut bytes, |cell| format!("{:?}", cell).to_lowercase())
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(bytes.clone()).unwrap(),
+            "grass,water,grass\nrock,rock,water\n"
+        );
+        assert_eq!(VecGrid::from_csv(&bytes[..], parse_terrain).unwrap(), grid);
+    }
+
+    #[test]
+    fn reports_bad_input() {
+        let ragged = VecGrid::from_csv("1,2\n3,4\n5\n".as_bytes(), |field, _| field.parse::<u8>());
+        match ragged {
+            Err(CsvGridError::Ragged {
+                line: 3,
+                expected: 2,
+                received: 1,
+            }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let typo: Result<_, CsvGridError<ParseIntError>> =
+            VecGrid::from_csv("1,2\n3,x\n".as_bytes(), |field, _| field.parse::<u8>());
+        match typo {
+            Err(CsvGridError::Cell(CellError { coord, .. })) => {
+                assert_eq!(coord, Coord::new(1, 1))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    coord::Coord,
+    grid::{Grid, GridMut},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+/// A value along with the tick it was last written at.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Stamped<T> {
+    tick: u64,
+    value: T,
+}
+
+impl<T> Stamped<T> {
+    /// Returns whether the value is still fresh at `now`. Values stamped
+    /// after `now` count as stamped at `now`.
+    fn is_fresh(&self, now: u64, max_age: u64) -> bool {
+        now.saturating_sub(self.tick) <= max_age
+    }
+}
+
+/// A grid of cells that remember when they were last written, for values
+/// that should only last a limited number of turns, such as scent trails,
+/// fires or explored tiles that fog over again.
+///
+/// Cells start out empty, and [`touch`](DecayGrid::touch) gives a cell a
+/// value stamped with the current tick. Ages are measured in whole ticks: a
+/// cell touched at tick `t` is fresh for a `max_age` of `n` from tick `t`
+/// through tick `t + n` inclusive, and stale from tick `t + n + 1` on. A
+/// `max_age` of 0 keeps cells fresh only during the tick they were touched.
+///
+/// Stale cells keep their value until they're swept by
+/// [`decay_pass`](DecayGrid::decay_pass), but are never returned by the
+/// reads taking a `max_age`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DecayGrid<T> {
+    cells: VecGrid<Option<Stamped<T>>>,
+}
+
+impl<T> DecayGrid<T> {
+    /// Constructs a grid with every cell empty.
+    pub fn new(bounds: Rect) -> Self {
+        Self {
+            cells: VecGrid::with_generator(bounds, |_: Coord| None),
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.cells.bounds
+    }
+
+    /// Sets the cell at `coord` to `value`, stamped with `now`, returning
+    /// false if it's out of bounds.
+    pub fn touch<C: Into<Coord>>(&mut self, coord: C, value: T, now: u64) -> bool {
+        self.cells.set(coord, Some(Stamped { tick: now, value }))
+    }
+
+    /// Returns the value of the cell at `coord` if it has one and it's still
+    /// fresh at `now`.
+    pub fn get_fresh<C: Into<Coord>>(&self, coord: C, now: u64, max_age: u64) -> Option<&T> {
+        match self.cells.get(coord) {
+            Some(Some(cell)) if cell.is_fresh(now, max_age) => Some(&cell.value),
+            _ => None,
+        }
+    }
+
+    /// Returns the tick the cell at `coord` was last touched at, whether or
+    /// not it's still fresh, or `None` if it's empty.
+    pub fn last_touched<C: Into<Coord>>(&self, coord: C) -> Option<u64> {
+        Some(self.cells.get(coord)?.as_ref()?.tick)
+    }
+
+    /// Empties the cell at `coord`, returning its value, fresh or not.
+    pub fn remove<C: Into<Coord>>(&mut self, coord: C) -> Option<T> {
+        Some(self.cells.get_mut(coord)?.take()?.value)
+    }
+
+    /// Empties every cell that's stale at `now`, in row-major order, handing
+    /// each one's coordinate and value to `on_expire`. Returns the number of
+    /// cells emptied.
+    pub fn decay_pass<F>(&mut self, now: u64, max_age: u64, mut on_expire: F) -> usize
+    where
+        F: FnMut(Coord, T),
+    {
+        let mut expired = 0;
+        for (coord, cell) in self.cells.iter_mut() {
+            if cell
+                .as_ref()
+                .is_some_and(|cell| !cell.is_fresh(now, max_age))
+            {
+                on_expire(coord, cell.take().unwrap().value);
+                expired += 1;
+            }
+        }
+        expired
+    }
+
+    /// Returns an iterator over the cells that are fresh at `now`, in
+    /// row-major order.
+    pub fn iter_fresh(&self, now: u64, max_age: u64) -> impl Iterator<Item = (Coord, &T)> {
+        self.cells
+            .iter()
+            .filter_map(move |(coord, cell)| match cell {
+                Some(cell) if cell.is_fresh(now, max_age) => Some((coord, &cell.value)),
+                _ => None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn freshness_boundaries() {
+        let mut scent = DecayGrid::new(Rect::new((4, 3)).translate((-1, 0)));
+        assert!(scent.touch((0, 1), 'a', 10));
+        assert!(!scent.touch((3, 1), 'x', 10));
+        assert_eq!(scent.get_fresh((0, 1), 10, 0), Some(&'a'));
+        assert_eq!(scent.get_fresh((0, 1), 11, 0), None);
+        assert_eq!(scent.get_fresh((0, 1), 13, 3), Some(&'a'));
+        assert_eq!(scent.get_fresh((0, 1), 14, 3), None);
+        // Ticks before the touch count as the tick itself.
+        assert_eq!(scent.get_fresh((0, 1), 2, 0), Some(&'a'));
+        assert_eq!(scent.get_fresh((1, 1), 10, 3), None);
+        assert_eq!(scent.last_touched((0, 1)), Some(10));
+
+        // Touching again restarts the clock.
+        scent.touch((0, 1), 'b', 14);
+        assert_eq!(scent.get_fresh((0, 1), 17, 3), Some(&'b'));
+        assert_eq!(scent.remove((0, 1)), Some('b'));
+        assert_eq!(scent.last_touched((0, 1)), None);
+    }
+
+    #[test]
+    fn sweeps_expired_cells() {
+        let mut fire = DecayGrid::new(Rect::new((3, 3)));
+        for (tick, coord) in [(0, 1), (1, 1), (1, 1), (2, 0)].iter().enumerate() {
+            fire.touch(*coord, tick, tick as u64);
+        }
+        fire.touch((0, 2), 10, 3);
+        assert_eq!(
+            fire.iter_fresh(3, 1).collect::<Vec<_>>(),
+            vec![
+                (Coord::new(2, 0), &3),
+                (Coord::new(1, 1), &2),
+                (Coord::new(0, 2), &10),
+            ]
+        );
+
+        let mut burnt_out = Vec::new();
+        assert_eq!(
+            fire.decay_pass(3, 1, |coord, value| burnt_out.push((coord, value))),
+            1
+        );
+        assert_eq!(burnt_out, vec![(Coord::new(0, 1), 0)]);
+        assert_eq!(fire.last_touched((0, 1)), None);
+        assert_eq!(fire.last_touched((1, 1)), Some(2));
+        assert_eq!(fire.decay_pass(3, 1, |_, _| panic!()), 0);
+        assert_eq!(fire.decay_pass(4, 1, |_, _| ()), 1);
+        assert_eq!(fire.decay_pass(5, 1, |_, _| ()), 2);
+        assert_eq!(fire.iter_fresh(0, u64::MAX).count(), 0);
+    }
+}
@@ -0,0 +1,70 @@
+use core::fmt;
+
+use crate::vecgrid::VecGrid;
+
+/// A grid drawn as text, with each row on its own line and the cells of a row
+/// written one after the other with no separators. Created with
+/// [`VecGrid::display`], and drawn through its `Display` impl.
+///
+/// Rows are drawn from top to bottom by default, with y growing downwards as
+/// in the grid's own coordinates. Code that treats +y as up can flip the
+/// output with [`y_up`](GridDisplay::y_up) so that it matches what's on
+/// screen, without changing any coordinates.
+#[derive(Debug, Clone, Copy)]
+pub struct GridDisplay<'a, T> {
+    grid: &'a VecGrid<T>,
+    y_up: bool,
+}
+
+impl<T> VecGrid<T> {
+    pub fn display(&self) -> GridDisplay<'_, T> {
+        GridDisplay {
+            grid: self,
+            y_up: false,
+        }
+    }
+}
+
+impl<'a, T> GridDisplay<'a, T> {
+    /// Sets whether rows are drawn from the bottom up, so that the row with
+    /// the highest y coordinate comes first.
+    pub fn y_up(mut self, y_up: bool) -> Self {
+        self.y_up = y_up;
+        self
+    }
+}
+
+impl<'a, T: fmt::Display> fmt::Display for GridDisplay<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (_, row) in self.grid.rows_oriented(self.y_up) {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::Rect;
+    use alloc::string::ToString;
+
+    #[test]
+    fn flips_rows() {
+        let grid = VecGrid::with_generator(Rect::new((2, 3)).translate((0, -1)), |(x, y)| {
+            x + (y + 1) * 2
+        });
+        assert_eq!(grid.display().to_string(), "01\n23\n45\n");
+        assert_eq!(grid.display().y_up(true).to_string(), "45\n23\n01\n");
+        assert_eq!(
+            VecGrid::<u8>::new(Rect::new((0, 2)))
+                .display()
+                .y_up(true)
+                .to_string(),
+            "\n\n"
+        );
+    }
+}
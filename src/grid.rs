@@ -1,6 +1,15 @@
-use std::mem;
+use alloc::{
+    collections::{BinaryHeap, VecDeque},
+    vec,
+    vec::Vec,
+};
+use core::{cmp::Reverse, fmt, mem, ops::Range};
 
-use crate::coord::Coord;
+use crate::{
+    coord::Coord,
+    patterns::{Connectivity, Line, Rect},
+    vecgrid::VecGrid,
+};
 
 /// The return type of all Grid iterators; a tuple of the cell coordinate and a
 /// reference to the cell data.
@@ -10,19 +19,638 @@ pub type IterCell<'a, T> = (Coord, &'a T);
 /// and a mutable reference to the cell data.
 pub type IterCellMut<'a, T> = (Coord, &'a mut T);
 
+/// The error type of fallible grid operations.
 #[derive(Debug, PartialEq, Eq)]
 pub enum GridError {
     /// The coordinate has no cell associated with it, as it's out of the grid
     /// bounds.
-    OutOfBounds(Coord),
+    OutOfBounds { coord: Coord, bounds: Rect },
     /// The coordinate has previously been mutably borrowed from the iterator,
     /// and doing so again would break safety guarantees.
     AlreadyVisited(Coord),
+    /// The row index lies outside of the grid's range of rows.
+    RowOutOfBounds { row: i32, rows: Range<i32> },
+    /// The column index lies outside of the grid's range of columns.
+    ColumnOutOfBounds { column: i32, columns: Range<i32> },
+    /// The number of values supplied doesn't match the number of cells they
+    /// were meant to fill.
+    WrongLength { expected: usize, received: usize },
+    /// A scale factor has a zero or negative component, or would scale the
+    /// grid past the limits of `i32` coordinates.
+    InvalidScale(Coord),
+    /// Two grids were expected to cover the same bounds, but don't.
+    BoundsMismatch { expected: Rect, received: Rect },
+    /// The operation only works on square regions, but the rect isn't one.
+    NotSquare(Rect),
+    /// The operation needs at least one cell, but the grid has zero area.
+    Empty,
+    /// A margin is negative, or wider than the grid can give up.
+    InvalidMargin(i32),
+    /// The dimensions are too large for a grid, whose coordinates and area
+    /// must fit in an `i32`.
+    TooLarge { width: u32, height: u32 },
 }
 
+/// Describes a rect by its size and top-left corner, e.g. `4x3 at (0, 0)`.
+fn describe_rect(rect: &Rect) -> impl fmt::Display {
+    struct Description(Rect);
+    impl fmt::Display for Description {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let rect = self.0;
+            write!(f, "{}x{} at {}", rect.width(), rect.height(), rect.offset())
+        }
+    }
+    Description(*rect)
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::OutOfBounds { coord, bounds } => write!(
+                f,
+                "{} is outside of the {} grid",
+                coord,
+                describe_rect(bounds)
+            ),
+            GridError::AlreadyVisited(coord) => {
+                write!(f, "the cell at {} was already visited", coord)
+            }
+            GridError::RowOutOfBounds { row, rows } => write!(
+                f,
+                "row {} is outside of the grid's rows {}..{}",
+                row, rows.start, rows.end
+            ),
+            GridError::ColumnOutOfBounds { column, columns } => write!(
+                f,
+                "column {} is outside of the grid's columns {}..{}",
+                column, columns.start, columns.end
+            ),
+            GridError::WrongLength { expected, received } => {
+                write!(f, "expected {} values, received {}", expected, received)
+            }
+            GridError::InvalidScale(scale) => {
+                write!(
+                    f,
+                    "scale {} must be positive on both axes and small enough for the grid",
+                    scale
+                )
+            }
+            GridError::BoundsMismatch { expected, received } => write!(
+                f,
+                "expected a {} grid, received a {} grid",
+                describe_rect(expected),
+                describe_rect(received)
+            ),
+            GridError::NotSquare(rect) => {
+                write!(f, "the {} region isn't square", describe_rect(rect))
+            }
+            GridError::Empty => write!(f, "the grid has no cells"),
+            GridError::InvalidMargin(margin) => {
+                write!(f, "margin {} is invalid for the grid", margin)
+            }
+            GridError::TooLarge { width, height } => {
+                write!(f, "a {}x{} grid is too large", width, height)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GridError {}
+
+/// An error produced while computing the value of a specific cell.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CellError<E> {
+    /// The coordinate of the cell being computed.
+    pub coord: Coord,
+    pub error: E,
+}
+
+impl<E: fmt::Display> fmt::Display for CellError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cell {}: {}", self.coord, self.error)
+    }
+}
+
+/// The reason [`VecGrid::get_many_mut`](crate::VecGrid::get_many_mut) failed,
+/// along with the position of the offending coordinate in its input.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GetManyError {
+    pub index: usize,
+    /// Either [`GridError::OutOfBounds`], or [`GridError::AlreadyVisited`]
+    /// when the coordinate appears earlier in the input.
+    pub error: GridError,
+}
+
+impl fmt::Display for GetManyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "coordinate {}: {}", self.index, self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GetManyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for CellError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// How coordinates past the edge of a grid are resolved to cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderMode {
+    /// Use the nearest edge cell.
+    Clamp,
+    /// Wrap around to the opposite edge, as if the grid were a torus.
+    Wrap,
+}
+
+/// How [`VecGrid::resample`](crate::VecGrid::resample) computes each output
+/// cell from the source grid. [`Nearest`] works for any cell type, while
+/// [`Bilinear`] interpolates float grids.
+pub trait ResampleMode<T> {
+    /// Returns the value of the output cell whose center lies at `(x, y)` in
+    /// `grid`, measured in cells from the top-left corner of its bounds, so
+    /// that the first cell covers the square from `(0, 0)` to `(1, 1)`.
+    fn sample(&self, grid: &VecGrid<T>, x: f64, y: f64) -> T;
+}
+
+/// Copies the source cell under the center of each output cell
+/// (nearest-neighbor scaling).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Nearest;
+
+impl<T: Clone> ResampleMode<T> for Nearest {
+    fn sample(&self, grid: &VecGrid<T>, x: f64, y: f64) -> T {
+        // Positions are never negative, so truncating rounds them down. Only
+        // the far edge needs clamping, for scales that don't divide evenly.
+        let nearest = |position: f64, len: i32| (position as i32).min(len - 1);
+        let bounds = grid.bounds;
+        let coord = Coord::new(
+            bounds.left + nearest(x, bounds.width()),
+            bounds.top + nearest(y, bounds.height()),
+        );
+        grid.get(coord).unwrap().clone()
+    }
+}
+
+/// Interpolates between the source cells around the center of each output
+/// cell with [`VecGrid::sample_bilinear`](crate::VecGrid::sample_bilinear),
+/// clamping at the edges.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Bilinear;
+
+#[cfg(feature = "std")]
+macro_rules! impl_bilinear {
+    ($($t:ty),*) => {
+        $(
+            impl ResampleMode<$t> for Bilinear {
+                fn sample(&self, grid: &VecGrid<$t>, x: f64, y: f64) -> $t {
+                    // Cell values lie at the centers of their coordinates.
+                    let x = grid.bounds.left as f64 + x - 0.5;
+                    let y = grid.bounds.top as f64 + y - 0.5;
+                    grid.sample_bilinear(x, y, BorderMode::Clamp).unwrap() as $t
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "std")]
+impl_bilinear!(f32, f64);
+
+/// How [`VecGrid::shift`](crate::VecGrid::shift) treats cells pushed past
+/// the edge of the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShiftMode<T> {
+    /// Cells pushed off one edge reappear on the opposite edge.
+    Wrap,
+    /// Cells pushed off the edge are discarded, and the cells left exposed are
+    /// filled with clones of the value.
+    Fill(T),
+}
+
+/// How [`VecGrid::padded`](crate::VecGrid::padded) fills the margin around a
+/// grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PadMode<T> {
+    /// Fill the margin with clones of the value.
+    Constant(T),
+    /// Repeat the nearest edge cell.
+    ClampEdge,
+    /// Continue from the opposite edge, as if the grid were a torus.
+    Wrap,
+}
+
+/// Read access to the cells of a grid.
+///
+/// Algorithms are written in terms of this trait, so they work the same over a
+/// [`VecGrid`], a [`GridView`](crate::GridView), a
+/// [`SparseGrid`](crate::SparseGrid), or any grid type of your own that
+/// implements [`get`](Grid::get), [`bounds`](Grid::bounds) and
+/// [`iter`](Grid::iter).
 pub trait Grid<T> {
+    /// Returns the cell at `coord`, or `None` if there is no cell there.
     fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T>;
 
+    /// Returns a rect containing every cell of the grid. Algorithms which
+    /// produce a [`VecGrid`] produce one with these bounds.
+    fn bounds(&self) -> Rect;
+
+    /// Returns an iterator over every cell of the grid.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a;
+
+    //
+    // DEFAULT IMPLEMENTATIONS
+    //
+
+    fn dimensions(&self) -> Coord {
+        self.bounds().dimensions()
+    }
+
+    /// Returns whether there is a cell at `coord`.
+    fn contains<C: Into<Coord>>(&self, coord: C) -> bool {
+        self.get(coord).is_some()
+    }
+
+    /// Returns the cell at `coord`, or `default` if it's out of bounds.
+    fn get_or<'a, C: Into<Coord>>(&'a self, coord: C, default: &'a T) -> &'a T {
+        self.get(coord).unwrap_or(default)
+    }
+
+    /// Returns a copy of the cell at `coord`.
+    fn get_copied<C: Into<Coord>>(&self, coord: C) -> Option<T>
+    where
+        T: Copy,
+    {
+        self.get(coord).copied()
+    }
+
+    /// Returns an iterator over the cells at each of `coords`, looking each
+    /// one up as it's reached. Coords without a cell yield a
+    /// [`GridError::OutOfBounds`].
+    fn selection_iter<'a, I>(
+        &'a self,
+        coords: I,
+    ) -> impl Iterator<Item = Result<IterCell<'a, T>, GridError>>
+    where
+        T: 'a,
+        I: IntoIterator,
+        I::Item: Into<Coord>,
+    {
+        coords.into_iter().map(move |coord| {
+            let coord = coord.into();
+            self.get(coord)
+                .map(|cell| (coord, cell))
+                .ok_or_else(|| GridError::OutOfBounds {
+                    coord,
+                    bounds: self.bounds(),
+                })
+        })
+    }
+
+    /// Returns an iterator over the in-bounds cells of the orthogonal and
+    /// diagonal (Moore) neighborhood of `coord`.
+    ///
+    /// `coord` itself doesn't need to be in bounds.
+    fn neighbors<'a, C>(&'a self, coord: C) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+        C: Into<Coord>,
+    {
+        self.selection_iter(Connectivity::Eight.neighbors(coord))
+            .filter_map(Result::ok)
+    }
+
+    /// Returns an iterator over the in-bounds cells of the orthogonal (Von
+    /// Neumann) neighborhood of `coord`.
+    ///
+    /// `coord` itself doesn't need to be in bounds.
+    fn ortho_neighbors<'a, C>(&'a self, coord: C) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+        C: Into<Coord>,
+    {
+        self.selection_iter(Connectivity::Four.neighbors(coord))
+            .filter_map(Result::ok)
+    }
+
+    /// Returns whether `to` can be seen from `from`, meaning no cell strictly
+    /// between them satisfies `blocks`. The endpoints themselves are never
+    /// tested, and cells outside the grid always block.
+    ///
+    /// The line is traced with [`Line::iter_supercover`], so the result is the
+    /// same in both directions.
+    fn line_of_sight<C1, C2, F>(&self, from: C1, to: C2, blocks: F) -> bool
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+        F: Fn(Coord, &T) -> bool,
+    {
+        let from = from.into();
+        let to = to.into();
+        Line::new(from, to)
+            .iter_supercover()
+            .filter(|&coord| coord != from && coord != to)
+            .all(|coord| match self.get(coord) {
+                Some(cell) => !blocks(coord, cell),
+                None => false,
+            })
+    }
+
+    /// Returns a grid with the same bounds marking every cell visible from
+    /// `origin`: those within `radius` (by euclidean distance) that have
+    /// [`line_of_sight`](Grid::line_of_sight) to it. Opaque cells can be
+    /// seen themselves, so walls bordering visible floor are marked visible.
+    ///
+    /// The origin is always visible, unless it lies outside the grid, in which
+    /// case every cell is marked not visible.
+    fn field_of_view<C, F>(&self, origin: C, radius: i32, opaque: F) -> VecGrid<bool>
+    where
+        C: Into<Coord>,
+        F: Fn(Coord, &T) -> bool,
+    {
+        let origin = origin.into();
+        let radius = radius.max(0) as i64;
+        let origin_in_bounds = self.contains(origin);
+        VecGrid::with_generator(self.bounds(), |coord: Coord| {
+            let delta = coord - origin;
+            let distance_squared = (delta.x as i64).pow(2) + (delta.y as i64).pow(2);
+            origin_in_bounds
+                && distance_squared <= radius * radius
+                && self.line_of_sight(origin, coord, &opaque)
+        })
+    }
+
+    /// Returns a grid with the same bounds holding the number of steps from
+    /// each cell to the nearest of `goals`, moving between passable cells of
+    /// the Moore neighborhood (so diagonal steps cost the same as orthogonal
+    /// ones). Impassable and unreachable cells hold `None`, and goals which
+    /// are impassable or out of bounds are ignored.
+    fn distance_map<I, F>(&self, goals: I, passable: F) -> VecGrid<Option<u32>>
+    where
+        I: IntoIterator<Item = Coord>,
+        F: Fn(Coord, &T) -> bool,
+    {
+        let mut distances = VecGrid::filled(self.bounds(), None);
+        let mut queue = VecDeque::new();
+        for goal in goals {
+            if let (Some(cell), Some(distance)) = (self.get(goal), distances.get_mut(goal)) {
+                if distance.is_none() && passable(goal, cell) {
+                    *distance = Some(0);
+                    queue.push_back(goal);
+                }
+            }
+        }
+
+        while let Some(coord) = queue.pop_front() {
+            let next_distance = distances.get(coord).copied().flatten().map(|d| d + 1);
+            for (neighbor, cell) in self.neighbors(coord) {
+                let distance = distances.get_mut(neighbor).unwrap();
+                if distance.is_none() && passable(neighbor, cell) {
+                    *distance = next_distance;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Returns a grid with the same bounds where each passable cell holds the
+    /// unit offset towards its neighbor closest to `goals`, as measured by
+    /// [`distance_map`](Grid::distance_map). Following the offsets from
+    /// any reachable cell leads to a goal along a shortest path.
+    ///
+    /// Goals, impassable cells and unreachable cells hold `None`. When several
+    /// neighbors are equally close, the first one in the order of
+    /// [`Neighborhood::moore`](crate::patterns::Neighborhood::moore) wins.
+    fn flow_field<I, F>(&self, goals: I, passable: F) -> VecGrid<Option<Coord>>
+    where
+        I: IntoIterator<Item = Coord>,
+        F: Fn(Coord, &T) -> bool,
+    {
+        let distances = self.distance_map(goals, passable);
+        VecGrid::with_generator(self.bounds(), |coord: Coord| {
+            match distances.get(coord).copied().flatten() {
+                None | Some(0) => None,
+                Some(_) => distances
+                    .neighbors(coord)
+                    .filter_map(|(neighbor, distance)| Some((neighbor, (*distance)?)))
+                    .min_by_key(|&(_, distance)| distance)
+                    .map(|(neighbor, _)| neighbor - coord),
+            }
+        })
+    }
+
+    /// Finds the cheapest orthogonal path from `start` to `goal`, returning
+    /// every coordinate along it (including both ends) and its total cost.
+    ///
+    /// `cost` gives the price of stepping onto a cell, or `None` if the cell
+    /// can't be entered. The start cell's own cost is never paid. Among paths
+    /// of equal cost, the one found first is returned, which is deterministic
+    /// for a given grid.
+    fn dijkstra_path<C1, C2, F>(&self, start: C1, goal: C2, cost: F) -> Option<(Vec<Coord>, u32)>
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+        F: Fn(Coord, &T) -> Option<u32>,
+    {
+        self.astar_path(start, goal, cost, |_| 0)
+    }
+
+    /// Like [`dijkstra_path`](Grid::dijkstra_path), but guided by
+    /// `heuristic`, an estimate of the remaining cost from a coordinate to
+    /// the goal.
+    ///
+    /// The heuristic is in the same units as `cost`, and must never
+    /// overestimate for the returned path to be the cheapest. Since movement
+    /// is orthogonal, the manhattan distance to the goal multiplied by the
+    /// cheapest possible step cost is always a safe choice.
+    fn astar_path<C1, C2, F, H>(
+        &self,
+        start: C1,
+        goal: C2,
+        cost: F,
+        heuristic: H,
+    ) -> Option<(Vec<Coord>, u32)>
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+        F: Fn(Coord, &T) -> Option<u32>,
+        H: Fn(Coord) -> u32,
+    {
+        let goal = goal.into();
+        if !self.contains(goal) {
+            return None;
+        }
+        cheapest_path(
+            self,
+            start.into(),
+            |coord| coord == goal,
+            cost,
+            heuristic,
+            u32::MAX,
+        )
+    }
+
+    /// Finds a shortest orthogonal path from `start` to whichever of `goals`
+    /// is closest, moving between passable cells, and returns every
+    /// coordinate along it.
+    ///
+    /// All goals are searched for at once, so this costs a single search
+    /// however many goals there are. Goals which are impassable or out of
+    /// bounds are never reached, but they don't keep the others from being
+    /// found. As with [`dijkstra_path`](Grid::dijkstra_path), the start cell
+    /// doesn't need to be passable.
+    fn path_to_nearest<C, I, F>(&self, start: C, goals: I, passable: F) -> Option<Vec<Coord>>
+    where
+        C: Into<Coord>,
+        I: IntoIterator<Item = Coord>,
+        F: Fn(Coord, &T) -> bool,
+    {
+        let mut is_goal = VecGrid::filled(self.bounds(), false);
+        let mut any_goals = false;
+        for goal in goals {
+            any_goals |= is_goal.set(goal, true);
+        }
+        if !any_goals {
+            return None;
+        }
+        let is_goal = |coord| is_goal.get(coord) == Some(&true);
+        let cost = |coord, cell: &T| if passable(coord, cell) { Some(1) } else { None };
+        cheapest_path(self, start.into(), is_goal, cost, |_| 0, u32::MAX).map(|(path, _)| path)
+    }
+
+    /// Finds a shortest orthogonal path from `start` to `goal` moving between
+    /// passable cells, like [`path_to_nearest`](Grid::path_to_nearest) with a
+    /// single goal, but only if it takes at most `max_cost` steps.
+    ///
+    /// Cells that can't be part of a path within the budget are never
+    /// explored, so a goal far out of reach is given up on right away rather
+    /// than after searching the whole grid.
+    fn path_with_budget<C1, C2, F>(
+        &self,
+        start: C1,
+        goal: C2,
+        passable: F,
+        max_cost: u32,
+    ) -> Option<Vec<Coord>>
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+        F: Fn(Coord, &T) -> bool,
+    {
+        let goal = goal.into();
+        if !self.contains(goal) {
+            return None;
+        }
+        let cost = |coord, cell: &T| if passable(coord, cell) { Some(1) } else { None };
+        let manhattan = |coord: Coord| {
+            let delta = goal - coord;
+            delta
+                .x
+                .unsigned_abs()
+                .saturating_add(delta.y.unsigned_abs())
+        };
+        cheapest_path(
+            self,
+            start.into(),
+            |coord| coord == goal,
+            cost,
+            manhattan,
+            max_cost,
+        )
+        .map(|(path, _)| path)
+    }
+}
+
+/// The search behind the pathfinding methods of [`Grid`]: finds the cheapest
+/// orthogonal path from `start` to any coordinate accepted by `is_goal`.
+///
+/// Cells whose cost plus `heuristic` exceeds `max_cost` are never queued, so
+/// with a heuristic that never overestimates, no path within the budget is
+/// missed, and nothing beyond it is explored.
+pub(crate) fn cheapest_path<T, G, P, F, H>(
+    grid: &G,
+    start: Coord,
+    is_goal: P,
+    cost: F,
+    heuristic: H,
+    max_cost: u32,
+) -> Option<(Vec<Coord>, u32)>
+where
+    G: Grid<T> + ?Sized,
+    P: Fn(Coord) -> bool,
+    F: Fn(Coord, &T) -> Option<u32>,
+    H: Fn(Coord) -> u32,
+{
+    if !grid.contains(start) || heuristic(start) > max_cost {
+        return None;
+    }
+
+    let mut best_costs = VecGrid::filled(grid.bounds(), None);
+    let mut came_from = VecGrid::filled(grid.bounds(), None);
+    // Entries are ordered by estimated total cost, then by insertion order
+    // to break ties deterministically.
+    let mut open = BinaryHeap::new();
+    let mut pushed = 0usize;
+    best_costs.set(start, Some(0));
+    open.push(Reverse((heuristic(start), pushed, 0, start.x, start.y)));
+
+    while let Some(Reverse((_, _, path_cost, x, y))) = open.pop() {
+        let coord = Coord::new(x, y);
+        if is_goal(coord) {
+            let mut path = vec![coord];
+            while let Some(&Some(previous)) = came_from.get(*path.last().unwrap()) {
+                path.push(previous);
+            }
+            path.reverse();
+            return Some((path, path_cost));
+        }
+        // Skip entries superseded by a cheaper route to the same cell.
+        if best_costs.get(coord) != Some(&Some(path_cost)) {
+            continue;
+        }
+
+        // Taking neighbors from the grid, rather than offsetting `coord`,
+        // lets grids such as `WrappingGrid` hand back in-bounds coordinates.
+        for (neighbor, cell) in grid.ortho_neighbors(coord) {
+            if let Some(step_cost) = cost(neighbor, cell) {
+                let neighbor_cost = path_cost.saturating_add(step_cost);
+                let best_cost = best_costs.get_mut(neighbor).unwrap();
+                if best_cost.is_some_and(|best_cost| neighbor_cost >= best_cost) {
+                    continue;
+                }
+                let estimate = neighbor_cost.saturating_add(heuristic(neighbor));
+                if estimate <= max_cost {
+                    *best_cost = Some(neighbor_cost);
+                    came_from.set(neighbor, Some(coord));
+                    pushed += 1;
+                    open.push(Reverse((
+                        estimate,
+                        pushed,
+                        neighbor_cost,
+                        neighbor.x,
+                        neighbor.y,
+                    )));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Write access to the cells of a grid.
+pub trait GridMut<T>: Grid<T> {
     fn get_mut<C: Into<Coord>>(&mut self, coord: C) -> Option<&mut T>;
 
     fn copy<C1, C2>(&mut self, src: C1, dest: C2) -> bool
@@ -57,14 +685,124 @@ pub trait Grid<T> {
     }
 
     fn replace<C: Into<Coord>>(&mut self, coord: C, value: T) -> Option<T> {
-        self.get_mut(coord)
-            .and_then(|cell| Some(mem::replace(cell, value)))
+        self.get_mut(coord).map(|cell| mem::replace(cell, value))
     }
 
     fn take<C: Into<Coord>>(&mut self, coord: C) -> Option<T>
     where
         T: Default,
     {
-        self.get_mut(coord).and_then(|cell| Some(mem::take(cell)))
+        self.get_mut(coord).map(|cell| mem::take(cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::SparseGrid;
+
+    #[cfg(feature = "std")]
+    #[rustfmt::skip]
+    const FLOOR: [u8; 20] = [
+        1, 1, 1, 1, 1,
+        0, 0, 0, 1, 0,
+        1, 1, 1, 1, 0,
+        1, 0, 0, 0, 0,
+    ];
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn algorithms_work_over_every_grid() {
+        let bounds = Rect::new((5, 4));
+        let dense = VecGrid::from_raw_parts(bounds, FLOOR.to_vec()).unwrap();
+        let sparse = dense
+            .iter()
+            .filter(|(_, &cell)| cell == 1)
+            .map(|(coord, &cell)| (coord, cell))
+            .collect::<SparseGrid<_>>();
+        let view = dense.view(bounds).unwrap();
+        let cost = |_, &cell: &u8| if cell == 1 { Some(1) } else { None };
+
+        let expected = dense.dijkstra_path((0, 0), (0, 3), cost).unwrap();
+        assert_eq!(expected.1, 9);
+        assert_eq!(
+            sparse.dijkstra_path((0, 0), (0, 3), cost),
+            Some(expected.clone())
+        );
+        assert_eq!(view.dijkstra_path((0, 0), (0, 3), cost), Some(expected));
+
+        let passable = |_, &cell: &u8| cell == 1;
+        let goals = vec![Coord::new(0, 0)];
+        assert_eq!(
+            sparse.distance_map(goals.clone(), passable),
+            dense.distance_map(goals, passable)
+        );
+        assert_eq!(sparse.ortho_neighbors((3, 1)).count(), 2);
+        assert_eq!(view.neighbors((4, 3)).count(), 3);
+    }
+
+    #[test]
+    fn view_mut_writes() {
+        let mut grid = VecGrid::with_generator(Rect::new((3, 3)), |(x, y)| x + y * 3);
+        let mut view = grid.view_mut(Rect::with_corners((1, 1), (3, 3))).unwrap();
+        assert!(view.swap((0, 0), (1, 1)));
+        assert!(!view.swap((0, 0), (2, 0)));
+        assert!(view.copy((1, 0), (0, 1)));
+        assert_eq!(view.mov(Coord::new(0, 0), Coord::new(1, 0)), Some(5));
+        assert!(view.set((1, 1), 9));
+        assert_eq!(grid.cells, vec![0, 1, 2, 3, 0, 8, 6, 5, 9]);
+    }
+
+    #[test]
+    fn errors_display() {
+        let grid = VecGrid::filled(Rect::new((4, 3)).translate((-1, 2)), 0u8);
+        let error = grid.selection_iter([(3, 2)]).next().unwrap().unwrap_err();
+        assert_eq!(
+            error,
+            GridError::OutOfBounds {
+                coord: Coord::new(3, 2),
+                bounds: grid.bounds
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "(3, 2) is outside of the 4x3 at (-1, 2) grid"
+        );
+        assert_eq!(
+            GridError::WrongLength {
+                expected: 12,
+                received: 3
+            }
+            .to_string(),
+            "expected 12 values, received 3"
+        );
+
+        let cell_error = CellError {
+            coord: Coord::new(1, 0),
+            error: "x".parse::<u8>().unwrap_err(),
+        };
+        assert_eq!(
+            cell_error.to_string(),
+            "cell (1, 0): invalid digit found in string"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn errors_propagate_with_question_mark() {
+        fn first_cell(grid: &VecGrid<u8>) -> Result<u8, Box<dyn std::error::Error>> {
+            let (_, &cell) = grid.selection_iter([(0, 0)]).next().unwrap()?;
+            Ok(cell)
+        }
+        assert_eq!(
+            first_cell(&VecGrid::filled(Rect::new((1, 1)), 7)).unwrap(),
+            7
+        );
+        let error = first_cell(&VecGrid::new(Rect::new((0, 0)))).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "(0, 0) is outside of the 0x0 at (0, 0) grid"
+        );
     }
 }
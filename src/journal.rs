@@ -0,0 +1,284 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{
+    coord::Coord,
+    grid::{Grid, GridError, GridMut, IterCell},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+/// Write access to a grid that remembers the value each cell had before it
+/// was first written to. Created by [`VecGrid::edit`].
+///
+/// Reads and writes go through the [`Grid`] and [`GridMut`] traits, so tools
+/// written against those work unchanged inside a transaction.
+#[derive(Debug)]
+pub struct GridTransaction<'a, T> {
+    grid: &'a mut VecGrid<T>,
+    /// The original value of every touched cell, keyed by its index.
+    originals: BTreeMap<usize, T>,
+}
+
+impl<'a, T: Clone> GridTransaction<'a, T> {
+    /// Returns the index of the cell at `coord`, saving its value first if
+    /// this is the first time the transaction has touched it.
+    fn touch(&mut self, coord: Coord) -> Option<usize> {
+        let index = VecGrid::<T>::coord_to_index_with_bounds(self.grid.bounds, coord)?;
+        let cells = &self.grid.cells;
+        self.originals
+            .entry(index)
+            .or_insert_with(|| cells[index].clone());
+        Some(index)
+    }
+}
+
+impl<'a, T> Grid<T> for GridTransaction<'a, T> {
+    fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        self.grid.get(coord)
+    }
+
+    fn bounds(&self) -> Rect {
+        self.grid.bounds
+    }
+
+    fn iter<'b>(&'b self) -> impl Iterator<Item = IterCell<'b, T>>
+    where
+        T: 'b,
+    {
+        self.grid.iter()
+    }
+}
+
+impl<'a, T: Clone> GridMut<T> for GridTransaction<'a, T> {
+    fn get_mut<C: Into<Coord>>(&mut self, coord: C) -> Option<&mut T> {
+        let index = self.touch(coord.into())?;
+        self.grid.cells.get_mut(index)
+    }
+
+    fn copy<C1, C2>(&mut self, src: C1, dest: C2) -> bool
+    where
+        T: Copy,
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        let (src, dest) = (src.into(), dest.into());
+        if !self.grid.contains(src) || self.touch(dest).is_none() {
+            return false;
+        }
+        self.grid.copy(src, dest)
+    }
+
+    fn swap<C1, C2>(&mut self, coord1: C1, coord2: C2) -> bool
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        let (coord1, coord2) = (coord1.into(), coord2.into());
+        if !(self.grid.contains(coord1) && self.grid.contains(coord2)) {
+            return false;
+        }
+        self.touch(coord1);
+        self.touch(coord2);
+        self.grid.swap(coord1, coord2)
+    }
+
+    fn mov(&mut self, src: Coord, dest: Coord) -> Option<T>
+    where
+        T: Default,
+    {
+        if !(self.grid.contains(src) && self.grid.contains(dest)) {
+            return None;
+        }
+        self.touch(src);
+        self.touch(dest);
+        self.grid.mov(src, dest)
+    }
+}
+
+/// One cell of a [`ChangeSet`], with its values from before and after the
+/// edit.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CellChange<T> {
+    coord: Coord,
+    old: T,
+    new: T,
+}
+
+/// The cells changed by one call to [`VecGrid::edit`], which can be undone and
+/// redone later.
+///
+/// Each cell appears once however many times it was written to, holding its
+/// value from before the edit and its final value. Cells that were written
+/// back to their original value are still included.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChangeSet<T> {
+    bounds: Rect,
+    changes: Vec<CellChange<T>>,
+}
+
+impl<T: Clone> ChangeSet<T> {
+    /// Restores every changed cell of `grid` to its value from before the
+    /// edit.
+    ///
+    /// Returns [`GridError::BoundsMismatch`] without changing anything if
+    /// `grid` doesn't have the bounds of the grid that was edited.
+    pub fn undo(&self, grid: &mut VecGrid<T>) -> Result<(), GridError> {
+        self.apply(grid, |change| &change.old)
+    }
+
+    /// Writes every changed cell of `grid` back to its value from after the
+    /// edit.
+    ///
+    /// Returns [`GridError::BoundsMismatch`] without changing anything if
+    /// `grid` doesn't have the bounds of the grid that was edited.
+    pub fn redo(&self, grid: &mut VecGrid<T>) -> Result<(), GridError> {
+        self.apply(grid, |change| &change.new)
+    }
+
+    fn apply<F>(&self, grid: &mut VecGrid<T>, value: F) -> Result<(), GridError>
+    where
+        F: Fn(&CellChange<T>) -> &T,
+    {
+        if grid.bounds != self.bounds {
+            return Err(GridError::BoundsMismatch {
+                expected: self.bounds,
+                received: grid.bounds,
+            });
+        }
+        for change in self.changes.iter() {
+            grid.set(change.coord, value(change).clone());
+        }
+        Ok(())
+    }
+}
+
+impl<T> ChangeSet<T> {
+    /// Returns the number of cells changed.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the coordinates of the changed cells, in row-major order.
+    pub fn coords(&self) -> impl Iterator<Item = Coord> + '_ {
+        self.changes.iter().map(|change| change.coord)
+    }
+}
+
+impl<T: Clone> VecGrid<T> {
+    /// Runs `f` on a [`GridTransaction`] over the grid, and returns the cells
+    /// it changed as a [`ChangeSet`] for undoing the edit later.
+    ///
+    /// Only the cells `f` writes to are recorded, however it writes to them,
+    /// so the cost of an edit grows with the number of cells touched rather
+    /// than with the size of the grid.
+    pub fn edit<F>(&mut self, f: F) -> ChangeSet<T>
+    where
+        F: FnOnce(&mut GridTransaction<'_, T>),
+    {
+        let mut transaction = GridTransaction {
+            grid: self,
+            originals: BTreeMap::new(),
+        };
+        f(&mut transaction);
+
+        let GridTransaction { grid, originals } = transaction;
+        let changes = originals
+            .into_iter()
+            .map(|(index, old)| CellChange {
+                coord: VecGrid::<T>::index_to_coord_with_bounds(grid.bounds, index),
+                old,
+                new: grid.cells[index].clone(),
+            })
+            .collect();
+        ChangeSet {
+            bounds: grid.bounds,
+            changes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::Line;
+
+    fn canvas() -> VecGrid<char> {
+        VecGrid::filled(Rect::new((6, 4)).translate((-1, -1)), '.')
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut grid = canvas();
+        let before = grid.clone();
+        let changes = grid.edit(|tx| {
+            for coord in Line::new((-1, -1), (4, 2)).iter() {
+                tx.set(coord, '#');
+            }
+            tx.set((0, 2), '@');
+            // Out of bounds writes don't touch anything.
+            assert!(!tx.set((9, 9), '!'));
+        });
+        let after = grid.clone();
+        assert_eq!(changes.len(), 7);
+        assert_eq!(after.count_value(&'#'), 6);
+        assert_eq!(after.get((0, 2)), Some(&'@'));
+
+        changes.undo(&mut grid).unwrap();
+        assert_eq!(grid, before);
+        changes.redo(&mut grid).unwrap();
+        assert_eq!(grid, after);
+    }
+
+    #[test]
+    fn repeated_writes_coalesce() {
+        let mut grid = canvas();
+        let changes = grid.edit(|tx| {
+            for c in "abcdef".chars() {
+                tx.set((2, 1), c);
+                *tx.get_mut((3, 1)).unwrap() = c;
+            }
+            tx.swap((2, 1), (-1, -1));
+            tx.set((0, 0), 'x');
+            tx.set((0, 0), '.');
+        });
+        assert_eq!(
+            changes.coords().collect::<Vec<_>>(),
+            vec![
+                Coord::new(-1, -1),
+                Coord::new(0, 0),
+                Coord::new(2, 1),
+                Coord::new(3, 1),
+            ]
+        );
+        assert_eq!(grid.get((-1, -1)), Some(&'f'));
+
+        changes.undo(&mut grid).unwrap();
+        assert_eq!(grid, canvas());
+    }
+
+    #[test]
+    fn bounds_must_match() {
+        let mut grid = canvas();
+        let changes = grid.edit(|tx| {
+            tx.set((0, 0), '#');
+        });
+        let mut resized = VecGrid::filled(Rect::new((6, 4)), '#');
+        assert_eq!(
+            changes.undo(&mut resized),
+            Err(GridError::BoundsMismatch {
+                expected: canvas().bounds,
+                received: resized.bounds,
+            })
+        );
+        assert_eq!(resized.count_value(&'#'), 24);
+
+        let nothing = grid.edit(|tx| {
+            assert_eq!(tx.get((0, 0)), Some(&'#'));
+        });
+        assert!(nothing.is_empty());
+    }
+}
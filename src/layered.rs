@@ -0,0 +1,217 @@
+use alloc::vec::Vec;
+
+use crate::{
+    coord::Coord,
+    grid::{Grid, GridError, GridMut},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+/// A stack of grids which always share the same bounds, such as the terrain,
+/// item and actor layers of a map.
+///
+/// Layers are indexed from the bottom of the stack. Operations which change
+/// the bounds, like [`resize`](LayeredGrid::resize) and
+/// [`crop`](LayeredGrid::crop), apply to every layer at once. Changing a
+/// layer's bounds directly through [`layer_mut`](LayeredGrid::layer_mut)
+/// breaks the alignment, and isn't supported.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LayeredGrid<T> {
+    layers: Vec<VecGrid<T>>,
+    bounds: Rect,
+}
+
+impl<T> LayeredGrid<T> {
+    /// Constructs a grid covering `bounds` with no layers.
+    pub fn new(bounds: Rect) -> Self {
+        Self {
+            layers: Vec::new(),
+            bounds,
+        }
+    }
+
+    /// Constructs a grid covering `bounds` from existing layers.
+    ///
+    /// Returns [`GridError::BoundsMismatch`] for the first layer that doesn't
+    /// cover `bounds`.
+    pub fn from_layers(bounds: Rect, layers: Vec<VecGrid<T>>) -> Result<Self, GridError> {
+        let mut grid = Self::new(bounds);
+        for layer in layers.iter() {
+            grid.check_bounds_match(layer)?;
+        }
+        grid.layers = layers;
+        Ok(grid)
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn layer(&self, index: usize) -> Option<&VecGrid<T>> {
+        self.layers.get(index)
+    }
+
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut VecGrid<T>> {
+        self.layers.get_mut(index)
+    }
+
+    /// Returns every layer, from the bottom up.
+    pub fn layers(&self) -> &[VecGrid<T>] {
+        &self.layers
+    }
+
+    pub fn into_layers(self) -> Vec<VecGrid<T>> {
+        self.layers
+    }
+
+    /// Adds a layer on top of the stack, returning
+    /// [`GridError::BoundsMismatch`] if it doesn't cover the grid bounds.
+    pub fn push_layer(&mut self, layer: VecGrid<T>) -> Result<(), GridError> {
+        self.check_bounds_match(&layer)?;
+        self.layers.push(layer);
+        Ok(())
+    }
+
+    /// Inserts a layer at `index`, shifting the layers above it up. See
+    /// [`push_layer`](LayeredGrid::push_layer).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of layers.
+    pub fn insert_layer(&mut self, index: usize, layer: VecGrid<T>) -> Result<(), GridError> {
+        self.check_bounds_match(&layer)?;
+        self.layers.insert(index, layer);
+        Ok(())
+    }
+
+    /// Removes and returns the layer at `index`, if there is one.
+    pub fn remove_layer(&mut self, index: usize) -> Option<VecGrid<T>> {
+        if index < self.layers.len() {
+            Some(self.layers.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the cell at `coord` in each layer, from the
+    /// bottom up. Nothing is yielded if `coord` is out of bounds.
+    pub fn iter_column<C: Into<Coord>>(&self, coord: C) -> impl Iterator<Item = &T> {
+        let coord = coord.into();
+        let coord = Some(coord).filter(|&coord| self.bounds.contains(coord));
+        self.layers
+            .iter()
+            .filter_map(move |layer| layer.get(coord?))
+    }
+
+    /// Mutable version of [`iter_column`](LayeredGrid::iter_column).
+    pub fn iter_column_mut<C: Into<Coord>>(&mut self, coord: C) -> impl Iterator<Item = &mut T> {
+        let coord = coord.into();
+        let coord = Some(coord).filter(|&coord| self.bounds.contains(coord));
+        self.layers
+            .iter_mut()
+            .filter_map(move |layer| layer.get_mut(coord?))
+    }
+
+    /// Resizes every layer to `bounds`, as in [`VecGrid::resize`].
+    pub fn resize(&mut self, bounds: Rect, fill: T)
+    where
+        T: Clone,
+    {
+        for layer in self.layers.iter_mut() {
+            layer.resize(bounds, fill.clone());
+        }
+        self.bounds = bounds;
+    }
+
+    /// Crops every layer to `rect`, as in [`VecGrid::crop`]. If `rect` doesn't
+    /// fit, no layer is changed.
+    pub fn crop(&mut self, rect: Rect) -> Result<(), GridError> {
+        VecGrid::<T>::check_region_with_bounds(self.bounds, rect)?;
+        for layer in self.layers.iter_mut() {
+            layer.crop(rect)?;
+        }
+        self.bounds = rect;
+        Ok(())
+    }
+
+    fn check_bounds_match(&self, layer: &VecGrid<T>) -> Result<(), GridError> {
+        if layer.bounds != self.bounds {
+            return Err(GridError::BoundsMismatch {
+                expected: self.bounds,
+                received: layer.bounds,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layers() -> LayeredGrid<char> {
+        let bounds = Rect::new((3, 2)).translate((-1, 0));
+        let layers = vec![
+            VecGrid::filled(bounds, '.'),
+            VecGrid::filled(bounds, ' '),
+            VecGrid::with_generator(bounds, |coord: Coord| if coord.x == 0 { '@' } else { ' ' }),
+        ];
+        LayeredGrid::from_layers(bounds, layers).unwrap()
+    }
+
+    #[test]
+    fn columns() {
+        let mut grid = layers();
+        assert_eq!(grid.iter_column((0, 1)).copied().collect::<String>(), ". @");
+        assert_eq!(grid.iter_column((2, 0)).count(), 0);
+        for cell in grid.iter_column_mut((-1, 0)).filter(|cell| **cell == ' ') {
+            *cell = '%';
+        }
+        assert_eq!(
+            grid.iter_column((-1, 0)).copied().collect::<String>(),
+            ".%%"
+        );
+        grid.layer_mut(1).unwrap().set((1, 1), '$');
+        assert_eq!(grid.layer(1).unwrap().get((1, 1)), Some(&'$'));
+        assert_eq!(grid.layer(3), None);
+    }
+
+    #[test]
+    fn bounds_stay_aligned() {
+        let mut grid = layers();
+        assert_eq!(
+            grid.push_layer(VecGrid::filled(Rect::new((3, 2)), 'x')),
+            Err(GridError::BoundsMismatch {
+                expected: grid.bounds(),
+                received: Rect::new((3, 2)),
+            })
+        );
+        assert_eq!(
+            grid.crop(Rect::new((3, 2))),
+            Err(GridError::OutOfBounds {
+                coord: Coord::new(2, 1),
+                bounds: grid.bounds()
+            })
+        );
+        assert_eq!(grid, layers());
+
+        grid.crop(Rect::with_corners((0, 0), (2, 1))).unwrap();
+        assert!(grid
+            .layers()
+            .iter()
+            .all(|layer| layer.bounds == grid.bounds()));
+        assert_eq!(grid.iter_column((0, 0)).copied().collect::<String>(), ". @");
+
+        let bounds = Rect::with_corners((0, 0), (3, 3));
+        grid.resize(bounds, '#');
+        assert!(grid.layers().iter().all(|layer| layer.bounds == bounds));
+        assert_eq!(grid.iter_column((2, 2)).copied().collect::<String>(), "###");
+        grid.push_layer(VecGrid::filled(bounds, 'x')).unwrap();
+        assert_eq!(grid.remove_layer(0).unwrap().get((0, 0)), Some(&'.'));
+        assert_eq!(grid.layer_count(), 3);
+    }
+}
@@ -0,0 +1,480 @@
+use alloc::{vec, vec::Vec};
+use core::{iter, marker::PhantomData, mem, slice};
+
+use crate::{
+    coord::Coord,
+    grid::{GridError, IterCell, IterCellMut},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+/// The order in which a [`VecGrid`] stores its cells, chosen by its second
+/// type parameter.
+///
+/// The layout only decides where each cell lives in
+/// [`cells`](VecGrid::cells), so a grid reads and writes cells by coordinate
+/// the same way in every layout.
+pub trait Layout: Sized {
+    /// Returns the index of the cell at `coord` in the storage of a grid
+    /// covering `bounds`, or `None` if `coord` lies outside of `bounds`.
+    fn index(bounds: Rect, coord: Coord) -> Option<usize>;
+
+    /// Returns an iterator over the cells of `grid` in row-major order,
+    /// whatever order they're stored in. This backs
+    /// [`Grid::iter`](crate::Grid::iter) for grids in the layout.
+    fn iter<T>(grid: &VecGrid<T, Self>) -> impl Iterator<Item = IterCell<'_, T>>;
+}
+
+/// Stores the cells row by row, from the top row down, so that the cell at
+/// `(x, y)` lives at index `(y - top) * width + (x - left)`. This is the
+/// default layout, and the only one supporting the whole `VecGrid` API.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RowMajor;
+
+/// Stores the cells column by column, from the left column rightwards, so that
+/// the cell at `(x, y)` lives at index `(x - left) * height + (y - top)`. This
+/// is the order used by Fortran and by many framebuffers.
+///
+/// Column-major grids support access by coordinate through
+/// [`Grid`](crate::Grid) and [`GridMut`](crate::GridMut), iteration, and the
+/// `Debug` and `Display` impls, all of which behave exactly as they do for
+/// row-major grids. Rows are no longer contiguous, so `rows` returns iterators
+/// rather than slices, while `columns` returns slices. Anything else can be
+/// done after converting with [`into_layout`](VecGrid::into_layout).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ColumnMajor;
+
+impl Layout for RowMajor {
+    fn index(bounds: Rect, coord: Coord) -> Option<usize> {
+        VecGrid::<()>::coord_to_index_with_bounds(bounds, coord)
+    }
+
+    fn iter<T>(grid: &VecGrid<T>) -> impl Iterator<Item = IterCell<'_, T>> {
+        VecGrid::<T>::iter(grid)
+    }
+}
+
+impl Layout for ColumnMajor {
+    fn index(bounds: Rect, coord: Coord) -> Option<usize> {
+        if !bounds.contains(coord) {
+            return None;
+        }
+        let offset_coord = coord - bounds.offset();
+        Some((offset_coord.y + offset_coord.x * bounds.height()) as usize)
+    }
+
+    fn iter<T>(grid: &VecGrid<T, ColumnMajor>) -> impl Iterator<Item = IterCell<'_, T>> {
+        VecGrid::<T, ColumnMajor>::iter(grid)
+    }
+}
+
+impl<T, L: Layout> VecGrid<T, L> {
+    /// Constructs a grid in `layout` from its bounds and its raw storage,
+    /// which must already be in that layout. The cells are used as they are,
+    /// without copying them.
+    ///
+    /// Returns [`GridError::WrongLength`] if `cells` doesn't hold exactly one
+    /// value per cell.
+    pub fn from_raw_parts_in(bounds: Rect, cells: Vec<T>, _layout: L) -> Result<Self, GridError> {
+        let expected = bounds.area().max(0) as usize;
+        if cells.len() != expected {
+            return Err(GridError::WrongLength {
+                expected,
+                received: cells.len(),
+            });
+        }
+        Ok(Self {
+            cells,
+            bounds,
+            layout: PhantomData,
+        })
+    }
+
+    /// Constructs a grid in `layout` by calling `generator` with the
+    /// coordinate of each cell, in row-major order whatever the layout.
+    pub fn with_generator_in<C>(bounds: Rect, layout: L, generator: impl FnMut(C) -> T) -> Self
+    where
+        C: From<Coord>,
+    {
+        VecGrid::with_generator(bounds, generator).into_layout(layout)
+    }
+
+    /// Converts the grid into the same cells stored in `layout`. The cells are
+    /// reordered in place, without cloning them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid's bounds don't match its number of cells.
+    pub fn into_layout<M: Layout>(mut self, _layout: M) -> VecGrid<T, M> {
+        let area = self.bounds.area().max(0) as usize;
+        assert_eq!(
+            self.cells.len(),
+            area,
+            "grid bounds {:?} don't match its cells",
+            self.bounds
+        );
+        let mut destinations = vec![0; area];
+        for coord in self.bounds.iter().take(area) {
+            destinations[L::index(self.bounds, coord).unwrap()] =
+                M::index(self.bounds, coord).unwrap();
+        }
+        permute(&mut self.cells, |index| destinations[index]);
+        VecGrid {
+            cells: self.cells,
+            bounds: self.bounds,
+            layout: PhantomData,
+        }
+    }
+
+    /// Returns the grid's cells as a contiguous slice, in the order given by
+    /// the layout.
+    ///
+    /// The layout is guaranteed to stay that way. For the default
+    /// [`RowMajor`] layout, index 0 holds the cell at the top-left corner of
+    /// the bounds, `(left, top)`, the stride between rows is the grid's
+    /// width, and the cell at `(x, y)` lives at index
+    /// `(y - top) * width + (x - left)`. For [`ColumnMajor`], the stride
+    /// between columns is the grid's height, and the cell at `(x, y)` lives
+    /// at index `(x - left) * height + (y - top)`.
+    ///
+    /// These are the standard and Fortran layouts of a 2D `ndarray` array
+    /// of shape `(height, width)`, so with the `ndarray` feature,
+    /// [`as_array_view`](VecGrid::as_array_view) borrows the grid as an array
+    /// without copying.
+    pub fn as_slice(&self) -> &[T] {
+        &self.cells
+    }
+
+    /// Mutable version of [`as_slice`](VecGrid::as_slice), with the same
+    /// layout.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    /// Decomposes the grid into its bounds and its storage, laid out as
+    /// described in [`as_slice`](VecGrid::as_slice).
+    pub fn into_raw_parts(self) -> (Rect, Vec<T>) {
+        (self.bounds, self.cells)
+    }
+
+    /// Returns an iterator over the values of all cells in storage order,
+    /// without their coordinates.
+    ///
+    /// This is a plain pass over the backing slice, so passes that don't need
+    /// coordinates should prefer it to [`iter`](VecGrid::iter), which also
+    /// works out the coordinate of every cell. The `--bench` mode of the
+    /// `life` example times summing a grid through each of them.
+    pub fn values(&self) -> slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    /// Returns a mutable iterator over the values of all cells in storage
+    /// order, without their coordinates.
+    pub fn values_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    /// Converts a 2D Grid coordinate into a linear Vec index.
+    pub(crate) fn coord_to_index<C: Into<Coord>>(&self, coord: C) -> Option<usize> {
+        L::index(self.bounds, coord.into())
+    }
+}
+
+impl<T> VecGrid<T, ColumnMajor> {
+    /// Returns an iterator over all cells in the grid, in row-major order like
+    /// the iterator of a row-major grid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid's bounds don't match its number of cells.
+    pub fn iter(&self) -> impl Iterator<Item = IterCell<'_, T>> {
+        ColumnMajorIter {
+            cursor: ColumnMajorCursor::new(self.bounds, self.column_dimensions()),
+            cells: &self.cells,
+        }
+    }
+
+    /// Returns a mutable iterator over all cells in the grid, in row-major
+    /// order like the iterator of a row-major grid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid's bounds don't match its number of cells.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        ColumnMajorIterMut {
+            cursor: ColumnMajorCursor::new(self.bounds, self.column_dimensions()),
+            cells: self.cells.as_mut_ptr(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over all cells in the grid in column-major order,
+    /// which is a plain pass over the backing slice for this layout.
+    pub fn iter_column_major(&self) -> impl Iterator<Item = IterCell<'_, T>> {
+        self.bounds.iter_column_major().zip(self.cells.iter())
+    }
+
+    /// Mutable version of `iter_column_major`.
+    pub fn iter_column_major_mut(&mut self) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        self.bounds.iter_column_major().zip(self.cells.iter_mut())
+    }
+
+    /// Returns an iterator over each row of the grid, from top to bottom. The
+    /// cells of a row aren't contiguous in this layout, so each row is an
+    /// iterator over its cells from left to right.
+    pub fn rows(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = iter::StepBy<slice::Iter<'_, T>>> + ExactSizeIterator {
+        let height = self.bounds.height().max(0) as usize;
+        let cells = &self.cells;
+        (0..height).map(move |y| cells.get(y..).unwrap_or(&[]).iter().step_by(height))
+    }
+
+    /// Returns an iterator over each column of the grid, from left to right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid's bounds don't match its number of cells.
+    pub fn columns(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        let (width, height) = self.column_dimensions();
+        let cells = &self.cells;
+        (0..width).map(move |x| &cells[x * height..(x + 1) * height])
+    }
+
+    /// Returns a mutable iterator over each column of the grid, from left to
+    /// right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid's bounds don't match its number of cells.
+    pub fn columns_mut(&mut self) -> impl ExactSizeIterator<Item = &mut [T]> {
+        let (width, height) = self.column_dimensions();
+        let mut rest = &mut self.cells[..];
+        (0..width).map(move |_| {
+            let (column, tail) = mem::take(&mut rest).split_at_mut(height);
+            rest = tail;
+            column
+        })
+    }
+
+    /// Returns the width and height of the grid, checking that they match the
+    /// storage so that every column can be sliced out of it.
+    fn column_dimensions(&self) -> (usize, usize) {
+        let width = self.bounds.width().max(0) as usize;
+        let height = self.bounds.height().max(0) as usize;
+        assert_eq!(
+            self.cells.len(),
+            width * height,
+            "grid bounds {:?} don't match its cells",
+            self.bounds
+        );
+        (width, height)
+    }
+}
+
+/// Tracks the coordinate and storage index of the next cell of a
+/// column-major grid, visited in row-major order. Moving along a row skips a
+/// whole column in storage, so the index steps by the grid's height.
+struct ColumnMajorCursor {
+    next: Coord,
+    bounds: Rect,
+    index: usize,
+    height: usize,
+    remaining: usize,
+}
+
+impl ColumnMajorCursor {
+    fn new(bounds: Rect, (width, height): (usize, usize)) -> Self {
+        Self {
+            next: bounds.offset(),
+            bounds,
+            index: 0,
+            height,
+            remaining: width * height,
+        }
+    }
+
+    /// Returns the current coordinate and its index, and moves on to the
+    /// following cell.
+    fn advance(&mut self) -> Option<(Coord, usize)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let current = (self.next, self.index);
+        self.next.x += 1;
+        self.index += self.height;
+        if self.next.x == self.bounds.right {
+            self.next.x = self.bounds.left;
+            self.next.y += 1;
+            self.index = (self.next.y - self.bounds.top) as usize;
+        }
+        Some(current)
+    }
+}
+
+struct ColumnMajorIter<'a, T> {
+    cursor: ColumnMajorCursor,
+    cells: &'a [T],
+}
+
+impl<'a, T> Iterator for ColumnMajorIter<'a, T> {
+    type Item = IterCell<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (coord, index) = self.cursor.advance()?;
+        Some((coord, &self.cells[index]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.cursor.remaining, Some(self.cursor.remaining))
+    }
+}
+
+struct ColumnMajorIterMut<'a, T> {
+    cursor: ColumnMajorCursor,
+    /// Points to the grid's backing storage. A raw pointer is used since the
+    /// cells aren't handed out in storage order, so the slice can't simply be
+    /// split as it's walked.
+    cells: *mut T,
+    marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> Iterator for ColumnMajorIterMut<'a, T> {
+    type Item = IterCellMut<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (coord, index) = self.cursor.advance()?;
+        // SAFETY: The cursor was created from dimensions checked against the
+        // length of the backing storage, which is mutably borrowed for `'a`,
+        // and it visits each index below that length exactly once.
+        let cell = unsafe { &mut *self.cells.add(index) };
+        Some((coord, cell))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.cursor.remaining, Some(self.cursor.remaining))
+    }
+}
+
+/// Moves the value at each index `i` of `values` to `destination(i)`, which
+/// must be a permutation, by following each cycle of it with swaps.
+fn permute<T>(values: &mut [T], destination: impl Fn(usize) -> usize) {
+    let mut placed = vec![false; values.len()];
+    for start in 0..values.len() {
+        if placed[start] {
+            continue;
+        }
+        placed[start] = true;
+        let mut index = destination(start);
+        while index != start {
+            values.swap(start, index);
+            placed[index] = true;
+            index = destination(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{Grid, GridMut};
+    use alloc::{format, string::ToString};
+
+    fn strings() -> VecGrid<String> {
+        VecGrid::with_generator(Rect::new((3, 2)).translate((1, -1)), |(x, y)| {
+            format!("{},{} ", x, y)
+        })
+    }
+
+    #[test]
+    fn column_major_storage() {
+        let bounds = Rect::new((3, 2)).translate((1, 0));
+        #[rustfmt::skip]
+        let cells = vec![
+            1, 4,
+            2, 5,
+            3, 6,
+        ];
+        let mut grid = VecGrid::from_raw_parts_in(bounds, cells.clone(), ColumnMajor).unwrap();
+        assert_eq!(grid.get((1, 1)), Some(&4));
+        assert_eq!(grid.get((3, 0)), Some(&3));
+        assert_eq!(grid.get((0, 0)), None);
+        assert!(grid.set((2, 1), 50));
+        assert!(grid.swap((1, 0), (3, 1)));
+        assert_eq!(grid.as_slice(), &[6, 4, 2, 50, 3, 1]);
+        assert_eq!(
+            VecGrid::from_raw_parts_in(bounds, vec![0], ColumnMajor),
+            Err(GridError::WrongLength {
+                expected: 6,
+                received: 1
+            })
+        );
+
+        let row_major = grid.clone().into_layout(RowMajor);
+        assert_eq!(row_major.as_slice(), &[6, 2, 3, 4, 50, 1]);
+        assert_eq!(row_major.into_layout(ColumnMajor), grid);
+        assert_eq!(grid.into_raw_parts(), (bounds, vec![6, 4, 2, 50, 3, 1]));
+    }
+
+    #[test]
+    fn layouts_behave_identically() {
+        let row_major = strings();
+        let mut column_major = strings().into_layout(ColumnMajor);
+        assert_eq!(
+            VecGrid::with_generator_in(row_major.bounds, ColumnMajor, |(x, y)| {
+                format!("{},{} ", x, y)
+            }),
+            column_major
+        );
+        assert_eq!(column_major.as_slice()[1], "1,0 ");
+        assert!(column_major.iter().eq(row_major.iter()));
+        assert!(column_major
+            .rows()
+            .zip(row_major.rows())
+            .all(|(a, b)| a.eq(b.iter())));
+        assert_eq!(column_major.to_string(), row_major.to_string());
+        assert_eq!(format!("{:?}", column_major), format!("{:?}", row_major));
+        assert_eq!(
+            column_major
+                .selection_iter(vec![(3, 0), (0, 0)])
+                .map(|result| result.ok().map(|(_, cell)| cell.as_str()))
+                .collect::<Vec<_>>(),
+            vec![Some("3,0 "), None]
+        );
+
+        for (coord, cell) in column_major.iter_mut() {
+            cell.push_str(if coord.x == 2 { "|" } else { "" });
+        }
+        let columns = column_major.columns().collect::<Vec<_>>();
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns[1], &["2,-1 |", "2,0 |"][..]);
+        for column in column_major.columns_mut() {
+            column.reverse();
+        }
+        assert_eq!(column_major.get((1, -1)).unwrap(), "1,0 ");
+        assert_eq!(
+            column_major
+                .iter_column_major()
+                .map(|(coord, _)| coord)
+                .take(3)
+                .collect::<Vec<_>>(),
+            vec![Coord::new(1, -1), Coord::new(1, 0), Coord::new(2, -1)]
+        );
+    }
+
+    #[test]
+    fn empty_column_major() {
+        let grid =
+            VecGrid::<u8, _>::from_raw_parts_in(Rect::new((0, 3)), vec![], ColumnMajor).unwrap();
+        assert_eq!(grid.rows().count(), 3);
+        assert!(grid.rows().all(|mut row| row.next().is_none()));
+        assert_eq!(grid.columns().count(), 0);
+        assert_eq!(grid.iter().count(), 0);
+        let wide =
+            VecGrid::<u8, _>::from_raw_parts_in(Rect::new((2, 0)), vec![], ColumnMajor).unwrap();
+        assert_eq!(wide.rows().count(), 0);
+        assert!(wide.columns().all(|column| column.is_empty()));
+        assert_eq!(wide.into_layout(RowMajor), VecGrid::new(Rect::new((2, 0))));
+    }
+}
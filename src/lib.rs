@@ -1,9 +1,93 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+mod macros;
+
+pub mod automata;
 pub mod patterns;
 
+mod ansi;
+#[cfg(feature = "ndarray")]
+mod array;
+mod arraygrid;
+#[cfg(feature = "std")]
+mod ascii;
+#[cfg(feature = "std")]
+mod binary;
+mod bitgrid;
+#[cfg(feature = "std")]
+mod chunked;
 mod coord;
+mod cow;
+#[cfg(feature = "std")]
+mod csv;
+mod decay;
+mod display;
 mod grid;
+mod journal;
+mod layered;
+mod layout;
+mod matching;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod quadgrid;
+#[cfg(feature = "rand")]
+mod random;
+mod raster;
+mod region;
+mod rlegrid;
+mod sim;
+#[cfg(feature = "std")]
+mod sparse;
+#[cfg(feature = "std")]
+mod spatial;
+mod table;
+mod tracked;
 mod vecgrid;
+mod view;
+mod wrapping;
 
+pub use ansi::{AnsiColor, CellStyle};
+pub use arraygrid::ArrayGrid;
+#[cfg(feature = "std")]
+pub use ascii::{AsciiMapError, RaggedLines};
+#[cfg(feature = "std")]
+pub use binary::{BinaryGridError, GridCodec};
+pub use bitgrid::BitGrid;
+#[cfg(feature = "std")]
+pub use chunked::ChunkedGrid;
 pub use coord::{Coord, ParseCoordError};
-pub use grid::{Grid, GridError, IterCell, IterCellMut};
-pub use vecgrid::{FloodIter, SelectionIter, SelectionIterMut, VecGrid};
+pub use cow::CowGrid;
+#[cfg(feature = "std")]
+pub use csv::CsvGridError;
+pub use decay::DecayGrid;
+pub use display::GridDisplay;
+#[cfg(feature = "std")]
+pub use grid::Bilinear;
+pub use grid::{
+    BorderMode, CellError, GetManyError, Grid, GridError, GridMut, IterCell, IterCellMut, Nearest,
+    PadMode, ResampleMode, ShiftMode,
+};
+pub use journal::{ChangeSet, GridTransaction};
+pub use layered::LayeredGrid;
+pub use layout::{ColumnMajor, Layout, RowMajor};
+pub use matching::PatternTransform;
+pub use quadgrid::QuadGrid;
+pub use raster::PixelGridError;
+#[cfg(feature = "image")]
+pub use raster::SaveImageError;
+pub use region::RegionStats;
+pub use rlegrid::RleGrid;
+pub use sim::SimGrid;
+#[cfg(feature = "std")]
+pub use sparse::SparseGrid;
+#[cfg(feature = "std")]
+pub use spatial::SpatialIndex;
+pub use table::{BorderStyle, Table};
+pub use tracked::TrackedGrid;
+pub use vecgrid::{
+    FloodIter, IntoIter, Iter, IterMut, Neighbors, SelectionIter, SelectionIterMut, VecGrid,
+};
+pub use view::{GridView, GridViewMut};
+pub use wrapping::WrappingGrid;
@@ -0,0 +1,54 @@
+/// Constructs a [`VecGrid`](crate::VecGrid) with its origin at `(0, 0)`.
+///
+/// - `grid![[1, 2, 3], [4, 5, 6]]` lays out the cells row by row, making a
+///   grid 3 wide and 2 high. Rows of different lengths fail to compile, since
+///   the rows are built as arrays of the same type.
+/// - `grid!(3 x 2; 0)` makes a grid 3 wide and 2 high with every cell set to a
+///   clone of `0`, like [`VecGrid::filled`](crate::VecGrid::filled).
+/// - `grid![]` makes an empty grid with zero width and height.
+#[macro_export]
+macro_rules! grid {
+    () => {
+        $crate::VecGrid::from([] as [[_; 0]; 0])
+    };
+    ($width:tt x $height:tt; $value:expr) => {
+        $crate::VecGrid::filled($crate::patterns::Rect::new(($width, $height)), $value)
+    };
+    ($([$($cell:expr),* $(,)?]),+ $(,)?) => {
+        $crate::VecGrid::from([$([$($cell),*]),+])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{grid::Grid, patterns::Rect, VecGrid};
+
+    #[test]
+    fn literals() {
+        let grid = grid![[1, 2, 3], [4, 5, 6]];
+        assert_eq!(grid.bounds, Rect::new((3, 2)));
+        assert_eq!(grid.get((2, 0)), Some(&3));
+        assert_eq!(grid.get((0, 1)), Some(&4));
+        assert_eq!(
+            grid,
+            VecGrid::with_generator(Rect::new((3, 2)), |(x, y)| x + y * 3 + 1)
+        );
+
+        let column = grid![['a'], ['b'],];
+        assert_eq!(column.bounds, Rect::new((1, 2)));
+        assert_eq!(column.get((0, 1)), Some(&'b'));
+        let strings = grid![[String::from("x"), String::new(),]];
+        assert_eq!(strings.get((0, 0)).map(String::as_str), Some("x"));
+    }
+
+    #[test]
+    fn filled_and_empty() {
+        let width = 4;
+        assert_eq!(grid!(width x 2; 0u8), VecGrid::filled(Rect::new((4, 2)), 0));
+        assert_eq!(grid!(1 x 1; 'z'), grid![['z']]);
+
+        let empty: VecGrid<i32> = grid![];
+        assert_eq!(empty.bounds, Rect::new((0, 0)));
+        assert_eq!(empty.iter().count(), 0);
+    }
+}
@@ -0,0 +1,320 @@
+use crate::{coord::Coord, grid::Grid, vecgrid::VecGrid};
+
+/// One of the eight ways of rotating and mirroring a pattern, for
+/// [`VecGrid::find_pattern_transformed`]. Rotations are clockwise with y
+/// growing downwards, as grids are usually displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatternTransform {
+    Identity,
+    /// A quarter turn clockwise.
+    RotateCw,
+    /// A half turn.
+    Rotate180,
+    /// A quarter turn counterclockwise.
+    RotateCcw,
+    /// Mirrored left to right.
+    MirrorHorizontal,
+    /// Mirrored top to bottom.
+    MirrorVertical,
+    /// Mirrored across the diagonal through the top-left corner.
+    Transpose,
+    /// Mirrored across the diagonal through the top-right corner.
+    AntiTranspose,
+}
+
+impl PatternTransform {
+    /// Every transform, in the order they're tried.
+    pub const ALL: [PatternTransform; 8] = [
+        PatternTransform::Identity,
+        PatternTransform::RotateCw,
+        PatternTransform::Rotate180,
+        PatternTransform::RotateCcw,
+        PatternTransform::MirrorHorizontal,
+        PatternTransform::MirrorVertical,
+        PatternTransform::Transpose,
+        PatternTransform::AntiTranspose,
+    ];
+
+    /// Returns whether the transform turns a pattern's rows into columns.
+    fn swaps_axes(self) -> bool {
+        matches!(
+            self,
+            PatternTransform::RotateCw
+                | PatternTransform::RotateCcw
+                | PatternTransform::Transpose
+                | PatternTransform::AntiTranspose
+        )
+    }
+
+    /// Returns the dimensions of a pattern of `dimensions` once transformed.
+    fn dimensions(self, dimensions: Coord) -> Coord {
+        if self.swaps_axes() {
+            dimensions.flip()
+        } else {
+            dimensions
+        }
+    }
+
+    /// Maps `local`, relative to the top-left corner of the transformed
+    /// pattern, to the cell of the original pattern of `dimensions` that ends
+    /// up there.
+    fn source(self, local: Coord, dimensions: Coord) -> Coord {
+        let (x, y) = (local.x, local.y);
+        let (w, h) = (dimensions.x, dimensions.y);
+        let (x, y) = match self {
+            PatternTransform::Identity => (x, y),
+            PatternTransform::RotateCw => (y, h - 1 - x),
+            PatternTransform::Rotate180 => (w - 1 - x, h - 1 - y),
+            PatternTransform::RotateCcw => (w - 1 - y, x),
+            PatternTransform::MirrorHorizontal => (w - 1 - x, y),
+            PatternTransform::MirrorVertical => (x, h - 1 - y),
+            PatternTransform::Transpose => (y, x),
+            PatternTransform::AntiTranspose => (w - 1 - y, h - 1 - x),
+        };
+        Coord::new(x, y)
+    }
+}
+
+impl<T> VecGrid<T> {
+    /// Returns the top-left coordinate of every placement of `pattern` where
+    /// `matches` holds for each cell of the grid and the pattern cell over
+    /// it, in row-major order. Overlapping matches are all reported.
+    ///
+    /// The pattern's own position is ignored, and its cells may be of another
+    /// type, so that some of them can match anything. Patterns with zero area
+    /// or larger than the grid never match.
+    ///
+    /// Each placement is checked cell by cell until one doesn't match, so
+    /// this takes up to the product of the two areas.
+    pub fn find_pattern<'a, U, F>(
+        &'a self,
+        pattern: &'a VecGrid<U>,
+        matches: F,
+    ) -> impl Iterator<Item = Coord> + 'a
+    where
+        F: Fn(&T, &U) -> bool + 'a,
+    {
+        self.placements().filter(move |&origin| {
+            self.pattern_matches(pattern, origin, PatternTransform::Identity, &matches)
+        })
+    }
+
+    /// Like [`find_pattern`](VecGrid::find_pattern), but also tries every
+    /// rotation and mirror image of `pattern`, yielding each placement along
+    /// with the transform that matched there. Patterns with symmetries match
+    /// under several transforms at once, and each of them is reported.
+    ///
+    /// The coordinate is always the top-left corner of the transformed
+    /// pattern, and placements are in row-major order, with the transforms
+    /// matching at each one in the order of [`PatternTransform::ALL`].
+    pub fn find_pattern_transformed<'a, U, F>(
+        &'a self,
+        pattern: &'a VecGrid<U>,
+        matches: F,
+    ) -> impl Iterator<Item = (Coord, PatternTransform)> + 'a
+    where
+        F: Fn(&T, &U) -> bool + 'a,
+    {
+        self.placements()
+            .flat_map(|origin| {
+                PatternTransform::ALL
+                    .iter()
+                    .map(move |&transform| (origin, transform))
+            })
+            .filter(move |&(origin, transform)| {
+                self.pattern_matches(pattern, origin, transform, &matches)
+            })
+    }
+
+    /// Returns every coordinate a pattern's top-left corner could be placed
+    /// at, leaving it to the caller to check that the pattern fits.
+    fn placements(&self) -> impl Iterator<Item = Coord> {
+        self.bounds.iter()
+    }
+
+    fn pattern_matches<U, F>(
+        &self,
+        pattern: &VecGrid<U>,
+        origin: Coord,
+        transform: PatternTransform,
+        matches: &F,
+    ) -> bool
+    where
+        F: Fn(&T, &U) -> bool,
+    {
+        let dimensions = pattern.bounds.dimensions();
+        let transformed = transform.dimensions(dimensions);
+        let (right, bottom) = (
+            origin.x as i64 + transformed.x as i64,
+            origin.y as i64 + transformed.y as i64,
+        );
+        if pattern.cells.is_empty()
+            || right > self.bounds.right as i64
+            || bottom > self.bounds.bottom as i64
+        {
+            return false;
+        }
+        (0..transformed.y).all(|y| {
+            (0..transformed.x).all(|x| {
+                let local = Coord::new(x, y);
+                let source = transform.source(local, dimensions) + pattern.bounds.offset();
+                matches(
+                    self.get(origin + local).unwrap(),
+                    pattern.get(source).unwrap(),
+                )
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::Rect;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Cell {
+        Is(char),
+        DontCare,
+    }
+
+    const MAP: &str = "\
+#..#....
+##.##...
+....#...
+.....##.";
+
+    fn map() -> VecGrid<char> {
+        let rows = MAP.lines().map(|line| line.chars().collect::<Vec<_>>());
+        let bounds = Rect::new((8, 4)).translate((10, -2));
+        VecGrid::from_raw_parts(bounds, rows.flatten().collect()).unwrap()
+    }
+
+    fn matches(cell: &char, pattern: &Cell) -> bool {
+        match pattern {
+            Cell::Is(c) => cell == c,
+            Cell::DontCare => true,
+        }
+    }
+
+    #[test]
+    fn finds_every_placement() {
+        let map = map();
+        let corner = VecGrid::from_raw_parts(
+            Rect::new((2, 2)).translate((-7, 7)),
+            vec![Cell::Is('#'), Cell::DontCare, Cell::Is('#'), Cell::Is('#')],
+        )
+        .unwrap();
+        assert_eq!(
+            map.find_pattern(&corner, matches).collect::<Vec<_>>(),
+            vec![Coord::new(10, -2), Coord::new(13, -2)]
+        );
+
+        // Overlapping matches.
+        let dots = VecGrid::filled(Rect::new((3, 1)), Cell::Is('.'));
+        assert_eq!(
+            map.find_pattern(&dots, matches).take(3).collect::<Vec<_>>(),
+            vec![Coord::new(14, -2), Coord::new(15, -2), Coord::new(15, -1)]
+        );
+
+        let too_wide = VecGrid::filled(Rect::new((9, 1)), Cell::DontCare);
+        assert_eq!(map.find_pattern(&too_wide, matches).count(), 0);
+        let empty = VecGrid::filled(Rect::new((0, 1)), Cell::DontCare);
+        assert_eq!(map.find_pattern(&empty, matches).count(), 0);
+        let anything = VecGrid::filled(Rect::new((8, 4)), Cell::DontCare);
+        assert_eq!(
+            map.find_pattern(&anything, matches).collect::<Vec<_>>(),
+            vec![map.bounds.offset()]
+        );
+    }
+
+    #[test]
+    fn transforms_agree_with_rotating_the_pattern() {
+        let map = VecGrid::with_generator(Rect::new((9, 7)).translate((-3, 1)), |(x, y)| {
+            (3 * x * x + 5 * y * y + 7 * x * y + x).rem_euclid(11) < 5
+        });
+        let pattern =
+            VecGrid::from_raw_parts(Rect::new((3, 3)), vec![1, 1, 0, 1, 2, 2, 2, 2, 2]).unwrap();
+        let bits = |cell: &bool, pattern: &u8| *pattern == 2 || *cell == (*pattern == 1);
+
+        let mut rotated = pattern.clone();
+        let mut expected = Vec::new();
+        for &transform in [
+            PatternTransform::Identity,
+            PatternTransform::RotateCw,
+            PatternTransform::Rotate180,
+            PatternTransform::RotateCcw,
+        ]
+        .iter()
+        {
+            expected.extend(
+                map.find_pattern(&rotated, bits)
+                    .map(|coord| (coord, transform)),
+            );
+            rotated.rotate_region_cw(rotated.bounds).unwrap();
+        }
+        let mut mirrored = pattern.clone();
+        mirrored.mirror_region_horizontal(mirrored.bounds).unwrap();
+        expected.extend(
+            map.find_pattern(&mirrored, bits)
+                .map(|coord| (coord, PatternTransform::MirrorHorizontal)),
+        );
+        let mut mirrored = pattern.clone();
+        mirrored.mirror_region_vertical(mirrored.bounds).unwrap();
+        expected.extend(
+            map.find_pattern(&mirrored, bits)
+                .map(|coord| (coord, PatternTransform::MirrorVertical)),
+        );
+        let transposed = VecGrid::with_generator(pattern.bounds, |coord: Coord| {
+            *pattern.get(coord.flip()).unwrap()
+        });
+        expected.extend(
+            map.find_pattern(&transposed, bits)
+                .map(|coord| (coord, PatternTransform::Transpose)),
+        );
+        let mut anti_transposed = transposed.clone();
+        anti_transposed
+            .rotate_region_cw(anti_transposed.bounds)
+            .unwrap();
+        anti_transposed
+            .rotate_region_cw(anti_transposed.bounds)
+            .unwrap();
+        expected.extend(
+            map.find_pattern(&anti_transposed, bits)
+                .map(|coord| (coord, PatternTransform::AntiTranspose)),
+        );
+        assert_eq!(expected.len(), 15);
+
+        let mut found = map
+            .find_pattern_transformed(&pattern, bits)
+            .collect::<Vec<_>>();
+        let order = |&(coord, transform): &(Coord, PatternTransform)| {
+            let index = PatternTransform::ALL.iter().position(|&t| t == transform);
+            (coord.y, coord.x, index)
+        };
+        expected.sort_by_key(order);
+        assert!(found
+            .windows(2)
+            .all(|pair| order(&pair[0]) < order(&pair[1])));
+        found.sort_by_key(order);
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn non_square_transforms() {
+        // An L shaped door frame, two wide and three high.
+        let frame =
+            VecGrid::from_raw_parts(Rect::new((2, 3)), vec!['#', '.', '#', '.', '#', '#']).unwrap();
+        let map = VecGrid::from_raw_parts(
+            Rect::new((4, 3)),
+            vec!['.', '.', '.', '.', '#', '#', '#', '.', '#', '.', '.', '.'],
+        )
+        .unwrap();
+        assert_eq!(
+            map.find_pattern_transformed(&frame, |a, b| a == b)
+                .collect::<Vec<_>>(),
+            vec![(Coord::new(0, 1), PatternTransform::RotateCw)]
+        );
+    }
+}
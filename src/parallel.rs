@@ -0,0 +1,83 @@
+//! Parallel iterators over [`VecGrid`] cells, enabled with the `rayon`
+//! feature.
+
+use core::marker::PhantomData;
+
+use rayon::prelude::*;
+
+use crate::{
+    grid::{IterCell, IterCellMut},
+    vecgrid::VecGrid,
+};
+
+impl<T> VecGrid<T> {
+    /// Returns a parallel iterator over all cells in the grid.
+    pub fn par_iter(&self) -> impl IndexedParallelIterator<Item = IterCell<'_, T>>
+    where
+        T: Sync,
+    {
+        let bounds = self.bounds;
+        self.cells
+            .par_iter()
+            .enumerate()
+            .map(move |(index, cell)| (Self::index_to_coord_with_bounds(bounds, index), cell))
+    }
+
+    /// Returns a parallel mutable iterator over all cells in the grid.
+    ///
+    /// The backing storage is split into disjoint chunks, so each cell is only
+    /// ever visited by a single thread.
+    pub fn par_iter_mut(&mut self) -> impl IndexedParallelIterator<Item = IterCellMut<'_, T>>
+    where
+        T: Send,
+    {
+        let bounds = self.bounds;
+        self.cells
+            .par_iter_mut()
+            .enumerate()
+            .map(move |(index, cell)| (Self::index_to_coord_with_bounds(bounds, index), cell))
+    }
+
+    /// Parallel counterpart to [`map`](VecGrid::map).
+    pub fn par_map<U, F>(&self, f: F) -> VecGrid<U>
+    where
+        T: Sync,
+        U: Send,
+        F: Fn(&T) -> U + Sync + Send,
+    {
+        VecGrid {
+            cells: self.cells.par_iter().map(f).collect(),
+            bounds: self.bounds,
+            layout: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{patterns::Rect, Coord};
+
+    #[test]
+    fn par_iter_coords() {
+        let grid = VecGrid::with_generator(Rect::new((37, 23)), |coord: Coord| coord);
+        assert!(grid.par_iter().all(|(coord, &cell)| coord == cell));
+    }
+
+    #[test]
+    fn par_iter_mut_coords() {
+        let mut grid: VecGrid<Option<Coord>> = VecGrid::new(Rect::with_corners((-5, -3), (60, 40)));
+        grid.par_iter_mut()
+            .for_each(|(coord, cell)| *cell = Some(coord));
+        assert!(grid.iter().all(|(coord, &cell)| cell == Some(coord)));
+    }
+
+    #[test]
+    fn par_map_matches_map() {
+        let grid = VecGrid::with_generator(Rect::new((128, 96)), |coord: Coord| {
+            (coord.x * 31 + coord.y * 17) % 97
+        });
+        let f = |&cell: &i32| (0..cell).fold(cell as u64, |acc, i| acc.wrapping_mul(i as u64 + 3));
+        assert_eq!(grid.par_map(f), grid.map(f));
+    }
+}
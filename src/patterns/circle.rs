@@ -1,4 +1,4 @@
-use std::collections::{HashSet, VecDeque};
+use alloc::collections::{BTreeSet, VecDeque};
 
 use crate::Coord;
 
@@ -19,17 +19,18 @@ impl Circle {
     /// Traces Bresenham's circle algorithm.
     pub fn iter(&self) -> CircleIter {
         let mut coord_queue = VecDeque::new();
-        let mut seen_coords = HashSet::new();
+        let mut seen_coords = BTreeSet::new();
 
         let starting_coord = Coord::new(0, self.radius);
 
         for coord in self
             .mirror_quadrants(starting_coord)
             .iter()
-            .map(|&coord| coord)
+            .flatten()
+            .copied()
         {
             coord_queue.push_back(coord);
-            seen_coords.insert(coord);
+            seen_coords.insert((coord.x, coord.y));
         }
 
         CircleIter {
@@ -41,44 +42,51 @@ impl Circle {
         }
     }
 
-    fn mirror_quadrants(&self, coord: Coord) -> [Coord; 4] {
+    /// Mirrors `coord` into each quadrant around the center. Mirrored coords
+    /// which would lie past `i32::MIN` or `i32::MAX` are `None`.
+    fn mirror_quadrants(&self, coord: Coord) -> [Option<Coord>; 4] {
         [
-            self.center + coord,
-            self.center + coord.flip(),
-            self.center + coord.negate_y(),
-            self.center + coord.flip().negate(),
+            self.center.checked_add(coord),
+            self.center.checked_add(coord.flip()),
+            self.center.checked_add(coord.negate_y()),
+            self.center.checked_add(coord.flip().negate()),
         ]
     }
 
-    fn mirror_octants(&self, coord: Coord) -> [Coord; 8] {
+    /// Mirrors `coord` into each octant around the center, like
+    /// [`mirror_quadrants`](Circle::mirror_quadrants).
+    fn mirror_octants(&self, coord: Coord) -> [Option<Coord>; 8] {
         [
-            self.center + coord,
-            self.center + coord.flip(),
-            self.center + coord.flip().negate_x(),
-            self.center + coord.negate_x(),
-            self.center + coord.negate(),
-            self.center + coord.flip().negate(),
-            self.center + coord.flip().negate_y(),
-            self.center + coord.negate_y(),
+            self.center.checked_add(coord),
+            self.center.checked_add(coord.flip()),
+            self.center.checked_add(coord.flip().negate_x()),
+            self.center.checked_add(coord.negate_x()),
+            self.center.checked_add(coord.negate()),
+            self.center.checked_add(coord.flip().negate()),
+            self.center.checked_add(coord.flip().negate_y()),
+            self.center.checked_add(coord.negate_y()),
         ]
     }
 }
 
+/// Iterates over the outline of a circle. Created by [`Circle::iter`].
+#[derive(Debug, Clone)]
 pub struct CircleIter {
     circle: Circle,
     cursor: Coord,
     d: i32,
     /// Coords to be returned on subsequent iterations.
     coord_queue: VecDeque<Coord>,
-    /// Used to prevent duplicate Coords from being returned.
-    seen_coords: HashSet<Coord>,
+    /// Used to prevent duplicate Coords from being returned, stored as
+    /// `(x, y)` pairs since Coords aren't ordered.
+    seen_coords: BTreeSet<(i32, i32)>,
 }
 
 impl Iterator for CircleIter {
     type Item = Coord;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.coord_queue.len() == 0 && self.cursor.y > self.cursor.x {
+        if self.coord_queue.is_empty() && self.cursor.y > self.cursor.x {
             self.cursor.x += 1;
 
             if self.d < 0 {
@@ -88,18 +96,35 @@ impl Iterator for CircleIter {
                 self.cursor.y -= 1;
             }
 
-            for coord in self.circle.mirror_octants(self.cursor).iter() {
-                if !self.seen_coords.contains(coord) {
-                    self.seen_coords.insert(*coord);
+            for coord in self.circle.mirror_octants(self.cursor).iter().flatten() {
+                if self.seen_coords.insert((coord.x, coord.y)) {
                     self.coord_queue.push_back(*coord);
                 }
             }
         }
 
-        if self.coord_queue.len() > 0 {
+        if !self.coord_queue.is_empty() {
             return self.coord_queue.pop_front();
         }
 
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_coords_past_the_coordinate_limits() {
+        let full = Circle::new((0, 0), 3).iter().count();
+        let corner = Circle::new((i32::MAX, i32::MIN), 3)
+            .iter()
+            .collect::<Vec<_>>();
+        // Only the quarter of the circle up and to the left remains, plus
+        // the points on its two axes.
+        assert_eq!(corner.len(), full / 4 + 1);
+        assert!(corner.contains(&Coord::new(i32::MAX - 3, i32::MIN)));
+        assert!(corner.contains(&Coord::new(i32::MAX, i32::MIN + 3)));
+    }
+}
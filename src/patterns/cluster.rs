@@ -3,7 +3,7 @@ use std::{
     iter::FromIterator,
 };
 
-use crate::{coord::Coord, patterns::Neighborhood};
+use crate::{coord::Coord, patterns::Connectivity};
 
 /// Represents various "layers" of a selection of coords (cluster).
 ///
@@ -52,7 +52,7 @@ impl Cluster {
     // /// the `internal_border`.
     // ///
     // /// Defined as a non-cluster cell adjacent to at least one cluster cell.
-    pub fn iter_external_border(&self) -> ExternalBorderIter {
+    pub fn iter_external_border(&self) -> ExternalBorderIter<'_> {
         ExternalBorderIter {
             cluster: self,
             coords: self.0.iter(),
@@ -62,12 +62,15 @@ impl Cluster {
     }
 
     fn external_neighbors(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
-        Neighborhood::new(coord)
-            .into_iter()
+        Connectivity::Eight
+            .neighbors(coord)
             .filter(move |neighbor| !self.0.contains(neighbor))
     }
 }
 
+/// Iterates over the coords surrounding a cluster. Created by
+/// [`Cluster::iter_external_border`].
+#[derive(Debug, Clone)]
 pub struct ExternalBorderIter<'a> {
     /// Used for finding external neighbors.
     cluster: &'a Cluster,
@@ -85,8 +88,8 @@ impl<'a> Iterator for ExternalBorderIter<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         // If there are no external border coords to return, loop through the
         // cluster to find external neighbors until new ones are found.
-        if self.coords_to_return.len() == 0 {
-            while let Some(coord) = self.coords.next() {
+        if self.coords_to_return.is_empty() {
+            for coord in self.coords.by_ref() {
                 let external_neighbors = self.cluster.external_neighbors(*coord);
                 for neighbor in external_neighbors {
                     if !self.external_border_coords.contains(&neighbor) {
@@ -97,7 +100,7 @@ impl<'a> Iterator for ExternalBorderIter<'a> {
             }
         }
 
-        if self.coords_to_return.len() > 0 {
+        if !self.coords_to_return.is_empty() {
             return self.coords_to_return.pop_front();
         }
 
@@ -111,7 +114,7 @@ mod tests {
 
     #[test]
     fn single_coord_cluster_layers() {
-        let cluster = Cluster::new(vec![Coord::ZERO].iter().map(|&c| c));
+        let cluster = Cluster::new([Coord::ZERO].iter().copied());
         assert!(cluster.iter_interior().count() == 0);
         assert!(cluster.iter_internal_border().count() == 1);
         assert!(cluster.iter_external_border().count() == 8);
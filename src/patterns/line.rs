@@ -18,12 +18,44 @@ impl Line {
         }
     }
 
+    /// Returns the per-axis distance from `from` to `to`, which is computed in
+    /// 64 bits since it can exceed the range of `i32` for far apart endpoints.
+    fn delta(&self) -> (i64, i64) {
+        (
+            self.to.x as i64 - self.from.x as i64,
+            self.to.y as i64 - self.from.y as i64,
+        )
+    }
+
     /// Traces Bresenham's line algorithm between `from` and `to`.
-    pub fn iter(&self) -> impl Iterator<Item = Coord> {
-        let delta = self.to - self.from;
-        let x_step = Coord::new(delta.x.signum(), 0);
-        let y_step = Coord::new(0, delta.y.signum());
-        let x_is_major = delta.x.abs() > delta.y.abs();
+    ///
+    /// Where the line passes exactly halfway between two cells, the choice
+    /// between them depends on the direction of travel, so the line from `to`
+    /// to `from` may visit different cells. Use
+    /// [`iter_symmetric`](Line::iter_symmetric) when that matters.
+    pub fn iter(&self) -> LineIter {
+        self.bresenham(false)
+    }
+
+    /// Traces Bresenham's line algorithm like [`iter`](Line::iter), but
+    /// breaks ties the same way whichever endpoint it starts from, so the
+    /// line from `to` to `from` visits exactly the same cells in reverse.
+    ///
+    /// Ties are broken as [`iter`](Line::iter) would when tracing from the
+    /// endpoint with the smaller x coordinate (or the smaller y, for vertical
+    /// lines).
+    pub fn iter_symmetric(&self) -> LineIter {
+        let is_reversed = (self.from.x, self.from.y) > (self.to.x, self.to.y);
+        self.bresenham(is_reversed)
+    }
+
+    /// Sets up Bresenham's line algorithm from `from` to `to`, stepping along
+    /// the minor axis on ties when `minor_on_ties` is set.
+    fn bresenham(&self, minor_on_ties: bool) -> LineIter {
+        let delta = self.delta();
+        let x_step = Coord::new(delta.0.signum() as i32, 0);
+        let y_step = Coord::new(0, delta.1.signum() as i32);
+        let x_is_major = delta.0.abs() > delta.1.abs();
 
         let (major_step, minor_step) = if x_is_major {
             (x_step, y_step)
@@ -32,24 +64,46 @@ impl Line {
         };
 
         let (major_fault, minor_fault) = if x_is_major {
-            (delta.x.abs(), delta.y.abs())
+            (delta.0.abs(), delta.1.abs())
         } else {
-            (delta.y.abs(), delta.x.abs())
+            (delta.1.abs(), delta.0.abs())
         };
 
+        // The fault is kept doubled so that it starts out at a whole number.
         LineIter {
             end_coord: self.to,
             next_coord: self.from,
             major_step,
             minor_step,
-            fault: major_fault as f32 / 2.0,
-            major_fault,
-            minor_fault,
+            fault: major_fault,
+            major_fault: 2 * major_fault,
+            minor_fault: 2 * minor_fault,
+            minor_on_ties,
             is_finished: false,
         }
     }
+
+    /// Walks every cell whose interior the straight segment between the
+    /// centers of `from` and `to` passes through, in order. Where the segment
+    /// goes exactly through a cell corner, the walk steps diagonally.
+    ///
+    /// Unlike [`iter`](Line::iter), the walk from `to` to `from` visits
+    /// exactly the same cells in reverse, which makes it suitable for
+    /// symmetric queries like line of sight.
+    pub fn iter_supercover(&self) -> SupercoverIter {
+        let delta = self.delta();
+        SupercoverIter {
+            next_coord: Some(self.from),
+            step: Coord::new(delta.0.signum() as i32, delta.1.signum() as i32),
+            length: (delta.0.abs(), delta.1.abs()),
+            progress: (0, 0),
+        }
+    }
 }
 
+/// Iterates over the cells of a Bresenham line, from one endpoint to the
+/// other. Created by [`Line::iter`] and [`Line::iter_symmetric`].
+#[derive(Debug, Clone)]
 pub struct LineIter {
     end_coord: Coord,
     next_coord: Coord,
@@ -57,11 +111,13 @@ pub struct LineIter {
     major_step: Coord,
     // Added to the coordinate when `fault` falls below zero.
     minor_step: Coord,
-    fault: f32,
+    fault: i64,
     // Amount to add to `fault` when it falls below zero.
-    major_fault: i32,
+    major_fault: i64,
     // Amount to subtract from `fault` every iteration.
-    minor_fault: i32,
+    minor_fault: i64,
+    // Whether to also take the minor step when `fault` reaches exactly zero.
+    minor_on_ties: bool,
     is_finished: bool,
 }
 
@@ -82,16 +138,182 @@ impl Iterator for LineIter {
 
         self.next_coord += self.major_step;
 
-        self.fault -= self.minor_fault as f32;
+        self.fault -= self.minor_fault;
         // The choice of < over <= here seems arbitrary. The step patterns they
         // produce are mirror images of each other, for example:
-        //  < 0.0 -- 3-4-4-5-4-3
-        // <= 0.0 -- 3-4-5-4-4-3
-        if self.fault < 0.0 {
-            self.fault += self.major_fault as f32;
+        //  < 0 -- 3-4-4-5-4-3
+        // <= 0 -- 3-4-5-4-4-3
+        // so tracing a line backwards with the other choice retraces it.
+        if self.fault < 0 || (self.minor_on_ties && self.fault == 0) {
+            self.fault += self.major_fault;
             self.next_coord += self.minor_step;
         }
 
         Some(return_coord)
     }
 }
+
+/// Iterates over every cell a line passes through. Created by
+/// [`Line::iter_supercover`].
+#[derive(Debug, Clone)]
+pub struct SupercoverIter {
+    next_coord: Option<Coord>,
+    step: Coord,
+    // Number of columns and rows to cross before reaching the end.
+    length: (i64, i64),
+    // Number of columns and rows crossed so far.
+    progress: (i64, i64),
+}
+
+impl Iterator for SupercoverIter {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Coord> {
+        let return_coord = self.next_coord?;
+        let (nx, ny) = self.length;
+        let (ix, iy) = self.progress;
+        if (ix, iy) == self.length {
+            self.next_coord = None;
+            return Some(return_coord);
+        }
+
+        // Compares when the segment crosses the next column boundary with when
+        // it crosses the next row boundary, scaled to stay in integers. The
+        // products can exceed 64 bits for lines spanning most of the
+        // coordinate space.
+        let decision = (1 + 2 * ix as i128) * ny as i128 - (1 + 2 * iy as i128) * nx as i128;
+        let mut next_coord = return_coord;
+        if decision <= 0 {
+            next_coord.x += self.step.x;
+            self.progress.0 += 1;
+        }
+        if decision >= 0 {
+            next_coord.y += self.step.y;
+            self.progress.1 += 1;
+        }
+        self.next_coord = Some(next_coord);
+
+        Some(return_coord)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supercover_is_symmetric() {
+        for (x, y) in (-4..=4).flat_map(|x| (-4..=4).map(move |y| (x, y))) {
+            let forward = Line::new((1, 2), (x, y))
+                .iter_supercover()
+                .collect::<Vec<_>>();
+            let mut backward = Line::new((x, y), (1, 2))
+                .iter_supercover()
+                .collect::<Vec<_>>();
+            backward.reverse();
+            assert_eq!(forward, backward);
+        }
+    }
+
+    #[test]
+    fn supercover_steps() {
+        let coords = Line::new((0, 0), (3, 1))
+            .iter_supercover()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            coords,
+            vec![
+                Coord::new(0, 0),
+                Coord::new(1, 0),
+                Coord::new(2, 1),
+                Coord::new(3, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn far_apart_endpoints() {
+        let (min, max) = (i32::MIN, i32::MAX);
+        let line = Line::new((min, min), (max, max));
+        assert_eq!(
+            line.iter().take(3).collect::<Vec<_>>(),
+            vec![
+                Coord::new(min, min),
+                Coord::new(min + 1, min + 1),
+                Coord::new(min + 2, min + 2)
+            ]
+        );
+        assert_eq!(
+            line.iter_supercover().take(2).collect::<Vec<_>>(),
+            vec![Coord::new(min, min), Coord::new(min + 1, min + 1)]
+        );
+
+        // Lines between any two corners start off in the right direction.
+        let corners = [(min, min), (max, min), (min, max), (max, max)];
+        for &from in corners.iter() {
+            for &to in corners.iter() {
+                let second = Line::new(from, to).iter().nth(1);
+                let step = |from: i32, to: i32| from + (to as i64 - from as i64).signum() as i32;
+                let expected = (step(from.0, to.0), step(from.1, to.1));
+                if from == to {
+                    assert_eq!(second, None);
+                } else {
+                    assert_eq!(second, Some(expected.into()), "{:?} {:?}", from, to);
+                }
+            }
+        }
+
+        // A short line along the edge ends exactly at its endpoint.
+        let coords = Line::new((max - 4, min), (max, min + 1))
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(coords.len(), 5);
+        assert_eq!(coords.last(), Some(&Coord::new(max, min + 1)));
+        let coords = Line::new((max, min + 1), (max - 4, min))
+            .iter_supercover()
+            .collect::<Vec<_>>();
+        assert_eq!(coords.len(), 6);
+        assert_eq!(coords.last(), Some(&Coord::new(max - 4, min)));
+    }
+
+    #[test]
+    fn symmetric_lines_retrace_themselves() {
+        let from = Coord::new(1, -2);
+        for (x, y) in (-6..=6).flat_map(|x| (-6..=6).map(move |y| (x, y))) {
+            let to = from + Coord::new(x, y);
+            let forward = Line::new(from, to).iter_symmetric().collect::<Vec<_>>();
+            let mut backward = Line::new(to, from).iter_symmetric().collect::<Vec<_>>();
+            backward.reverse();
+            assert_eq!(forward, backward, "{:?} {:?}", from, to);
+            assert_eq!(forward.first(), Some(&from));
+            assert_eq!(forward.last(), Some(&to));
+        }
+
+        // Lines traced from their smaller endpoint match `iter`.
+        let line = Line::new((0, 0), (4, 2));
+        assert!(line.iter_symmetric().eq(line.iter()));
+        assert!(Line::new((4, 2), (0, 0))
+            .iter_symmetric()
+            .ne(Line::new((4, 2), (0, 0)).iter()));
+    }
+
+    #[test]
+    fn iterators_can_be_stored_and_cloned() {
+        struct Projectile {
+            path: LineIter,
+        }
+
+        let mut projectile = Projectile {
+            path: Line::new((0, 0), (3, 1)).iter(),
+        };
+        assert_eq!(projectile.path.next(), Some(Coord::new(0, 0)));
+        let rest = projectile.path.clone().collect::<Vec<_>>();
+        assert_eq!(rest.len(), 3);
+        assert!(projectile.path.eq(rest));
+        assert!(format!("{:?}", Line::new((0, 0), (1, 1)).iter()).starts_with("LineIter"));
+
+        let neighbors = crate::patterns::Connectivity::Eight.neighbors((0, 0));
+        assert_eq!(neighbors.clone().count(), 8);
+        assert_eq!(neighbors.count(), 8);
+    }
+}
@@ -7,13 +7,18 @@
 //! actual cell values.
 
 mod circle;
+#[cfg(feature = "std")]
 mod cluster;
 mod line;
 mod neighborhood;
 mod rect;
 
 pub use circle::{Circle, CircleIter};
+#[cfg(feature = "std")]
 pub use cluster::{Cluster, ExternalBorderIter};
-pub use line::{Line, LineIter};
-pub use neighborhood::Neighborhood;
-pub use rect::{BspTree, Orientation, Rect, RectIter};
+pub use line::{Line, LineIter, SupercoverIter};
+pub use neighborhood::{Connectivity, Neighborhood, NeighborhoodIter};
+pub use rect::{
+    BorderIter, BspTree, ColumnMajorIter, InvalidRectError, Orientation, Rect, RectIter, SnakeIter,
+    SpiralIter,
+};
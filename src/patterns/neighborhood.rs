@@ -1,3 +1,6 @@
+use alloc::{borrow::Cow, collections::BTreeSet, vec::Vec};
+use core::slice;
+
 use crate::coord::Coord;
 
 const NEIGHBOR_OFFSETS: [Coord; 8] = [
@@ -20,49 +23,336 @@ const DIAG_NEIGHBOR_OFFSETS: [Coord; 4] = [
     Coord::NORTH_WEST,
 ];
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Neighborhood(Coord);
+/// A set of offsets from a cell to the cells that count as its neighbors.
+///
+/// The built-in shapes are the [`moore`](Neighborhood::moore) and
+/// [`von_neumann`](Neighborhood::von_neumann) neighborhoods, and any other
+/// shape can be made with [`custom`](Neighborhood::custom), such as the six
+/// neighbors of axial hex coordinates or the moves of a chess piece. Each
+/// offset appears once, and the center is left out unless it's added with
+/// [`including_center`](Neighborhood::including_center).
+///
+/// Neighbors which would lie past `i32::MIN` or `i32::MAX` don't exist, so
+/// they're skipped rather than wrapping around to the other end of the
+/// coordinate space.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Neighborhood {
+    offsets: Cow<'static, [Coord]>,
+}
+
+static MOORE: Neighborhood = Neighborhood::from_static(&NEIGHBOR_OFFSETS);
+static VON_NEUMANN: Neighborhood = Neighborhood::from_static(&ORTHO_NEIGHBOR_OFFSETS);
 
 impl Neighborhood {
-    pub fn new<C: Into<Coord>>(coord: C) -> Self {
-        Self(coord.into())
+    const fn from_static(offsets: &'static [Coord]) -> Self {
+        Self {
+            offsets: Cow::Borrowed(offsets),
+        }
     }
 
-    /// Returns the orthogonal and diagonal (Moore) neighborhood of `coord`.
-    pub fn iter(&self) -> impl Iterator<Item = Coord> + '_ {
-        NEIGHBOR_OFFSETS.iter().map(move |&offset| self.0 + offset)
+    /// The eight orthogonal and diagonal neighbors, clockwise from north.
+    pub fn moore() -> Self {
+        MOORE.clone()
     }
 
-    /// Returns the orthogonal and diagonal (Moore) neighborhood of `coord`.
-    pub fn into_iter(self) -> impl Iterator<Item = Coord> {
-        NEIGHBOR_OFFSETS.iter().map(move |&offset| self.0 + offset)
+    /// The four orthogonal neighbors, clockwise from north.
+    pub fn von_neumann() -> Self {
+        VON_NEUMANN.clone()
     }
 
-    /// Returns the orthogonal (Von Neumann) neighborhood of `coord`.
-    pub fn iter_ortho(&self) -> impl Iterator<Item = Coord> + '_ {
-        ORTHO_NEIGHBOR_OFFSETS
-            .iter()
-            .map(move |&offset| self.0 + offset)
+    /// The four diagonal neighbors, clockwise from north east.
+    pub fn diagonal() -> Self {
+        Self::from_static(&DIAG_NEIGHBOR_OFFSETS)
     }
 
-    /// Returns the orthogonal (Von Neumann) neighborhood of `coord`.
-    pub fn into_iter_ortho(self) -> impl Iterator<Item = Coord> {
-        ORTHO_NEIGHBOR_OFFSETS
-            .iter()
-            .map(move |&offset| self.0 + offset)
+    /// A neighborhood of the given offsets, in the order given. Repeated
+    /// offsets are only kept the first time they appear.
+    pub fn custom<I>(offsets: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Coord>,
+    {
+        let mut unique = Vec::new();
+        for offset in offsets {
+            let offset = offset.into();
+            if !unique.contains(&offset) {
+                unique.push(offset);
+            }
+        }
+        Self {
+            offsets: Cow::Owned(unique),
+        }
     }
 
-    /// Returns the diagonal neighborhood of `coord` (for completeness).
-    pub fn iter_diag(&self) -> impl Iterator<Item = Coord> + '_ {
-        DIAG_NEIGHBOR_OFFSETS
-            .iter()
-            .map(move |&offset| self.0 + offset)
+    /// Extends the neighborhood to every cell that can be reached in at most
+    /// `radius` steps through it, which makes the Moore neighborhood a square
+    /// `2 * radius + 1` cells wide and the Von Neumann neighborhood a diamond.
+    ///
+    /// A radius of 1 leaves the neighborhood as it is. Otherwise the offsets
+    /// are ordered row by row, and the center is only kept if it was already
+    /// included, as the first offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radius` is negative.
+    pub fn with_radius(self, radius: i32) -> Self {
+        assert!(radius >= 0, "negative neighborhood radius {}", radius);
+        if radius == 1 {
+            return self;
+        }
+        let has_center = self.offsets.contains(&Coord::ZERO);
+        let mut reached = BTreeSet::new();
+        reached.insert((0, 0));
+        for _ in 0..radius {
+            let mut next = reached.clone();
+            for &(y, x) in reached.iter() {
+                for offset in self.offsets.iter() {
+                    if let Some(sum) = Coord::new(x, y).checked_add(*offset) {
+                        next.insert((sum.y, sum.x));
+                    }
+                }
+            }
+            reached = next;
+        }
+        reached.remove(&(0, 0));
+        let reached = Self {
+            offsets: Cow::Owned(reached.into_iter().map(|(y, x)| Coord::new(x, y)).collect()),
+        };
+        if has_center {
+            reached.including_center()
+        } else {
+            reached
+        }
+    }
+
+    /// Adds the center cell to the neighborhood as its first offset, if it
+    /// isn't already part of it.
+    pub fn including_center(self) -> Self {
+        if self.offsets.contains(&Coord::ZERO) {
+            return self;
+        }
+        let mut offsets = Vec::with_capacity(self.offsets.len() + 1);
+        offsets.push(Coord::ZERO);
+        offsets.extend_from_slice(&self.offsets);
+        Self {
+            offsets: Cow::Owned(offsets),
+        }
     }
 
-    /// Returns the diagonal neighborhood of `coord` (for completeness).
-    pub fn into_iter_diag(self) -> impl Iterator<Item = Coord> {
-        DIAG_NEIGHBOR_OFFSETS
+    /// Returns the offsets from a cell to each of its neighbors.
+    pub fn offsets(&self) -> &[Coord] {
+        &self.offsets
+    }
+
+    /// Returns the largest distance along either axis from a cell to one of
+    /// its neighbors, or 0 if there aren't any.
+    pub fn reach(&self) -> u32 {
+        self.offsets
             .iter()
-            .map(move |&offset| self.0 + offset)
+            .map(|offset| offset.x.unsigned_abs().max(offset.y.unsigned_abs()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the neighbors of `coord`, in the order of the offsets.
+    pub fn iter_around<C: Into<Coord>>(&self, coord: C) -> NeighborhoodIter<'_> {
+        NeighborhoodIter::new(coord.into(), &self.offsets)
+    }
+
+    /// Returns whether the neighborhood holds the same offsets as `offsets`,
+    /// in any order.
+    pub(crate) fn has_offsets(&self, offsets: &[Coord]) -> bool {
+        self.offsets.len() == offsets.len()
+            && offsets.iter().all(|offset| self.offsets.contains(offset))
+    }
+
+    pub(crate) fn is_moore(&self) -> bool {
+        self.has_offsets(&NEIGHBOR_OFFSETS)
+    }
+
+    pub(crate) fn is_von_neumann(&self) -> bool {
+        self.has_offsets(&ORTHO_NEIGHBOR_OFFSETS)
+    }
+}
+
+impl AsRef<Neighborhood> for Neighborhood {
+    fn as_ref(&self) -> &Neighborhood {
+        self
+    }
+}
+
+/// Iterates over the coords at a fixed set of offsets from a center coord,
+/// skipping any that would lie past `i32::MIN` or `i32::MAX`. Created by
+/// [`Neighborhood::iter_around`] and by [`Connectivity::neighbors`].
+#[derive(Debug, Clone)]
+pub struct NeighborhoodIter<'a> {
+    center: Coord,
+    offsets: slice::Iter<'a, Coord>,
+}
+
+impl<'a> NeighborhoodIter<'a> {
+    fn new(center: Coord, offsets: &'a [Coord]) -> Self {
+        Self {
+            center,
+            offsets: offsets.iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for NeighborhoodIter<'a> {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Coord> {
+        let center = self.center;
+        self.offsets.find_map(|&offset| center.checked_add(offset))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.offsets.size_hint().1)
+    }
+}
+
+/// Which cells count as adjacent to each other, for algorithms that spread
+/// from cell to cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Connectivity {
+    /// Only orthogonal (Von Neumann) neighbors are adjacent.
+    Four,
+    /// Orthogonal and diagonal (Moore) neighbors are adjacent.
+    Eight,
+}
+
+impl Connectivity {
+    /// Returns the offsets from a cell to each of its adjacent cells, in the
+    /// same order as the matching [`Neighborhood`].
+    pub fn offsets(&self) -> &'static [Coord] {
+        match self {
+            Connectivity::Four => &ORTHO_NEIGHBOR_OFFSETS,
+            Connectivity::Eight => &NEIGHBOR_OFFSETS,
+        }
+    }
+
+    /// Returns the coords adjacent to `coord`, skipping any that would lie
+    /// past `i32::MIN` or `i32::MAX`.
+    pub fn neighbors<C: Into<Coord>>(&self, coord: C) -> NeighborhoodIter<'static> {
+        NeighborhoodIter::new(coord.into(), self.offsets())
+    }
+}
+
+/// Lets the four and eight connected neighborhoods be passed wherever a
+/// [`Neighborhood`] is accepted.
+impl AsRef<Neighborhood> for Connectivity {
+    fn as_ref(&self) -> &Neighborhood {
+        match self {
+            Connectivity::Four => &VON_NEUMANN,
+            Connectivity::Eight => &MOORE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORNERS: [Coord; 4] = [
+        Coord::new(i32::MIN, i32::MIN),
+        Coord::new(i32::MAX, i32::MIN),
+        Coord::new(i32::MIN, i32::MAX),
+        Coord::new(i32::MAX, i32::MAX),
+    ];
+
+    #[test]
+    fn skips_neighbors_past_the_coordinate_limits() {
+        for &corner in CORNERS.iter() {
+            let neighbors = Neighborhood::moore()
+                .iter_around(corner)
+                .collect::<Vec<_>>();
+            assert_eq!(neighbors.len(), 3, "{:?}", corner);
+            for neighbor in neighbors {
+                let distance = (
+                    (neighbor.x as i64 - corner.x as i64).abs(),
+                    (neighbor.y as i64 - corner.y as i64).abs(),
+                );
+                assert!(distance == (0, 1) || distance == (1, 0) || distance == (1, 1));
+            }
+            assert_eq!(Neighborhood::von_neumann().iter_around(corner).count(), 2);
+            assert_eq!(Neighborhood::diagonal().iter_around(corner).count(), 1);
+            assert_eq!(Connectivity::Four.neighbors(corner).count(), 2);
+            assert_eq!(Connectivity::Eight.neighbors(corner).count(), 3);
+        }
+
+        let moore = Neighborhood::moore();
+        let mut edge = moore.iter_around((7, i32::MAX));
+        assert_eq!(edge.clone().count(), 5);
+        assert!(edge.all(|neighbor| neighbor.y >= i32::MAX - 1));
+    }
+
+    #[test]
+    fn builders() {
+        assert_eq!(Neighborhood::moore().offsets(), &NEIGHBOR_OFFSETS);
+        assert_eq!(Neighborhood::moore().with_radius(1), Neighborhood::moore());
+        assert_eq!(Neighborhood::moore().reach(), 1);
+
+        let square = Neighborhood::moore().with_radius(2);
+        assert_eq!(square.offsets().len(), 24);
+        assert_eq!(square.reach(), 2);
+        assert_eq!(square.offsets()[0], Coord::new(-2, -2));
+        assert!(!square.offsets().contains(&Coord::ZERO));
+
+        let diamond = Neighborhood::von_neumann().with_radius(2);
+        assert_eq!(diamond.offsets().len(), 12);
+        assert!(diamond.offsets().contains(&Coord::new(1, -1)));
+        assert!(!diamond.offsets().contains(&Coord::new(2, 1)));
+
+        let centered = Neighborhood::von_neumann().including_center();
+        assert_eq!(centered.offsets()[0], Coord::ZERO);
+        assert_eq!(centered.offsets().len(), 5);
+        assert_eq!(centered.clone().including_center(), centered);
+        assert_eq!(
+            centered.clone().with_radius(2),
+            Neighborhood::von_neumann()
+                .with_radius(2)
+                .including_center()
+        );
+        assert_eq!(
+            Neighborhood::moore().with_radius(0).offsets(),
+            &[] as &[Coord]
+        );
+        assert_eq!(
+            Neighborhood::moore()
+                .including_center()
+                .with_radius(0)
+                .offsets(),
+            &[Coord::ZERO]
+        );
+
+        assert!(Connectivity::Eight.as_ref().is_moore());
+        assert!(Connectivity::Four.as_ref().is_von_neumann());
+        assert!(Neighborhood::custom(ORTHO_NEIGHBOR_OFFSETS.iter().rev()).is_von_neumann());
+    }
+
+    #[test]
+    fn custom_offsets() {
+        let knight = Neighborhood::custom(vec![
+            (1, 2),
+            (2, 1),
+            (2, -1),
+            (1, -2),
+            (-1, -2),
+            (-2, -1),
+            (-2, 1),
+            (-1, 2),
+            (1, 2),
+        ]);
+        assert_eq!(knight.offsets().len(), 8);
+        assert_eq!(knight.reach(), 2);
+        assert_eq!(
+            knight.iter_around((10, 10)).take(2).collect::<Vec<_>>(),
+            vec![Coord::new(11, 12), Coord::new(12, 11)]
+        );
+        assert!(!knight.is_moore());
+
+        let hex = Neighborhood::custom([(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)]);
+        assert_eq!(hex.with_radius(2).offsets().len(), 18);
     }
 }
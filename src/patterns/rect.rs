@@ -1,8 +1,14 @@
-use std::ops::Range;
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::ops::Range;
 
 use crate::coord::Coord;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A rectangular area of coords, including `top` and `left` but excluding
+/// `bottom` and `right`.
+///
+/// A rect with zero width or height has zero area: it contains no coords, its
+/// iterators yield nothing, and it never intersects another rect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Rect {
     pub top: i32,
     pub bottom: i32,
@@ -12,10 +18,34 @@ pub struct Rect {
 
 impl Rect {
     /// Constructs a Rect at (0, 0).
+    ///
+    /// `dimensions` is treated as the opposite corner, so a negative
+    /// dimension extends the rect in the negative direction instead: the rect
+    /// of `(-2, 3)` spans x coords -2 and -1. Use [`try_new`](Rect::try_new)
+    /// to reject negative dimensions instead.
     pub fn new<C: Into<Coord>>(dimensions: C) -> Self {
         Self::with_corners((0, 0), dimensions)
     }
 
+    /// Constructs a Rect with its `(left, top)` corner at `origin`, or returns
+    /// an error if either dimension is negative or the far corner would lie
+    /// past `i32::MAX`.
+    pub fn try_new<C1, C2>(origin: C1, dimensions: C2) -> Result<Self, InvalidRectError>
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        let origin = origin.into();
+        let dimensions = dimensions.into();
+        if dimensions.x < 0 || dimensions.y < 0 {
+            return Err(InvalidRectError::NegativeDimensions(dimensions));
+        }
+        let corner = origin
+            .checked_add(dimensions)
+            .ok_or(InvalidRectError::Overflow)?;
+        Ok(Self::with_corners(origin, corner))
+    }
+
     /// Constructs a Rect, given _any_ two corners.
     ///
     /// It is advisable to use this over creating a RectBounds literal, because
@@ -120,11 +150,37 @@ impl Rect {
         }
     }
 
+    /// Returns the rect grown by `margin` on every side, or shrunk if `margin`
+    /// is negative.
+    pub fn expand(&self, margin: i32) -> Self {
+        Self {
+            top: self.top - margin,
+            bottom: self.bottom + margin,
+            left: self.left - margin,
+            right: self.right + margin,
+        }
+    }
+
     pub fn contains<C: Into<Coord>>(&self, coord: C) -> bool {
         let coord = coord.into();
         coord.x >= self.left && coord.x < self.right && coord.y >= self.top && coord.y < self.bottom
     }
 
+    /// Returns the area covered by both rects, or `None` if they don't overlap.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let rect = Rect {
+            top: self.top.max(other.top),
+            bottom: self.bottom.min(other.bottom),
+            left: self.left.max(other.left),
+            right: self.right.min(other.right),
+        };
+        if rect.top < rect.bottom && rect.left < rect.right {
+            Some(rect)
+        } else {
+            None
+        }
+    }
+
     pub fn x_range(&self) -> Range<i32> {
         self.left..self.right
     }
@@ -133,15 +189,82 @@ impl Rect {
         self.top..self.bottom
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = Coord> {
+    /// Iterates column by column, starting from the `(left, top)` corner.
+    pub fn iter_column_major(&self) -> ColumnMajorIter {
+        ColumnMajorIter {
+            rect: *self,
+            next_coord: self.offset(),
+            is_finished: self.is_empty(),
+        }
+    }
+
+    /// Iterates row by row like [`iter`](Rect::iter), but every other row is
+    /// traversed in reverse (boustrophedon order), so that consecutive coords
+    /// are always adjacent.
+    pub fn iter_snake(&self) -> SnakeIter {
+        SnakeIter {
+            rect: *self,
+            next_coord: self.offset(),
+            is_finished: self.is_empty(),
+        }
+    }
+
+    /// Iterates from the outside in, tracing the
+    /// [`iter_border`](Rect::iter_border) of successively smaller rects.
+    pub fn iter_spiral(&self) -> SpiralIter {
+        SpiralIter {
+            ring: *self,
+            border: self.iter_border(),
+        }
+    }
+
+    /// Iterates over the outermost coords of the rect, each exactly once.
+    ///
+    /// Starts at the `(left, top)` corner and proceeds clockwise (with `top`
+    /// displayed first): along the top edge, down the right edge, back along
+    /// the bottom edge, and up the left edge.
+    pub fn iter_border(&self) -> BorderIter {
+        let (width, height) = (self.width() as i64, self.height() as i64);
+        let len = if self.is_empty() {
+            0
+        } else if width == 1 || height == 1 {
+            // Rects one cell thick have their bottom (or left) edge already
+            // covered by the top (or right) edge.
+            width.max(height)
+        } else {
+            2 * (width + height) - 4
+        };
+        BorderIter {
+            rect: *self,
+            index: 0,
+            len,
+        }
+    }
+
+    pub fn iter(&self) -> RectIter {
         let next_coord = Coord::new(self.left, self.top);
 
         RectIter {
             rect: *self,
             next_coord,
-            is_finished: false,
+            is_finished: self.is_empty(),
         }
     }
+
+    /// Returns whether the rect has zero width or height, and so contains no
+    /// coords.
+    pub fn is_empty(&self) -> bool {
+        self.left >= self.right || self.top >= self.bottom
+    }
+}
+
+/// An error produced by [`Rect::try_new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidRectError {
+    /// At least one of the dimensions is negative.
+    NegativeDimensions(Coord),
+    /// The far corner of the rect lies past `i32::MAX`.
+    Overflow,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -179,6 +302,8 @@ impl BspTree {
 }
 
 /// Iterates row by row from the bottom-left corner to the top-right corner.
+/// Created by [`Rect::iter`].
+#[derive(Debug, Clone)]
 pub struct RectIter {
     rect: Rect,
     next_coord: Coord,
@@ -210,8 +335,153 @@ impl Iterator for RectIter {
     }
 }
 
+/// Iterates column by column. Created by [`Rect::iter_column_major`].
+#[derive(Debug, Clone)]
+pub struct ColumnMajorIter {
+    rect: Rect,
+    next_coord: Coord,
+    is_finished: bool,
+}
+
+impl Iterator for ColumnMajorIter {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+        let next_coord = self.next_coord;
+        if self.next_coord.y < self.rect.bottom - 1 {
+            self.next_coord.y += 1;
+        } else if self.next_coord.x < self.rect.right - 1 {
+            self.next_coord.y = self.rect.top;
+            self.next_coord.x += 1;
+        } else {
+            self.is_finished = true;
+        }
+        Some(next_coord)
+    }
+}
+
+/// Iterates row by row, reversing every other row. Created by
+/// [`Rect::iter_snake`].
+#[derive(Debug, Clone)]
+pub struct SnakeIter {
+    rect: Rect,
+    next_coord: Coord,
+    is_finished: bool,
+}
+
+impl Iterator for SnakeIter {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+        let next_coord = self.next_coord;
+        let is_reversed = (next_coord.y - self.rect.top) % 2 == 1;
+        let row_end = if is_reversed {
+            self.rect.left
+        } else {
+            self.rect.right - 1
+        };
+        if next_coord.x != row_end {
+            self.next_coord.x += if is_reversed { -1 } else { 1 };
+        } else if next_coord.y < self.rect.bottom - 1 {
+            // The next row starts right below the end of this one.
+            self.next_coord.y += 1;
+        } else {
+            self.is_finished = true;
+        }
+        Some(next_coord)
+    }
+}
+
+/// Iterates clockwise around the edge of a rect. Created by
+/// [`Rect::iter_border`].
+#[derive(Debug, Clone)]
+pub struct BorderIter {
+    rect: Rect,
+    /// The position of the next coord along the border, counted clockwise
+    /// from the `(left, top)` corner.
+    index: i64,
+    len: i64,
+}
+
+impl Iterator for BorderIter {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.len {
+            return None;
+        }
+        let Rect {
+            left,
+            top,
+            right,
+            bottom,
+        } = self.rect;
+        let (width, height) = (self.rect.width() as i64, self.rect.height() as i64);
+        let mut index = self.index;
+        self.index += 1;
+
+        // Walk the edges in order, each ending just before the next corner.
+        if index < width {
+            return Some(Coord::new(left + index as i32, top));
+        }
+        index -= width;
+        if index < height - 1 {
+            return Some(Coord::new(right - 1, top + 1 + index as i32));
+        }
+        index -= height - 1;
+        if index < width - 1 {
+            return Some(Coord::new(right - 2 - index as i32, bottom - 1));
+        }
+        index -= width - 1;
+        Some(Coord::new(left, bottom - 2 - index as i32))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.len - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+/// Iterates over the borders of successively smaller rects, from the outside
+/// in. Created by [`Rect::iter_spiral`].
+#[derive(Debug, Clone)]
+pub struct SpiralIter {
+    ring: Rect,
+    border: BorderIter,
+}
+
+impl Iterator for SpiralIter {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(coord) = self.border.next() {
+                return Some(coord);
+            }
+            self.ring = Rect {
+                top: self.ring.top + 1,
+                bottom: self.ring.bottom - 1,
+                left: self.ring.left + 1,
+                right: self.ring.right - 1,
+            };
+            if self.ring.is_empty() {
+                return None;
+            }
+            self.border = self.ring.iter_border();
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use super::*;
 
     #[test]
@@ -223,8 +493,55 @@ mod tests {
 
     #[test]
     fn single_coord_rect_iter() {
-        let rect = Rect::new((0, 0));
-        assert_eq!(rect.iter().count(), 1);
+        let rect = Rect::new((1, 1));
+        assert_eq!(rect.iter().collect::<Vec<_>>(), vec![Coord::ZERO]);
+    }
+
+    #[test]
+    fn zero_area_rects() {
+        let rects = [
+            Rect::new((0, 0)),
+            Rect::new((0, 3)),
+            Rect::new((3, 0)).translate((-5, 2)),
+        ];
+        let full = Rect::new((10, 10)).translate((-5, -5));
+        for rect in rects.iter() {
+            assert_eq!(rect.area(), 0);
+            assert_eq!(rect.iter().count(), 0, "{:?}", rect);
+            assert_eq!(rect.iter_column_major().count(), 0);
+            assert_eq!(rect.iter_snake().count(), 0);
+            assert_eq!(rect.iter_border().count(), 0);
+            assert!(!rect.contains(rect.offset()));
+            assert_eq!(rect.intersection(&full), None);
+            assert_eq!(full.intersection(rect), None);
+        }
+        // Rects which only share an edge have nothing in common.
+        assert_eq!(
+            Rect::new((2, 2)).intersection(&Rect::new((2, 2)).translate((2, 0))),
+            None
+        );
+    }
+
+    #[test]
+    fn construction() {
+        // `new` treats the dimensions as a corner, whichever way it lies.
+        assert_eq!(Rect::new((-2, 3)), Rect::with_corners((-2, 0), (0, 3)));
+        assert_eq!(Rect::new((-2, 3)).dimensions(), Coord::new(2, 3));
+
+        assert_eq!(
+            Rect::try_new((4, -1), (2, 3)),
+            Ok(Rect::with_corners((4, -1), (6, 2)))
+        );
+        assert_eq!(Rect::try_new((4, -1), (0, 0)).unwrap().area(), 0);
+        assert_eq!(
+            Rect::try_new((4, -1), (2, -3)),
+            Err(InvalidRectError::NegativeDimensions(Coord::new(2, -3)))
+        );
+        assert_eq!(
+            Rect::try_new((i32::MAX - 1, 0), (2, 2)),
+            Err(InvalidRectError::Overflow)
+        );
+        assert_eq!(Rect::try_new((i32::MAX - 1, 0), (1, 2)).unwrap().width(), 1);
     }
 
     #[test]
@@ -296,4 +613,101 @@ mod tests {
         assert_eq!(bsp_leaves.len(), 16);
         assert!(bsp_leaves.iter().all(|rect| rect.area() == 16));
     }
+
+    #[test]
+    fn border_rect_iter() {
+        let rect = Rect::new((3, 3));
+        let coords = rect.iter_border().collect::<Vec<_>>();
+        let expected = [
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (2, 1),
+            (2, 2),
+            (1, 2),
+            (0, 2),
+            (0, 1),
+        ];
+        assert_eq!(
+            coords,
+            expected.iter().map(|&c| c.into()).collect::<Vec<Coord>>()
+        );
+    }
+
+    #[test]
+    fn thin_border_rect_iter() {
+        assert_eq!(Rect::new((1, 1)).iter_border().count(), 1);
+        assert_eq!(Rect::new((1, 4)).iter_border().count(), 4);
+        assert_eq!(Rect::new((4, 1)).iter_border().count(), 4);
+        assert_eq!(Rect::new((2, 2)).iter_border().count(), 4);
+        assert_eq!(Rect::new((0, 0)).iter_border().count(), 0);
+    }
+
+    fn to_coords(pairs: &[(i32, i32)]) -> Vec<Coord> {
+        pairs.iter().map(|&pair| pair.into()).collect()
+    }
+
+    #[test]
+    fn column_major_rect_iter() {
+        let coords = Rect::new((3, 4)).iter_column_major().collect::<Vec<_>>();
+        #[rustfmt::skip]
+        let expected = to_coords(&[
+            (0, 0), (0, 1), (0, 2), (0, 3),
+            (1, 0), (1, 1), (1, 2), (1, 3),
+            (2, 0), (2, 1), (2, 2), (2, 3),
+        ]);
+        assert_eq!(coords, expected);
+    }
+
+    #[test]
+    fn snake_rect_iter() {
+        let coords = Rect::new((3, 4)).iter_snake().collect::<Vec<_>>();
+        #[rustfmt::skip]
+        let expected = to_coords(&[
+            (0, 0), (1, 0), (2, 0),
+            (2, 1), (1, 1), (0, 1),
+            (0, 2), (1, 2), (2, 2),
+            (2, 3), (1, 3), (0, 3),
+        ]);
+        assert_eq!(coords, expected);
+    }
+
+    #[test]
+    fn spiral_rect_iter() {
+        let coords = Rect::new((3, 4)).iter_spiral().collect::<Vec<_>>();
+        #[rustfmt::skip]
+        let expected = to_coords(&[
+            (0, 0), (1, 0), (2, 0),
+            (2, 1), (2, 2), (2, 3),
+            (1, 3), (0, 3),
+            (0, 2), (0, 1),
+            (1, 1), (1, 2),
+        ]);
+        assert_eq!(coords, expected);
+    }
+
+    #[test]
+    fn traversal_orders_cover_rect() {
+        for &dimensions in &[(3, 4), (4, 3), (1, 5), (5, 1), (6, 6), (7, 2)] {
+            let rect = Rect::new(dimensions);
+            let all = rect.iter().collect::<HashSet<_>>();
+            for coords in [
+                rect.iter_column_major().collect::<Vec<_>>(),
+                rect.iter_snake().collect(),
+                rect.iter_spiral().collect(),
+            ] {
+                assert_eq!(coords.len(), all.len());
+                assert_eq!(coords.into_iter().collect::<HashSet<_>>(), all);
+            }
+        }
+    }
+
+    #[test]
+    fn intersection() {
+        let a = Rect::new((4, 3));
+        let b = Rect::new((4, 4)).translate((2, -1));
+        assert_eq!(a.intersection(&b), Some(Rect::with_corners((2, 0), (4, 3))));
+        assert_eq!(a.intersection(&b), b.intersection(&a));
+        assert_eq!(a.intersection(&a.translate((4, 0))), None);
+    }
 }
@@ -0,0 +1,348 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::mem;
+
+use crate::{
+    coord::Coord,
+    grid::{Grid, IterCell},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+/// A grid stored as a quadtree, where every uniform square block collapses
+/// into a single leaf, for large maps made mostly of big uniform regions.
+///
+/// The tree covers the smallest power-of-two square anchored at the grid's
+/// first cell that contains the bounds. Cells of that square outside of the
+/// bounds are never visible, but they're part of the tree, and keep the
+/// values they were built with.
+///
+/// Writes split leaves as needed, and merge four sibling leaves back into one
+/// as soon as they hold equal values, so the tree is always as small as it can
+/// be for its contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuadGrid<T> {
+    root: Node<T>,
+    bounds: Rect,
+    /// Side length of the square covered by the root node.
+    size: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node<T> {
+    Leaf(T),
+    /// Quadrants in row-major order: top left, top right, bottom left, bottom
+    /// right.
+    Branch(Box<[Node<T>; 4]>),
+}
+
+impl<T: Eq + Clone> Grid<T> for QuadGrid<T> {
+    fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        let coord = coord.into();
+        if !self.bounds.contains(coord) {
+            return None;
+        }
+        let mut local = coord - self.bounds.offset();
+        let mut size = self.size;
+        let mut node = &self.root;
+        loop {
+            match node {
+                Node::Leaf(value) => return Some(value),
+                Node::Branch(children) => {
+                    size /= 2;
+                    node = &children[quadrant(local, size)];
+                    local = Coord::new(local.x % size, local.y % size);
+                }
+            }
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Returns an iterator over every cell in row-major order. Each cell is
+    /// looked up individually; [`iter_regions`](QuadGrid::iter_regions) is
+    /// much faster when whole blocks can be handled at once.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+    {
+        let area = self.bounds.area().max(0) as usize;
+        self.bounds
+            .iter()
+            .take(area)
+            .map(move |coord| (coord, self.get(coord).unwrap()))
+    }
+}
+
+impl<T: Eq + Clone> QuadGrid<T> {
+    /// Constructs a grid with every cell set to `value`.
+    pub fn filled(bounds: Rect, value: T) -> Self {
+        Self {
+            root: Node::Leaf(value),
+            bounds,
+            size: root_size(bounds),
+        }
+    }
+
+    /// Builds a grid holding the same cells as `grid`. Cells outside of the
+    /// bounds copy their nearest cell inside, so that they don't prevent
+    /// blocks along the edges from merging.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `grid` has zero area.
+    pub fn from_vec_grid(grid: &VecGrid<T>) -> Self {
+        assert!(
+            grid.bounds.area() > 0,
+            "can't build a QuadGrid from an empty grid"
+        );
+        let size = root_size(grid.bounds);
+        Self {
+            root: build(grid, grid.bounds.offset(), size),
+            bounds: grid.bounds,
+            size,
+        }
+    }
+
+    pub fn to_vec_grid(&self) -> VecGrid<T> {
+        VecGrid::with_generator(self.bounds, |coord: Coord| self.get(coord).unwrap().clone())
+    }
+
+    /// Sets the cell at `coord`, returning false if it's out of bounds.
+    pub fn set<C: Into<Coord>>(&mut self, coord: C, value: T) -> bool {
+        let coord = coord.into();
+        if !self.bounds.contains(coord) {
+            return false;
+        }
+        let rect = Rect::new((1, 1)).translate(coord);
+        self.fill_region(rect, value);
+        true
+    }
+
+    /// Sets every cell covered by `rect` to `value`. Parts of `rect` outside of
+    /// the grid are ignored.
+    ///
+    /// Blocks entirely inside `rect` are replaced without visiting their
+    /// contents, so filling a region aligned to the tree only touches a number
+    /// of nodes proportional to the tree's depth.
+    pub fn fill_region(&mut self, rect: Rect, value: T) {
+        let rect = match rect.intersection(&self.bounds) {
+            Some(rect) => rect,
+            None => return,
+        };
+        let region = Rect::new((self.size, self.size)).translate(self.bounds.offset());
+        fill(&mut self.root, region, rect, self.bounds, &value);
+    }
+
+    /// Returns an iterator over the leaves of the tree, as the rect of cells
+    /// each one covers (clipped to the grid bounds) and their shared value.
+    /// Together the rects cover every cell exactly once.
+    pub fn iter_regions(&self) -> impl Iterator<Item = (Rect, &T)> {
+        let region = Rect::new((self.size, self.size)).translate(self.bounds.offset());
+        RegionIter {
+            stack: vec![(&self.root, region)],
+            bounds: self.bounds,
+        }
+    }
+
+    /// Returns the number of leaves in the tree.
+    pub fn leaf_count(&self) -> usize {
+        self.iter_regions().count()
+    }
+}
+
+struct RegionIter<'a, T> {
+    stack: Vec<(&'a Node<T>, Rect)>,
+    bounds: Rect,
+}
+
+impl<'a, T> Iterator for RegionIter<'a, T> {
+    type Item = (Rect, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, region)) = self.stack.pop() {
+            match node {
+                Node::Leaf(value) => {
+                    if let Some(rect) = region.intersection(&self.bounds) {
+                        return Some((rect, value));
+                    }
+                }
+                Node::Branch(children) => {
+                    // Push in reverse, so that quadrants come out in order.
+                    for (child, quadrant) in children.iter().zip(quadrants(region)).rev() {
+                        self.stack.push((child, quadrant));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Returns the smallest power of two that is at least as large as both
+/// dimensions of `bounds`.
+fn root_size(bounds: Rect) -> i32 {
+    (bounds.width().max(bounds.height()).max(1) as u32).next_power_of_two() as i32
+}
+
+/// Returns the index of the child of a node of side `2 * half` containing the
+/// node-local coordinate `local`.
+fn quadrant(local: Coord, half: i32) -> usize {
+    (local.x >= half) as usize + 2 * (local.y >= half) as usize
+}
+
+/// Splits a square region into its four quadrants, in child order.
+fn quadrants(region: Rect) -> [Rect; 4] {
+    let half = region.width() / 2;
+    let quadrant = Rect::new((half, half)).translate(region.offset());
+    [
+        quadrant,
+        quadrant.translate((half, 0)),
+        quadrant.translate((0, half)),
+        quadrant.translate((half, half)),
+    ]
+}
+
+fn build<T: Eq + Clone>(grid: &VecGrid<T>, origin: Coord, size: i32) -> Node<T> {
+    if size == 1 {
+        let bounds = grid.bounds;
+        let clamped = Coord::new(
+            origin.x.min(bounds.right - 1),
+            origin.y.min(bounds.bottom - 1),
+        );
+        return Node::Leaf(grid.get(clamped).unwrap().clone());
+    }
+    let half = size / 2;
+    let children = [
+        build(grid, origin, half),
+        build(grid, origin + Coord::new(half, 0), half),
+        build(grid, origin + Coord::new(0, half), half),
+        build(grid, origin + Coord::new(half, half), half),
+    ];
+    merged(children)
+}
+
+/// Fills the cells of `node`, which covers `region`, that lie in `rect`.
+fn fill<T: Eq + Clone>(node: &mut Node<T>, region: Rect, rect: Rect, bounds: Rect, value: &T) {
+    let visible = match region.intersection(&bounds) {
+        Some(visible) => visible,
+        None => return,
+    };
+    match visible.intersection(&rect) {
+        None => return,
+        // Every visible cell of the node is being filled, so the hidden ones
+        // may as well be too.
+        Some(overlap) if overlap == visible => {
+            *node = Node::Leaf(value.clone());
+            return;
+        }
+        Some(_) => {}
+    }
+    if let Node::Leaf(current) = node {
+        if current == value {
+            return;
+        }
+        let current = current.clone();
+        *node = Node::Branch(Box::new([
+            Node::Leaf(current.clone()),
+            Node::Leaf(current.clone()),
+            Node::Leaf(current.clone()),
+            Node::Leaf(current),
+        ]));
+    }
+    if let Node::Branch(children) = node {
+        for (child, quadrant) in children.iter_mut().zip(quadrants(region)) {
+            fill(child, quadrant, rect, bounds, value);
+        }
+    }
+    // Take the children out to see whether they can be merged, leaving a
+    // throwaway leaf in their place.
+    if let Node::Branch(children) = mem::replace(node, Node::Leaf(value.clone())) {
+        *node = merged(*children);
+    }
+}
+
+/// Makes a branch out of `children`, or a single leaf if they're all leaves
+/// with the same value.
+fn merged<T: Eq>(children: [Node<T>; 4]) -> Node<T> {
+    if let [Node::Leaf(a), Node::Leaf(b), Node::Leaf(c), Node::Leaf(d)] = &children {
+        if a == b && a == c && a == d {
+            let [first, ..] = children;
+            return first;
+        }
+    }
+    Node::Branch(Box::new(children))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridMut;
+
+    #[test]
+    fn merges_on_write() {
+        let bounds = Rect::new((8, 8));
+        let mut grid = QuadGrid::filled(bounds, 0);
+        assert_eq!(grid.leaf_count(), 1);
+        assert!(grid.set((5, 2), 1));
+        // One leaf per level of the split path, plus the changed cell.
+        assert_eq!(grid.leaf_count(), 10);
+        assert_eq!(grid.get((5, 2)), Some(&1));
+        assert_eq!(grid.get((4, 2)), Some(&0));
+        assert!(grid.set((5, 2), 0));
+        assert_eq!(grid.leaf_count(), 1);
+
+        // Completing a block merges it, all the way up.
+        for coord in Rect::new((2, 2)).iter() {
+            grid.set(coord, 7);
+        }
+        assert_eq!(grid.leaf_count(), 7);
+        grid.fill_region(Rect::new((8, 8)), 7);
+        assert_eq!(grid.leaf_count(), 1);
+        assert!(!grid.set((8, 0), 1));
+    }
+
+    #[test]
+    fn aligned_fills_and_regions() {
+        let mut grid = QuadGrid::filled(Rect::new((8, 8)).translate((-4, -4)), 'o');
+        grid.fill_region(Rect::with_corners((0, -4), (4, 0)), 'x');
+        assert_eq!(grid.leaf_count(), 4);
+        grid.fill_region(Rect::with_corners((-2, -2), (0, 0)), '#');
+        assert_eq!(grid.leaf_count(), 7);
+        // Straddling the center splits a corner off every quadrant.
+        grid.fill_region(Rect::with_corners((-1, 0), (1, 1)), '#');
+        let regions = grid.iter_regions().collect::<Vec<_>>();
+        assert_eq!(regions.len(), 19);
+        let area: i32 = regions.iter().map(|(rect, _)| rect.area()).sum();
+        assert_eq!(area, 64);
+        assert_eq!(regions[4], (Rect::with_corners((0, -4), (4, 0)), &'x'));
+        for (rect, &value) in regions {
+            for coord in rect.iter() {
+                assert_eq!(grid.get(coord), Some(&value));
+            }
+        }
+    }
+
+    #[test]
+    fn vec_grid_round_trip() {
+        // Neither dimension is a power of two, so part of the tree is hidden.
+        let bounds = Rect::new((11, 6)).translate((3, -2));
+        let dense = VecGrid::with_generator(bounds, |(x, y)| (x / 4 + y / 3) % 2 == 0);
+        let mut quad = QuadGrid::from_vec_grid(&dense);
+        assert_eq!(quad.to_vec_grid(), dense);
+        assert!(quad.leaf_count() < 66);
+
+        let mut expected = dense.clone();
+        for (coord, cell) in expected.iter_mut().filter(|(coord, _)| coord.x == coord.y) {
+            *cell = !*cell;
+            quad.set(coord, *cell);
+        }
+        quad.fill_region(Rect::with_corners((0, 0), (7, 2)), true);
+        for coord in Rect::with_corners((3, 0), (7, 2)).iter().take(8) {
+            expected.set(coord, true);
+        }
+        assert_eq!(quad.to_vec_grid(), expected);
+        assert_eq!(quad.get((2, 0)), None);
+    }
+}
@@ -0,0 +1,248 @@
+//! Random grid construction and cell sampling, enabled with the `rand`
+//! feature.
+//!
+//! Everything here draws from the RNG in a fixed order, and never samples a
+//! platform-sized integer, so a seeded RNG gives the same results everywhere.
+
+use alloc::vec::Vec;
+
+use rand::{distributions::Distribution, Rng};
+
+use crate::{coord::Coord, patterns::Rect, vecgrid::VecGrid};
+
+impl<T> VecGrid<T> {
+    /// Constructs a grid of `dimensions`, sampling each cell from `dist` in
+    /// row-major order.
+    pub fn random<C, D, R>(dimensions: C, dist: &D, rng: &mut R) -> Self
+    where
+        C: Into<Coord>,
+        D: Distribution<T>,
+        R: Rng,
+    {
+        Self::with_generator(Rect::new(dimensions), |_: Coord| dist.sample(rng))
+    }
+
+    /// Returns the coordinate of a uniformly chosen cell, or `None` if the
+    /// grid has zero area.
+    pub fn random_coord<R: Rng>(&self, rng: &mut R) -> Option<Coord> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        let index = rng.gen_range(0..self.cells.len() as u64);
+        Some(Self::index_to_coord_with_bounds(
+            self.bounds,
+            index as usize,
+        ))
+    }
+
+    /// Returns the coordinate of a cell chosen uniformly among those matching
+    /// `predicate`, or `None` if none do.
+    ///
+    /// This takes a single pass over the grid without allocating, using
+    /// reservoir sampling, so it draws one random number per match.
+    pub fn random_coord_where<R, F>(&self, rng: &mut R, mut predicate: F) -> Option<Coord>
+    where
+        R: Rng,
+        F: FnMut(Coord, &T) -> bool,
+    {
+        let mut chosen = None;
+        let mut matches = 0u64;
+        for (coord, cell) in self.iter() {
+            if !predicate(coord, cell) {
+                continue;
+            }
+            matches += 1;
+            // Keep the k-th match with probability 1/k.
+            if rng.gen_range(0..matches) == 0 {
+                chosen = Some(coord);
+            }
+        }
+        chosen
+    }
+
+    /// Returns the coordinate of a cell chosen with probability proportional
+    /// to its `weight`, or `None` if no cell has a positive weight.
+    ///
+    /// Negative, NaN and infinite weights count as zero, so those cells are
+    /// never chosen. Like
+    /// [`random_coord_where`](VecGrid::random_coord_where), this takes a
+    /// single pass without allocating, drawing one random number per cell
+    /// with a positive weight.
+    pub fn choose_weighted<R, F>(&self, rng: &mut R, weight: F) -> Option<Coord>
+    where
+        R: Rng,
+        F: Fn(Coord, &T) -> f64,
+    {
+        let weights = self.iter().map(|(coord, cell)| weight(coord, cell));
+        Self::choose_weighted_index(rng, weights)
+            .map(|index| Self::index_to_coord_with_bounds(self.bounds, index))
+    }
+
+    /// Returns the coordinates of up to `n` distinct cells, chosen one after
+    /// another with probability proportional to their `weight` among the
+    /// cells not chosen yet, in the order they were chosen. Fewer than `n` are
+    /// returned if fewer cells have a positive weight.
+    ///
+    /// Weights are treated as in
+    /// [`choose_weighted`](VecGrid::choose_weighted). `weight` is called once
+    /// per cell, and each choice takes a pass over the weights.
+    pub fn choose_multiple_weighted<R, F>(&self, rng: &mut R, n: usize, weight: F) -> Vec<Coord>
+    where
+        R: Rng,
+        F: Fn(Coord, &T) -> f64,
+    {
+        let mut weights = self
+            .iter()
+            .map(|(coord, cell)| weight(coord, cell))
+            .collect::<Vec<_>>();
+        let mut chosen = Vec::with_capacity(n.min(weights.len()));
+        while chosen.len() < n {
+            let index = match Self::choose_weighted_index(rng, weights.iter().copied()) {
+                Some(index) => index,
+                None => break,
+            };
+            weights[index] = 0.0;
+            chosen.push(Self::index_to_coord_with_bounds(self.bounds, index));
+        }
+        chosen
+    }
+
+    /// Picks an index with probability proportional to its weight, by
+    /// keeping each one with probability `weight / total so far`.
+    fn choose_weighted_index<R, I>(rng: &mut R, weights: I) -> Option<usize>
+    where
+        R: Rng,
+        I: IntoIterator<Item = f64>,
+    {
+        let mut chosen = None;
+        let mut total = 0.0;
+        for (index, weight) in weights.into_iter().enumerate() {
+            if !(weight > 0.0 && weight.is_finite()) {
+                continue;
+            }
+            total += weight;
+            if rng.gen::<f64>() * total < weight {
+                chosen = Some(index);
+            }
+        }
+        chosen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridMut;
+    use rand::{distributions::Bernoulli, rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn seeded_rngs_are_deterministic() {
+        let dist = Bernoulli::new(0.3).unwrap();
+        let a = VecGrid::random((12, 9), &dist, &mut StdRng::seed_from_u64(1));
+        let b = VecGrid::random((12, 9), &dist, &mut StdRng::seed_from_u64(1));
+        assert_eq!(a, b);
+        assert_eq!(a.bounds, Rect::new((12, 9)));
+
+        let mut rng = StdRng::seed_from_u64(2);
+        let coords = (0..3)
+            .map(|_| a.random_coord(&mut rng).unwrap())
+            .collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(2);
+        for &coord in coords.iter() {
+            assert_eq!(a.random_coord(&mut rng), Some(coord));
+            assert!(a.bounds.contains(coord));
+        }
+    }
+
+    #[test]
+    fn empty_grids_and_no_matches() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let empty = VecGrid::<u8>::new(Rect::new((0, 5)));
+        assert_eq!(empty.random_coord(&mut rng), None);
+        assert_eq!(empty.random_coord_where(&mut rng, |_, _| true), None);
+        let zeros = VecGrid::filled(Rect::new((4, 4)), 0);
+        assert_eq!(
+            zeros.random_coord_where(&mut rng, |_, &cell| cell == 1),
+            None
+        );
+    }
+
+    #[test]
+    fn random_coord_where_is_uniform() {
+        let grid = VecGrid::with_generator(Rect::new((5, 4)).translate((-2, -2)), |(x, y)| {
+            (x + y) % 3 == 0
+        });
+        let matches = grid.coords_where(|_, &cell| cell).collect::<Vec<_>>();
+        assert_eq!(matches.len(), 7);
+
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut counts = VecGrid::filled(grid.bounds, 0);
+        for _ in 0..7000 {
+            let coord = grid.random_coord_where(&mut rng, |_, &cell| cell).unwrap();
+            *counts.get_mut(coord).unwrap() += 1;
+        }
+        for (coord, &count) in counts.iter() {
+            if matches.contains(&coord) {
+                assert!((850..1150).contains(&count), "{:?}: {}", coord, count);
+            } else {
+                assert_eq!(count, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn choose_weighted_is_proportional() {
+        let weights =
+            VecGrid::from_raw_parts(Rect::new((3, 2)), vec![1.0, 2.0, 3.0, 0.0, -4.0, f64::NAN])
+                .unwrap();
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut counts = VecGrid::filled(weights.bounds, 0);
+        for _ in 0..6000 {
+            let coord = weights
+                .choose_weighted(&mut rng, |_, &weight| weight)
+                .unwrap();
+            *counts.get_mut(coord).unwrap() += 1;
+        }
+        let expected: [i32; 6] = [1000, 2000, 3000, 0, 0, 0];
+        for (&count, &expected) in counts.values().zip(expected.iter()) {
+            assert!(
+                (count - expected).abs() <= expected / 10,
+                "{} {}",
+                count,
+                expected
+            );
+        }
+
+        assert_eq!(weights.choose_weighted(&mut rng, |_, _| 0.0), None);
+        assert_eq!(
+            VecGrid::<u8>::new(Rect::new((0, 2))).choose_weighted(&mut rng, |_, _| 1.0),
+            None
+        );
+    }
+
+    #[test]
+    fn choose_multiple_weighted_without_replacement() {
+        let weights = VecGrid::from_raw_parts(Rect::new((4, 1)), vec![1.0, 1.0, 8.0, 0.0]).unwrap();
+        let mut rng = StdRng::seed_from_u64(6);
+        let mut counts = [0; 4];
+        for _ in 0..5000 {
+            let chosen = weights.choose_multiple_weighted(&mut rng, 2, |_, &weight| weight);
+            assert_eq!(chosen.len(), 2);
+            assert_ne!(chosen[0], chosen[1]);
+            for coord in chosen {
+                counts[coord.x as usize] += 1;
+            }
+        }
+        // The heavy cell is missed only when both light ones are picked
+        // first, with probability 2/90.
+        assert!((4800..4960).contains(&counts[2]), "{:?}", counts);
+        assert!((2350..2750).contains(&counts[0]), "{:?}", counts);
+        assert_eq!(counts[3], 0);
+
+        let all = weights.choose_multiple_weighted(&mut rng, 10, |_, &weight| weight);
+        assert_eq!(all.len(), 3);
+        assert!(weights
+            .choose_multiple_weighted(&mut rng, 3, |_, _| -1.0)
+            .is_empty());
+    }
+}
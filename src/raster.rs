@@ -0,0 +1,589 @@
+//! Conversions between grids and raw RGBA8 pixel buffers, for visual
+//! debugging and for loading levels drawn in a pixel editor.
+//!
+//! Buffers are row-major with four bytes per pixel and no padding between
+//! rows, which is the layout expected by `image::RgbaImage::from_raw` and most
+//! windowing and texture APIs. The first cell of the grid is the top-left
+//! pixel, unless the grid is exported with `y_up` set, which puts its first
+//! row at the bottom of the image.
+//!
+//! With the `image` feature, grids can also be converted to and from the
+//! `image` crate's types directly, and saved as image files.
+
+use alloc::vec::Vec;
+use core::{convert::TryFrom, fmt};
+#[cfg(feature = "image")]
+use std::path::Path;
+
+#[cfg(feature = "image")]
+use image::{DynamicImage, ImageError, Rgba, RgbaImage};
+
+use crate::{
+    coord::Coord,
+    grid::{CellError, GridError},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+impl<T> VecGrid<T> {
+    /// Renders the grid into an RGBA8 buffer, calling `pixel` to get the color
+    /// of each cell. Each cell covers a `scale`×`scale` block of pixels, so
+    /// the image is `width * scale` pixels wide and `height * scale` tall.
+    ///
+    /// Returns [`GridError::Empty`] if the grid has zero area, or
+    /// [`GridError::InvalidScale`] if `scale` is zero or makes the buffer
+    /// larger than `usize::MAX` bytes.
+    ///
+    /// The first row of the grid is at the top of the image, unless `y_up` is
+    /// true, in which case its last row is, for code that treats +y as up.
+    pub fn to_rgba8<F>(&self, scale: u32, y_up: bool, pixel: F) -> Result<Vec<u8>, GridError>
+    where
+        F: Fn(Coord, &T) -> [u8; 4],
+    {
+        if self.bounds.area() <= 0 {
+            return Err(GridError::Empty);
+        }
+        if scale == 0 {
+            return Err(GridError::InvalidScale(Coord::new(0, 0)));
+        }
+        let invalid_scale = || invalid_scale(scale);
+        let size = |cells: i32| (cells as usize).checked_mul(scale as usize);
+        let row_bytes = size(self.bounds.width())
+            .and_then(|row| row.checked_mul(4))
+            .ok_or_else(invalid_scale)?;
+        let len = size(self.bounds.height())
+            .and_then(|rows| rows.checked_mul(row_bytes))
+            .ok_or_else(invalid_scale)?;
+        let scale = scale as usize;
+        let mut bytes = Vec::with_capacity(len);
+        for (y, row) in self.rows_oriented(y_up) {
+            let start = bytes.len();
+            for (x, cell) in (self.bounds.left..).zip(row) {
+                let color = pixel(Coord::new(x, y), cell);
+                for _ in 0..scale {
+                    bytes.extend_from_slice(&color);
+                }
+            }
+            // The rest of the block is made of copies of the first pixel row.
+            for _ in 1..scale {
+                bytes.extend_from_within(start..start + row_bytes);
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Builds a `width`×`height` grid from an RGBA8 buffer, calling `cell` with
+    /// the coordinate and color of each pixel. The grid's origin is `(0, 0)`.
+    ///
+    /// Returns [`GridError::WrongLength`] if `bytes` doesn't hold exactly
+    /// `width * height` pixels, or [`GridError::TooLarge`] if the image has
+    /// more pixels than a grid can hold.
+    pub fn from_rgba8<F>(width: u32, height: u32, bytes: &[u8], cell: F) -> Result<Self, GridError>
+    where
+        F: Fn(Coord, [u8; 4]) -> T,
+    {
+        let (bounds, mut pixels) = pixels(width, height, bytes)?;
+        Ok(VecGrid::with_generator(bounds, |coord| {
+            cell(coord, pixels.next().unwrap())
+        }))
+    }
+
+    /// Fallible version of [`from_rgba8`](VecGrid::from_rgba8), which stops at
+    /// the first pixel `cell` rejects, such as an unexpected color, and returns
+    /// the error along with the pixel's coordinate.
+    pub fn try_from_rgba8<E, F>(
+        width: u32,
+        height: u32,
+        bytes: &[u8],
+        cell: F,
+    ) -> Result<Self, PixelGridError<E>>
+    where
+        F: Fn(Coord, [u8; 4]) -> Result<T, E>,
+    {
+        let (bounds, mut pixels) = pixels(width, height, bytes)?;
+        Ok(VecGrid::try_with_generator(bounds, |coord| {
+            cell(coord, pixels.next().unwrap())
+        })?)
+    }
+}
+
+/// An error produced while building a grid from pixels with
+/// [`VecGrid::try_from_rgba8`](crate::VecGrid::try_from_rgba8).
+#[derive(Debug, PartialEq, Eq)]
+pub enum PixelGridError<E> {
+    /// The pixels can't be made into a grid, as returned by
+    /// [`VecGrid::from_rgba8`](crate::VecGrid::from_rgba8).
+    Grid(GridError),
+    /// The closure rejected a pixel.
+    Cell(CellError<E>),
+}
+
+impl<E> From<GridError> for PixelGridError<E> {
+    fn from(error: GridError) -> Self {
+        PixelGridError::Grid(error)
+    }
+}
+
+impl<E> From<CellError<E>> for PixelGridError<E> {
+    fn from(error: CellError<E>) -> Self {
+        PixelGridError::Cell(error)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for PixelGridError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PixelGridError::Grid(error) => error.fmt(f),
+            PixelGridError::Cell(error) => error.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for PixelGridError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PixelGridError::Grid(error) => Some(error),
+            PixelGridError::Cell(error) => Some(error),
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+impl<T> VecGrid<T> {
+    /// Renders the grid into an image, calling `pixel` to get the color of
+    /// each cell. Each cell covers a `scale`×`scale` block of pixels. The
+    /// top-left cell of the grid, at `(left, top)`, is the top-left pixel of
+    /// the image, so x grows rightwards and y downwards in both. If `y_up` is
+    /// true the rows are flipped instead, so that y grows upwards in the
+    /// image, as in [`to_rgba8`](VecGrid::to_rgba8).
+    ///
+    /// Returns [`GridError::Empty`] if the grid has zero area, since some
+    /// viewers can't open images without pixels, or
+    /// [`GridError::InvalidScale`] if `scale` is zero or makes the image wider
+    /// or taller than `u32::MAX` pixels.
+    pub fn to_image<F>(&self, scale: u32, y_up: bool, pixel: F) -> Result<RgbaImage, GridError>
+    where
+        F: Fn(Coord, &T) -> [u8; 4],
+    {
+        let invalid_scale = || invalid_scale(scale);
+        let width = (self.bounds.width().max(0) as u32)
+            .checked_mul(scale)
+            .ok_or_else(invalid_scale)?;
+        let height = (self.bounds.height().max(0) as u32)
+            .checked_mul(scale)
+            .ok_or_else(invalid_scale)?;
+        let bytes = self.to_rgba8(scale, y_up, pixel)?;
+        Ok(RgbaImage::from_raw(width, height, bytes).unwrap())
+    }
+
+    /// Renders the grid as in [`to_image`](VecGrid::to_image) and saves it to
+    /// `path`, in the format given by its extension. Only PNG files are
+    /// supported.
+    pub fn save_image<P, F>(
+        &self,
+        path: P,
+        scale: u32,
+        y_up: bool,
+        pixel: F,
+    ) -> Result<(), SaveImageError>
+    where
+        P: AsRef<Path>,
+        F: Fn(Coord, &T) -> [u8; 4],
+    {
+        self.to_image(scale, y_up, pixel)?.save(path)?;
+        Ok(())
+    }
+
+    /// Builds a grid from an image, calling `cell` with the coordinate and
+    /// color of each pixel. Images in any format are converted to RGBA8 first.
+    /// The grid's origin is `(0, 0)`, at the top-left pixel of the image.
+    ///
+    /// Returns [`GridError::TooLarge`] if the image has more pixels than a grid
+    /// can hold.
+    pub fn from_image<F>(image: &DynamicImage, cell: F) -> Result<Self, GridError>
+    where
+        F: Fn(Coord, Rgba<u8>) -> T,
+    {
+        let image = image.to_rgba8();
+        VecGrid::from_rgba8(
+            image.width(),
+            image.height(),
+            image.as_raw(),
+            |coord, color| cell(coord, Rgba(color)),
+        )
+    }
+
+    /// Fallible version of [`from_image`](VecGrid::from_image), which stops at
+    /// the first pixel `cell` rejects, such as an unexpected color, and returns
+    /// the error along with the pixel's coordinate.
+    pub fn try_from_image<E, F>(image: &DynamicImage, cell: F) -> Result<Self, PixelGridError<E>>
+    where
+        F: Fn(Coord, Rgba<u8>) -> Result<T, E>,
+    {
+        let image = image.to_rgba8();
+        VecGrid::try_from_rgba8(
+            image.width(),
+            image.height(),
+            image.as_raw(),
+            |coord, color| cell(coord, Rgba(color)),
+        )
+    }
+}
+
+/// An error produced while saving a grid with
+/// [`VecGrid::save_image`](crate::VecGrid::save_image).
+#[cfg(feature = "image")]
+#[derive(Debug)]
+pub enum SaveImageError {
+    /// The grid couldn't be rendered, because it's empty or the scale is
+    /// invalid.
+    Grid(GridError),
+    /// Encoding or writing the image failed.
+    Image(ImageError),
+}
+
+#[cfg(feature = "image")]
+impl From<GridError> for SaveImageError {
+    fn from(error: GridError) -> Self {
+        SaveImageError::Grid(error)
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<ImageError> for SaveImageError {
+    fn from(error: ImageError) -> Self {
+        SaveImageError::Image(error)
+    }
+}
+
+#[cfg(feature = "image")]
+impl fmt::Display for SaveImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveImageError::Grid(error) => write!(f, "failed to render the grid: {}", error),
+            SaveImageError::Image(error) => write!(f, "failed to save the image: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+impl std::error::Error for SaveImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SaveImageError::Grid(error) => Some(error),
+            SaveImageError::Image(error) => Some(error),
+        }
+    }
+}
+
+/// Returns the error for a `scale` that's too large, which reports it on both
+/// axes like the scales of [`VecGrid::upscale`].
+fn invalid_scale(scale: u32) -> GridError {
+    let scale = scale.min(i32::MAX as u32) as i32;
+    GridError::InvalidScale(Coord::new(scale, scale))
+}
+
+/// Returns the bounds of a `width`×`height` image, and an iterator over the
+/// colors of its pixels in row-major order, after checking that `bytes` holds
+/// exactly the image.
+fn pixels(
+    width: u32,
+    height: u32,
+    bytes: &[u8],
+) -> Result<(Rect, impl Iterator<Item = [u8; 4]> + '_), GridError> {
+    let too_large = || GridError::TooLarge { width, height };
+    let dimensions = Coord::new(
+        i32::try_from(width).map_err(|_| too_large())?,
+        i32::try_from(height).map_err(|_| too_large())?,
+    );
+    let expected = dimensions
+        .x
+        .checked_mul(dimensions.y)
+        .and_then(|area| (area as usize).checked_mul(4))
+        .ok_or_else(too_large)?;
+    if bytes.len() != expected {
+        return Err(GridError::WrongLength {
+            expected,
+            received: bytes.len(),
+        });
+    }
+    let pixels = bytes
+        .chunks_exact(4)
+        .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]]);
+    Ok((Rect::new(dimensions), pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    #[test]
+    fn renders_scaled_pixels() {
+        let grid =
+            VecGrid::with_generator(Rect::new((3, 2)).translate((5, -1)), |(x, y)| x + y > 5);
+        let bytes = grid
+            .to_rgba8(2, false, |coord, &wall| {
+                let shade = if wall { 0 } else { 255 };
+                [shade, shade, shade, coord.x as u8]
+            })
+            .unwrap();
+        assert_eq!(bytes.len(), 6 * 4 * 4);
+        let pixels = bytes
+            .chunks(4)
+            .map(|pixel| (pixel[0] == 0, pixel[3]))
+            .collect::<Vec<_>>();
+        #[rustfmt::skip]
+        let expected = vec![
+            (false, 5), (false, 5), (false, 6), (false, 6), (true, 7), (true, 7),
+            (false, 5), (false, 5), (false, 6), (false, 6), (true, 7), (true, 7),
+            (false, 5), (false, 5), (true, 6), (true, 6), (true, 7), (true, 7),
+            (false, 5), (false, 5), (true, 6), (true, 6), (true, 7), (true, 7),
+        ];
+        assert_eq!(pixels, expected);
+
+        let flipped = grid
+            .to_rgba8(2, true, |coord, &wall| {
+                let shade = if wall { 0 } else { 255 };
+                [shade, shade, shade, coord.x as u8]
+            })
+            .unwrap();
+        // Each row of cells is a block of two pixel rows.
+        let blocks = |bytes: &[u8]| {
+            bytes
+                .chunks(6 * 4 * 2)
+                .map(<[u8]>::to_vec)
+                .collect::<Vec<_>>()
+        };
+        assert!(blocks(&bytes).into_iter().rev().eq(blocks(&flipped)));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn renders_images() {
+        let grid = VecGrid::with_generator(Rect::new((3, 2)).translate((-1, 4)), |(x, y)| {
+            [x as u8, y as u8, 0, 255]
+        });
+        let image = grid.to_image(2, false, |_, &color| color).unwrap();
+        assert_eq!(image.dimensions(), (6, 4));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 4, 0, 255]);
+        assert_eq!(image.get_pixel(5, 3).0, [1, 5, 0, 255]);
+        assert_eq!(image.get_pixel(3, 1).0, [0, 4, 0, 255]);
+        assert_eq!(
+            grid.to_rgba8(2, false, |_, &color| color).unwrap(),
+            image.into_raw()
+        );
+
+        let flipped = grid.to_image(2, true, |_, &color| color).unwrap();
+        assert_eq!(flipped.get_pixel(0, 0).0, [255, 5, 0, 255]);
+        assert_eq!(flipped.get_pixel(5, 3).0, [1, 4, 0, 255]);
+        assert_eq!(
+            grid.to_rgba8(2, true, |_, &color| color).unwrap(),
+            flipped.into_raw()
+        );
+
+        assert_eq!(
+            VecGrid::<u8>::new(Rect::new((3, 0))).to_image(1, false, |_, _| [0; 4]),
+            Err(GridError::Empty)
+        );
+        assert_eq!(
+            grid.to_image(u32::MAX, false, |_, &color| color),
+            Err(GridError::InvalidScale(Coord::new(i32::MAX, i32::MAX)))
+        );
+
+        let path = std::env::temp_dir().join("tapestry_renders_images.png");
+        grid.save_image(&path, 3, false, |_, &color| color).unwrap();
+        let saved = image::open(&path).unwrap().to_rgba8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(saved, grid.to_image(3, false, |_, &color| color).unwrap());
+        assert!(matches!(
+            grid.save_image(&path, 0, false, |_, &color| color),
+            Err(SaveImageError::Grid(GridError::InvalidScale(_)))
+        ));
+    }
+
+    #[test]
+    fn rejects_empty_grids_and_zero_scale() {
+        let empty = VecGrid::<bool>::new(Rect::new((0, 4)));
+        assert_eq!(
+            empty.to_rgba8(1, false, |_, _| [0; 4]),
+            Err(GridError::Empty)
+        );
+        let single = VecGrid::filled(Rect::new((1, 1)), ());
+        assert_eq!(
+            single.to_rgba8(1, false, |_, _| [1, 2, 3, 4]),
+            Ok(vec![1, 2, 3, 4])
+        );
+        assert_eq!(
+            single.to_rgba8(0, false, |_, _| [0; 4]),
+            Err(GridError::InvalidScale(Coord::new(0, 0)))
+        );
+    }
+
+    #[test]
+    fn rejects_scales_overflowing_the_buffer() {
+        let grid = VecGrid::filled(Rect::new((3, 2)), ());
+        let overflow = Err(GridError::InvalidScale(Coord::new(i32::MAX, i32::MAX)));
+        assert_eq!(grid.to_rgba8(u32::MAX, false, |_, _| [0; 4]), overflow);
+        assert_eq!(grid.to_rgba8(u32::MAX, true, |_, _| [0; 4]), overflow);
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Tile {
+        Floor,
+        Wall,
+        Lava,
+    }
+
+    /// A 4x3 level, as an editor would export it.
+    #[rustfmt::skip]
+    const LEVEL: [u8; 48] = [
+        0, 0, 0, 255,   0, 0, 0, 255,       0, 0, 0, 255,       0, 0, 0, 255,
+        0, 0, 0, 255,   255, 255, 255, 255, 255, 0, 0, 255,     0, 0, 0, 255,
+        0, 0, 0, 255,   0, 0, 0, 255,       255, 255, 255, 255, 0, 0, 0, 255,
+    ];
+
+    fn tile(color: [u8; 4]) -> Result<Tile, [u8; 4]> {
+        match color {
+            [0, 0, 0, _] => Ok(Tile::Wall),
+            [255, 0, 0, _] => Ok(Tile::Lava),
+            [255, 255, 255, _] => Ok(Tile::Floor),
+            _ => Err(color),
+        }
+    }
+
+    #[test]
+    fn loads_levels() {
+        let level = VecGrid::try_from_rgba8(4, 3, &LEVEL, |_, color| tile(color)).unwrap();
+        assert_eq!(level.bounds, Rect::new((4, 3)));
+        assert_eq!(level.get((2, 1)), Some(&Tile::Lava));
+        assert_eq!(level.get((1, 1)), Some(&Tile::Floor));
+        assert_eq!(
+            level
+                .iter()
+                .filter(|&(_, &tile)| tile == Tile::Wall)
+                .count(),
+            9
+        );
+
+        // Round trip through the exporter.
+        let bytes = level
+            .to_rgba8(1, false, |_, tile| match tile {
+                Tile::Floor => [255, 255, 255, 255],
+                Tile::Wall => [0, 0, 0, 255],
+                Tile::Lava => [255, 0, 0, 255],
+            })
+            .unwrap();
+        assert_eq!(bytes, LEVEL.to_vec());
+
+        let mut typo = LEVEL;
+        typo[2 * 4 + 16] = 7;
+        assert_eq!(
+            VecGrid::try_from_rgba8(4, 3, &typo, |_, color| tile(color)),
+            Err(PixelGridError::Cell(CellError {
+                coord: Coord::new(2, 1),
+                error: [7, 0, 0, 255],
+            }))
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_buffers() {
+        assert_eq!(
+            VecGrid::from_rgba8(4, 3, &LEVEL[..47], |_, _| ()),
+            Err(GridError::WrongLength {
+                expected: 48,
+                received: 47
+            })
+        );
+        assert_eq!(
+            VecGrid::try_from_rgba8(3, 3, &LEVEL, |_, color| tile(color)),
+            Err(PixelGridError::Grid(GridError::WrongLength {
+                expected: 36,
+                received: 48
+            }))
+        );
+        assert_eq!(
+            VecGrid::from_rgba8(u32::MAX, 1, &LEVEL, |_, _| ()),
+            Err(GridError::TooLarge {
+                width: u32::MAX,
+                height: 1
+            })
+        );
+        assert_eq!(
+            VecGrid::from_rgba8(1 << 16, 1 << 16, &[], |_, _| ()),
+            Err(GridError::TooLarge {
+                width: 1 << 16,
+                height: 1 << 16
+            })
+        );
+    }
+
+    /// [`LEVEL`] saved by an editor as an RGB PNG, without an alpha channel.
+    #[cfg(feature = "image")]
+    #[rustfmt::skip]
+    const LEVEL_PNG: [u8; 74] = [
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d,
+        0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x03,
+        0x08, 0x02, 0x00, 0x00, 0x00, 0x3b, 0x96, 0x39, 0x91, 0x00, 0x00, 0x00,
+        0x11, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0x60, 0x40, 0x03, 0xff,
+        0x81, 0x00, 0x8d, 0x0f, 0x24, 0x01, 0x60, 0xc6, 0x06, 0xfa, 0xaf, 0x6b,
+        0xfd, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42,
+        0x60, 0x82,
+    ];
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn loads_images() {
+        let image = image::load_from_memory(&LEVEL_PNG).unwrap();
+        let level = VecGrid::try_from_image(&image, |_, color| tile(color.0)).unwrap();
+        assert_eq!(
+            level,
+            VecGrid::try_from_rgba8(4, 3, &LEVEL, |_, color| tile(color)).unwrap()
+        );
+        let alpha = VecGrid::from_image(&image, |_, color| color.0[3]).unwrap();
+        assert!(alpha.iter().all(|(_, &alpha)| alpha == 255));
+
+        let strip =
+            DynamicImage::ImageLuma8(image::GrayImage::from_raw(3, 1, vec![0, 9, 255]).unwrap());
+        let strip = VecGrid::try_from_image(&strip, |coord, color| match color.0 {
+            [0, 0, 0, 255] => Ok(Tile::Wall),
+            [255, 255, 255, 255] => Ok(Tile::Floor),
+            _ => Err(coord),
+        });
+        assert_eq!(
+            strip,
+            Err(PixelGridError::Cell(CellError {
+                coord: Coord::new(1, 0),
+                error: Coord::new(1, 0),
+            }))
+        );
+    }
+
+    #[test]
+    fn loads_single_rows() {
+        let strip = VecGrid::from_rgba8(
+            3,
+            1,
+            &[9, 0, 0, 0, 8, 0, 0, 0, 7, 0, 0, 0],
+            |coord, color| (coord, color[0]),
+        )
+        .unwrap();
+        assert_eq!(
+            strip.cells,
+            vec![
+                (Coord::new(0, 0), 9),
+                (Coord::new(1, 0), 8),
+                (Coord::new(2, 0), 7)
+            ]
+        );
+        assert_eq!(
+            VecGrid::from_rgba8(0, 5, &[], |_, _| ())
+                .unwrap()
+                .cells
+                .len(),
+            0
+        );
+    }
+}
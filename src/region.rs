@@ -0,0 +1,132 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{coord::Coord, patterns::Rect, vecgrid::VecGrid};
+
+/// Summary of a single labeled region, as returned by
+/// [`VecGrid::region_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegionStats {
+    /// The label shared by every cell of the region.
+    pub label: usize,
+    /// The number of cells in the region.
+    pub area: usize,
+    /// The mean coordinate of the region's cells, with each component
+    /// rounded to the nearest integer (halves round away from zero). For
+    /// regions that aren't convex, this may lie outside of the region.
+    pub centroid: Coord,
+    /// The smallest rect containing every cell of the region.
+    pub bounds: Rect,
+}
+
+impl VecGrid<Option<usize>> {
+    /// Computes the statistics of each region in a grid of labels, such as the
+    /// one returned by
+    /// [`connected_components`](VecGrid::connected_components), in a single
+    /// pass.
+    ///
+    /// The result is sorted by label, and labels which don't appear in the
+    /// grid are skipped. Labels don't need to be contiguous, so any `usize`
+    /// can be used as one.
+    pub fn region_stats(&self) -> Vec<RegionStats> {
+        struct Accumulator {
+            area: usize,
+            sum: (i64, i64),
+            min: Coord,
+            max: Coord,
+        }
+
+        let mut accumulators: BTreeMap<usize, Accumulator> = BTreeMap::new();
+        for (coord, &label) in self.iter() {
+            let label = match label {
+                Some(label) => label,
+                None => continue,
+            };
+            let accumulator = accumulators.entry(label).or_insert(Accumulator {
+                area: 0,
+                sum: (0, 0),
+                min: coord,
+                max: coord,
+            });
+            accumulator.area += 1;
+            accumulator.sum.0 += coord.x as i64;
+            accumulator.sum.1 += coord.y as i64;
+            accumulator.min = Coord::new(
+                accumulator.min.x.min(coord.x),
+                accumulator.min.y.min(coord.y),
+            );
+            accumulator.max = Coord::new(
+                accumulator.max.x.max(coord.x),
+                accumulator.max.y.max(coord.y),
+            );
+        }
+
+        accumulators
+            .into_iter()
+            .map(|(label, accumulator)| {
+                let mean = |sum: i64| {
+                    // Rounds to the nearest integer, with halves away from
+                    // zero, without floats so this works under `no_std`.
+                    let area = accumulator.area as i64;
+                    let rounded = (2 * sum.abs() + area) / (2 * area);
+                    (rounded * sum.signum()) as i32
+                };
+                RegionStats {
+                    label,
+                    area: accumulator.area,
+                    centroid: Coord::new(mean(accumulator.sum.0), mean(accumulator.sum.1)),
+                    bounds: Rect::with_corners(accumulator.min, accumulator.max + Coord::ONE),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn region_stats() {
+        #[rustfmt::skip]
+        let labels = VecGrid::from_raw_parts(Rect::new((4, 3)), vec![
+            Some(0), Some(0), None, Some(3),
+            Some(0), None,    None, Some(3),
+            None,    None,    None, Some(3),
+        ]).unwrap();
+        assert_eq!(
+            labels.region_stats(),
+            vec![
+                RegionStats {
+                    label: 0,
+                    area: 3,
+                    centroid: Coord::new(0, 0),
+                    bounds: Rect::with_corners((0, 0), (2, 2)),
+                },
+                RegionStats {
+                    label: 3,
+                    area: 3,
+                    centroid: Coord::new(3, 1),
+                    bounds: Rect::with_corners((3, 0), (4, 3)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sparse_labels() {
+        let labels = VecGrid::from_raw_parts(
+            Rect::new((3, 1)),
+            vec![Some(usize::MAX), Some(usize::MAX / 3), Some(usize::MAX)],
+        )
+        .unwrap();
+        let stats = labels.region_stats();
+        assert_eq!(
+            stats
+                .iter()
+                .map(|stats| (stats.label, stats.area))
+                .collect::<Vec<_>>(),
+            vec![(usize::MAX / 3, 1), (usize::MAX, 2)]
+        );
+        assert_eq!(stats[1].bounds, Rect::new((3, 1)));
+    }
+}
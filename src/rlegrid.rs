@@ -0,0 +1,295 @@
+use alloc::{vec, vec::Vec};
+use core::{iter, ops::Range};
+
+use crate::{
+    coord::Coord,
+    grid::{Grid, IterCell},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+/// A grid storing each row as runs of equal cells, for huge layers made of
+/// long horizontal stretches of the same value, such as fog of war.
+///
+/// Writes split runs as needed, and merge neighboring runs as soon as they
+/// hold equal values, so each row always has as few runs as its contents
+/// allow. Reads find the run holding a cell with a binary search over its
+/// row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RleGrid<T> {
+    /// The runs of each row from top to bottom, ordered by their first
+    /// column. The first run of a row starts at the left edge of the bounds,
+    /// and each run ends where the next one starts.
+    rows: Vec<Vec<Run<T>>>,
+    bounds: Rect,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Run<T> {
+    start: i32,
+    value: T,
+}
+
+impl<T> Grid<T> for RleGrid<T> {
+    fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        let coord = coord.into();
+        if !self.bounds.contains(coord) {
+            return None;
+        }
+        let row = &self.rows[(coord.y - self.bounds.top) as usize];
+        Some(&row[run_index(row, coord.x)].value)
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Returns an iterator over every cell in row-major order, walking the
+    /// runs rather than looking up each cell.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+    {
+        self.iter_runs()
+            .flat_map(|(y, xs, value)| xs.map(move |x| (Coord::new(x, y), value)))
+    }
+}
+
+impl<T> RleGrid<T> {
+    /// Returns an iterator over the runs of every row, from top to bottom and
+    /// left to right, as the row, the columns the run covers and its value.
+    /// Together the runs cover every cell exactly once.
+    pub fn iter_runs(&self) -> impl Iterator<Item = (i32, Range<i32>, &T)> {
+        let (top, right) = (self.bounds.top, self.bounds.right);
+        self.rows.iter().zip(top..).flat_map(move |(row, y)| {
+            row.iter().enumerate().map(move |(index, run)| {
+                let end = row.get(index + 1).map_or(right, |next| next.start);
+                (y, run.start..end, &run.value)
+            })
+        })
+    }
+
+    /// Returns the total number of runs stored, which is what the grid's
+    /// memory use grows with.
+    pub fn run_count(&self) -> usize {
+        self.rows.iter().map(Vec::len).sum()
+    }
+}
+
+impl<T: Eq + Clone> RleGrid<T> {
+    /// Constructs a grid with every cell set to `value`.
+    pub fn filled(bounds: Rect, value: T) -> Self {
+        let height = bounds.height().max(0) as usize;
+        let rows = if bounds.width() > 0 {
+            let run = Run {
+                start: bounds.left,
+                value,
+            };
+            vec![vec![run]; height]
+        } else {
+            vec![Vec::new(); height]
+        };
+        Self { rows, bounds }
+    }
+
+    /// Builds a grid holding the same cells as `grid`.
+    pub fn from_vec_grid(grid: &VecGrid<T>) -> Self {
+        let left = grid.bounds.left;
+        let rows = grid
+            .rows()
+            .map(|cells| {
+                let mut runs: Vec<Run<T>> = Vec::new();
+                for (x, cell) in (left..).zip(cells) {
+                    if runs.last().is_none_or(|run| run.value != *cell) {
+                        runs.push(Run {
+                            start: x,
+                            value: cell.clone(),
+                        });
+                    }
+                }
+                runs
+            })
+            .collect();
+        Self {
+            rows,
+            bounds: grid.bounds,
+        }
+    }
+
+    pub fn to_vec_grid(&self) -> VecGrid<T> {
+        let cells = self
+            .iter_runs()
+            .flat_map(|(_, xs, value)| iter::repeat_n(value, xs.len()))
+            .cloned()
+            .collect();
+        VecGrid::from_raw_parts(self.bounds, cells).unwrap()
+    }
+
+    /// Sets the cell at `coord`, returning false if it's out of bounds.
+    pub fn set<C: Into<Coord>>(&mut self, coord: C, value: T) -> bool {
+        let coord = coord.into();
+        if !self.bounds.contains(coord) {
+            return false;
+        }
+        self.fill_span(coord.y, coord.x..coord.x + 1, value);
+        true
+    }
+
+    /// Sets every cell covered by `rect` to `value`. Parts of `rect` outside of
+    /// the grid are ignored.
+    ///
+    /// Each row of `rect` is written as a single run, so the cost depends on
+    /// the number of runs it covers rather than on its width.
+    pub fn fill_region(&mut self, rect: Rect, value: T) {
+        let rect = match rect.intersection(&self.bounds) {
+            Some(rect) if !rect.is_empty() => rect,
+            _ => return,
+        };
+        for y in rect.y_range() {
+            self.fill_span(y, rect.x_range(), value.clone());
+        }
+    }
+
+    /// Replaces the cells of row `y` covered by `xs`, which must be a
+    /// non-empty range within the bounds, with one run of `value`.
+    fn fill_span(&mut self, y: i32, xs: Range<i32>, value: T) {
+        let right = self.bounds.right;
+        let row = &mut self.rows[(y - self.bounds.top) as usize];
+        let first = run_index(row, xs.start);
+        let last = run_index(row, xs.end - 1);
+
+        // Whatever the last run covered past the span is left as it was.
+        let last_end = row.get(last + 1).map_or(right, |next| next.start);
+        let rest = if xs.end < last_end {
+            Some(Run {
+                start: xs.end,
+                value: row[last].value.clone(),
+            })
+        } else {
+            None
+        };
+        // So is the part of the first run before the span.
+        let start = if row[first].start < xs.start {
+            first + 1
+        } else {
+            first
+        };
+        let span = Run {
+            start: xs.start,
+            value,
+        };
+        let inserted = 1 + rest.is_some() as usize;
+        row.splice(start..=last, iter::once(span).chain(rest));
+
+        // Only the new runs and their neighbors can have become equal.
+        let mut index = start.max(1);
+        let mut end = (start + inserted + 1).min(row.len());
+        while index < end {
+            if row[index].value == row[index - 1].value {
+                row.remove(index);
+                end -= 1;
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+/// Returns the index of the run of `row` covering column `x`, which must be
+/// inside the bounds.
+fn run_index<T>(row: &[Run<T>], x: i32) -> usize {
+    row.partition_point(|run| run.start <= x) - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridMut;
+
+    fn runs<T: Clone>(grid: &RleGrid<T>) -> Vec<(i32, Range<i32>, T)> {
+        grid.iter_runs()
+            .map(|(y, xs, value)| (y, xs, value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn splits_and_merges_runs() {
+        let mut grid = RleGrid::filled(Rect::new((10, 2)).translate((-5, 3)), 0);
+        assert_eq!(grid.run_count(), 2);
+
+        // Splitting a run in three.
+        assert!(grid.set((0, 3), 1));
+        assert_eq!(
+            runs(&grid)[..3],
+            [(3, -5..0, 0), (3, 0..1, 1), (3, 1..5, 0)]
+        );
+        // Growing a run, shrinking its neighbor.
+        grid.set((1, 3), 1);
+        assert_eq!(
+            runs(&grid)[..3],
+            [(3, -5..0, 0), (3, 0..2, 1), (3, 2..5, 0)]
+        );
+        // Writes at the edges of the row.
+        grid.set((-5, 3), 2);
+        grid.set((4, 3), 2);
+        assert_eq!(
+            runs(&grid)[..5],
+            [
+                (3, -5..-4, 2),
+                (3, -4..0, 0),
+                (3, 0..2, 1),
+                (3, 2..4, 0),
+                (3, 4..5, 2),
+            ]
+        );
+        // Spans covering several runs, merging with both neighbors.
+        grid.fill_region(Rect::new((6, 1)).translate((-4, 3)), 2);
+        assert_eq!(runs(&grid)[..2], [(3, -5..2, 2), (3, 2..4, 0)]);
+        grid.fill_region(Rect::new((2, 1)).translate((2, 3)), 2);
+        assert_eq!(runs(&grid)[..1], [(3, -5..5, 2)]);
+        // Writing a value that's already there changes nothing.
+        grid.set((0, 4), 0);
+        assert_eq!(grid.run_count(), 2);
+        assert!(!grid.set((5, 3), 1));
+        assert_eq!(grid.get((5, 3)), None);
+        assert_eq!(grid.get((4, 4)), Some(&0));
+    }
+
+    #[test]
+    fn matches_vec_grid_writes() {
+        let bounds = Rect::new((13, 5)).translate((2, -2));
+        let mut expected = VecGrid::with_generator(bounds, |(x, y)| (x / 3 + y) % 3);
+        let mut grid = RleGrid::from_vec_grid(&expected);
+        assert_eq!(grid.to_vec_grid(), expected);
+
+        let mut state = 7u32;
+        for _ in 0..300 {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let x = 1 + (state >> 8) as i32 % 15;
+            let y = -3 + (state >> 16) as i32 % 7;
+            let value = (state >> 24) as i32 % 3;
+            if state >> 30 == 0 {
+                let rect = Rect::new(((state >> 4) as i32 % 6, 2)).translate((x, y));
+                grid.fill_region(rect, value);
+                for coord in rect.iter() {
+                    expected.set(coord, value);
+                }
+            } else {
+                assert_eq!(grid.set((x, y), value), expected.set((x, y), value));
+            }
+            assert!(grid.iter().eq(expected.iter()), "{:?}", runs(&grid));
+        }
+        assert_eq!(grid, RleGrid::from_vec_grid(&expected));
+    }
+
+    #[test]
+    fn empty_grids() {
+        let grid = RleGrid::filled(Rect::new((0, 3)), 'x');
+        assert_eq!(grid.run_count(), 0);
+        assert_eq!(grid.iter().count(), 0);
+        assert_eq!(grid.to_vec_grid(), VecGrid::filled(grid.bounds, 'x'));
+        let mut grid = RleGrid::from_vec_grid(&VecGrid::filled(Rect::new((4, 0)), 'x'));
+        grid.fill_region(Rect::new((4, 4)), 'y');
+        assert_eq!(grid.run_count(), 0);
+    }
+}
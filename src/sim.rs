@@ -0,0 +1,118 @@
+use core::mem;
+
+use crate::{coord::Coord, patterns::Rect, vecgrid::VecGrid};
+
+/// A double-buffered grid for stepping simulations without allocating.
+///
+/// The front buffer holds the current state. Each [`step`](SimGrid::step)
+/// computes the next state into the back buffer from read access to the
+/// front, then swaps the two, leaving the previous state in the back buffer
+/// (handy for interpolating between states when rendering).
+///
+/// Both buffers always have the same bounds. Changing the bounds of the front
+/// buffer through [`front_mut`](SimGrid::front_mut) isn't supported.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimGrid<T> {
+    front: VecGrid<T>,
+    back: VecGrid<T>,
+}
+
+impl<T> SimGrid<T> {
+    /// Constructs a simulation starting from `initial`. The back buffer starts
+    /// out as a copy of it.
+    pub fn new(initial: VecGrid<T>) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            back: initial.clone(),
+            front: initial,
+        }
+    }
+
+    /// Returns the current state.
+    pub fn front(&self) -> &VecGrid<T> {
+        &self.front
+    }
+
+    pub fn front_mut(&mut self) -> &mut VecGrid<T> {
+        &mut self.front
+    }
+
+    /// Returns the state before the last [`step`](SimGrid::step).
+    pub fn back(&self) -> &VecGrid<T> {
+        &self.back
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.front.bounds
+    }
+
+    pub fn into_front(self) -> VecGrid<T> {
+        self.front
+    }
+
+    /// Advances the simulation by one step, calling `rule` for each cell with
+    /// its coordinate and the entire current state to get its next value.
+    pub fn step<F>(&mut self, mut rule: F)
+    where
+        F: FnMut(Coord, &VecGrid<T>) -> T,
+    {
+        for (coord, next) in self.back.iter_mut() {
+            *next = rule(coord, &self.front);
+        }
+        mem::swap(&mut self.front, &mut self.back);
+    }
+
+    /// Like [`step`](SimGrid::step), but only updates the cells covered by
+    /// `rect`; the rest keep their current values.
+    ///
+    /// The buffers aren't swapped, since the back buffer only holds the next
+    /// values of the region. Instead, the new values are copied into the front
+    /// buffer, so afterwards both buffers hold them inside the region.
+    pub fn step_region<F>(&mut self, rect: Rect, mut rule: F)
+    where
+        T: Clone,
+        F: FnMut(Coord, &VecGrid<T>) -> T,
+    {
+        for (coord, next) in self.back.iter_rect_mut(rect) {
+            *next = rule(coord, &self.front);
+        }
+        let updates = self.back.iter_rect(rect);
+        for ((_, cell), (_, value)) in self.front.iter_rect_mut(rect).zip(updates) {
+            cell.clone_from(value);
+        }
+    }
+
+    /// Swaps the front and back buffers.
+    pub fn swap_buffers(&mut self) {
+        mem::swap(&mut self.front, &mut self.back);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    #[test]
+    fn step() {
+        let mut sim = SimGrid::new(VecGrid::with_generator(Rect::new((4, 1)), |(x, _)| x));
+        let front = sim.front().cells.as_ptr();
+        let shift_left =
+            |coord: Coord, grid: &VecGrid<i32>| *grid.get(coord + Coord::EAST).unwrap_or(&-1);
+        sim.step(shift_left);
+        assert_eq!(sim.front().cells, vec![1, 2, 3, -1]);
+        assert_eq!(sim.back().cells, vec![0, 1, 2, 3]);
+        sim.step(shift_left);
+        assert_eq!(sim.front().cells, vec![2, 3, -1, -1]);
+        // The buffers are reused rather than reallocated.
+        assert_eq!(sim.front().cells.as_ptr(), front);
+
+        sim.step_region(Rect::with_corners((1, 0), (3, 1)), |coord, grid| {
+            grid.get(coord).unwrap() * 10
+        });
+        assert_eq!(sim.front().cells, vec![2, 30, -10, -1]);
+        assert_eq!(sim.back().cells[1..3], [30, -10]);
+    }
+}
@@ -0,0 +1,373 @@
+use std::{
+    collections::{HashMap, HashSet},
+    iter::FromIterator,
+};
+
+use crate::{
+    coord::Coord,
+    grid::{Grid, GridError, GridMut, IterCell, IterCellMut},
+    patterns::{Connectivity, Rect},
+    vecgrid::VecGrid,
+};
+
+/// A grid that only stores its occupied cells, for worlds that are mostly
+/// empty or span coordinates far too large for a [`VecGrid`].
+///
+/// There are no bounds or origin; every coordinate, negative or not, is either
+/// occupied or empty. Empty cells behave like the out of bounds cells of a
+/// [`VecGrid`], except that [`set`](GridMut::set) and
+/// [`replace`](GridMut::replace) occupy them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Coord, T>,
+}
+
+impl<T> Grid<T> for SparseGrid<T> {
+    fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        self.cells.get(&coord.into())
+    }
+
+    /// Returns the [`bounding_box`](SparseGrid::bounding_box) of the occupied
+    /// cells, or an empty rect at the origin if there are none.
+    fn bounds(&self) -> Rect {
+        self.bounding_box().unwrap_or_else(|| Rect::new((0, 0)))
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+    {
+        SparseGrid::iter(self)
+    }
+
+    // The neighbors are looked up directly, rather than through
+    // `selection_iter`, since every empty neighbor would otherwise compute
+    // the bounds for its error.
+    fn neighbors<'a, C>(&'a self, coord: C) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+        C: Into<Coord>,
+    {
+        Connectivity::Eight
+            .neighbors(coord)
+            .filter_map(move |coord| Some((coord, self.cells.get(&coord)?)))
+    }
+
+    fn ortho_neighbors<'a, C>(&'a self, coord: C) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+        C: Into<Coord>,
+    {
+        Connectivity::Four
+            .neighbors(coord)
+            .filter_map(move |coord| Some((coord, self.cells.get(&coord)?)))
+    }
+}
+
+impl<T> GridMut<T> for SparseGrid<T> {
+    fn get_mut<C: Into<Coord>>(&mut self, coord: C) -> Option<&mut T> {
+        self.cells.get_mut(&coord.into())
+    }
+
+    /// Copies `src` into `dest`, returning false (and leaving `dest` alone) if
+    /// `src` is empty.
+    fn copy<C1, C2>(&mut self, src: C1, dest: C2) -> bool
+    where
+        T: Copy,
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        match self.get_copied(src) {
+            Some(value) => {
+                self.cells.insert(dest.into(), value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Swaps the contents of two cells, returning false if either is empty.
+    fn swap<C1, C2>(&mut self, coord1: C1, coord2: C2) -> bool
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        let coord1 = coord1.into();
+        let coord2 = coord2.into();
+        if !(self.cells.contains_key(&coord1) && self.cells.contains_key(&coord2)) {
+            return false;
+        }
+        if coord1 != coord2 {
+            let value1 = self.cells.remove(&coord1).unwrap();
+            let value2 = self.cells.insert(coord2, value1).unwrap();
+            self.cells.insert(coord1, value2);
+        }
+        true
+    }
+
+    /// Moves the contents of `src` into `dest`, leaving `src` empty. Returns
+    /// `None` without changing anything if either cell is empty.
+    fn mov(&mut self, src: Coord, dest: Coord) -> Option<T>
+    where
+        T: Default,
+    {
+        if !(self.cells.contains_key(&src) && self.cells.contains_key(&dest)) {
+            return None;
+        }
+        let src_value = self.take(src).unwrap();
+        self.replace(dest, src_value)
+    }
+
+    /// Stores `value` at `coord`, occupying it if it was empty. Always returns
+    /// true.
+    fn set<C: Into<Coord>>(&mut self, coord: C, value: T) -> bool {
+        self.cells.insert(coord.into(), value);
+        true
+    }
+
+    /// Stores `value` at `coord`, returning the previous contents, or `None`
+    /// if it was empty.
+    fn replace<C: Into<Coord>>(&mut self, coord: C, value: T) -> Option<T> {
+        self.cells.insert(coord.into(), value)
+    }
+
+    /// Removes the contents of `coord`, leaving it empty. See
+    /// [`remove`](SparseGrid::remove).
+    fn take<C: Into<Coord>>(&mut self, coord: C) -> Option<T>
+    where
+        T: Default,
+    {
+        self.remove(coord)
+    }
+}
+
+impl<T> SparseGrid<T> {
+    /// Constructs a grid with no occupied cells.
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Constructs a sparse grid from the cells of `grid` that don't hold
+    /// `T::default()`.
+    pub fn from_vec_grid(grid: VecGrid<T>) -> Self
+    where
+        T: Default + PartialEq,
+    {
+        let default = T::default();
+        grid.into_iter()
+            .filter(|(_, cell)| *cell != default)
+            .collect()
+    }
+
+    /// Copies the cells inside `bounds` into a dense grid, with clones of
+    /// `fill` standing in for the empty ones.
+    pub fn to_vec_grid(&self, bounds: Rect, fill: T) -> VecGrid<T>
+    where
+        T: Clone,
+    {
+        VecGrid::with_generator(bounds, |coord: Coord| {
+            self.cells.get(&coord).unwrap_or(&fill).clone()
+        })
+    }
+
+    /// Returns the number of occupied cells.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns true if no cells are occupied.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Empties the cell at `coord`, returning its previous contents.
+    pub fn remove<C: Into<Coord>>(&mut self, coord: C) -> Option<T> {
+        self.cells.remove(&coord.into())
+    }
+
+    /// Empties every cell.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Returns the smallest rect containing every occupied cell, or `None` if
+    /// the grid is empty.
+    pub fn bounding_box(&self) -> Option<Rect> {
+        let mut coords = self.cells.keys();
+        let first = *coords.next()?;
+        let (min, max) = coords.fold((first, first), |(min, max), coord| {
+            (
+                Coord::new(min.x.min(coord.x), min.y.min(coord.y)),
+                Coord::new(max.x.max(coord.x), max.y.max(coord.y)),
+            )
+        });
+        Some(Rect::with_corners(min, max + Coord::ONE))
+    }
+
+    /// Returns an iterator over the occupied cells, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = IterCell<'_, T>> {
+        self.cells.iter().map(|(&coord, cell)| (coord, cell))
+    }
+
+    /// Returns a mutable iterator over the occupied cells, in no particular
+    /// order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        self.cells.iter_mut().map(|(&coord, cell)| (coord, cell))
+    }
+
+    /// Returns a mutable iterator over the cells specified by the coords
+    /// iterator, following the same rules as
+    /// [`VecGrid::selection_iter_mut`]: each coord yields one item, empty cells
+    /// yield a [`GridError::OutOfBounds`], and repeat visits to an occupied
+    /// cell yield a [`GridError::AlreadyVisited`].
+    ///
+    /// Setting up the iterator takes time proportional to the number of
+    /// occupied cells.
+    pub fn selection_iter_mut<I>(
+        &mut self,
+        coords: I,
+    ) -> impl Iterator<Item = Result<IterCellMut<'_, T>, GridError>>
+    where
+        I: IntoIterator,
+        I::Item: Into<Coord>,
+    {
+        let bounds = self.bounds();
+        // Handing each cell out of a map of references keeps the borrows
+        // disjoint without any unsafe code.
+        let mut unvisited = self
+            .cells
+            .iter_mut()
+            .map(|(&coord, cell)| (coord, cell))
+            .collect::<HashMap<_, _>>();
+        let mut visited = HashSet::new();
+        coords.into_iter().map(move |coord| {
+            let coord = coord.into();
+            match unvisited.remove(&coord) {
+                Some(cell) => {
+                    visited.insert(coord);
+                    Ok((coord, cell))
+                }
+                None if visited.contains(&coord) => Err(GridError::AlreadyVisited(coord)),
+                None => Err(GridError::OutOfBounds { coord, bounds }),
+            }
+        })
+    }
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<(Coord, T)> for SparseGrid<T> {
+    /// Collects cells into a grid. Later cells overwrite earlier ones with the
+    /// same coord.
+    fn from_iter<I: IntoIterator<Item = (Coord, T)>>(iter: I) -> Self {
+        Self {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> Extend<(Coord, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (Coord, T)>>(&mut self, iter: I) {
+        self.cells.extend(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_set_remove() {
+        let mut grid = SparseGrid::new();
+        assert!(grid.set((-250_000, 400_000), 'a'));
+        assert!(grid.set((3, -7), 'b'));
+        assert_eq!(grid.get((-250_000, 400_000)), Some(&'a'));
+        assert_eq!(grid.get((0, 0)), None);
+        assert_eq!(grid.replace((3, -7), 'c'), Some('b'));
+        *grid.get_mut((3, -7)).unwrap() = 'd';
+        assert_eq!(grid.remove((3, -7)), Some('d'));
+        assert_eq!(grid.remove((3, -7)), None);
+        assert_eq!(grid.len(), 1);
+        assert!(grid.contains((-250_000, 400_000)));
+
+        assert!(!grid.swap((-250_000, 400_000), (1, 1)));
+        grid.set((1, 1), 'e');
+        assert!(grid.swap((-250_000, 400_000), (1, 1)));
+        assert_eq!(grid.get((1, 1)), Some(&'a'));
+        assert!(grid.copy((1, 1), (9, 9)));
+        assert!(!grid.copy((2, 2), (9, 9)));
+        assert_eq!(grid.get((9, 9)), Some(&'a'));
+    }
+
+    #[test]
+    fn bounding_box() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(grid.bounding_box(), None);
+        grid.set((-5, 2), ());
+        assert_eq!(
+            grid.bounding_box(),
+            Some(Rect::with_corners((-5, 2), (-4, 3)))
+        );
+        grid.set((100_000, -30), ());
+        grid.set((0, 70), ());
+        assert_eq!(
+            grid.bounding_box(),
+            Some(Rect::with_corners((-5, -30), (100_001, 71)))
+        );
+    }
+
+    #[test]
+    fn selection_iter_mut() {
+        let mut grid = vec![((0, 0).into(), 1), ((-1, 0).into(), 2)]
+            .into_iter()
+            .collect::<SparseGrid<_>>();
+        let coords = vec![
+            Coord::new(0, 0),
+            Coord::new(5, 5),
+            Coord::new(0, 0),
+            Coord::new(-1, 0),
+        ];
+        let results = grid
+            .selection_iter_mut(coords)
+            .map(|result| {
+                result.map(|(coord, cell)| {
+                    *cell *= 10;
+                    coord
+                })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![
+                Ok(Coord::new(0, 0)),
+                Err(GridError::OutOfBounds {
+                    coord: Coord::new(5, 5),
+                    bounds: grid.bounds()
+                }),
+                Err(GridError::AlreadyVisited(Coord::new(0, 0))),
+                Ok(Coord::new(-1, 0)),
+            ]
+        );
+        assert_eq!(grid.get((0, 0)), Some(&10));
+        assert_eq!(grid.get((-1, 0)), Some(&20));
+    }
+
+    #[test]
+    fn vec_grid_round_trip() {
+        let bounds = Rect::new((3, 2)).translate((-1, -1));
+        let dense = VecGrid::with_generator(bounds, |(x, y)| if x == y { x + 2 } else { 0 });
+        let sparse = SparseGrid::from_vec_grid(dense.clone());
+        assert_eq!(sparse.len(), 2);
+        assert_eq!(sparse.get((-1, -1)), Some(&1));
+        assert_eq!(sparse.to_vec_grid(bounds, 0), dense);
+
+        let window = sparse.to_vec_grid(Rect::new((2, 1)), -1);
+        assert_eq!(window.cells, vec![2, -1]);
+    }
+}
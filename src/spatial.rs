@@ -0,0 +1,352 @@
+use std::{boxed::Box, collections::HashMap, hash::Hash, vec::Vec};
+
+use crate::{
+    coord::Coord,
+    grid::{Grid, GridError, GridMut},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+/// Where a [`SpatialIndex`] keeps the keys standing on each cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Buckets<K> {
+    Dense(VecGrid<Vec<K>>),
+    Sparse(HashMap<Coord, Vec<K>>),
+}
+
+impl<K: Eq> Buckets<K> {
+    fn get(&self, coord: Coord) -> Option<&Vec<K>> {
+        match self {
+            Buckets::Dense(grid) => grid.get(coord),
+            Buckets::Sparse(map) => map.get(&coord),
+        }
+    }
+
+    /// Checks that keys can be placed at `coord`.
+    fn check(&self, coord: Coord) -> Result<(), GridError> {
+        match self {
+            Buckets::Dense(grid) if !grid.contains(coord) => Err(GridError::OutOfBounds {
+                coord,
+                bounds: grid.bounds,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Adds `key` to the bucket of `coord`, which must have been checked.
+    fn push(&mut self, coord: Coord, key: K) {
+        match self {
+            Buckets::Dense(grid) => grid.get_mut(coord).unwrap().push(key),
+            Buckets::Sparse(map) => map.entry(coord).or_default().push(key),
+        }
+    }
+
+    /// Removes `key` from the bucket of `coord`, where it must be. Empty
+    /// sparse buckets are dropped, so memory use follows the number of keys
+    /// rather than the number of cells they've visited.
+    fn remove(&mut self, coord: Coord, key: &K) {
+        let bucket = match self {
+            Buckets::Dense(grid) => grid.get_mut(coord),
+            Buckets::Sparse(map) => map.get_mut(&coord),
+        }
+        .unwrap();
+        let index = bucket.iter().position(|other| other == key).unwrap();
+        bucket.remove(index);
+        if let Buckets::Sparse(map) = self {
+            if map[&coord].is_empty() {
+                map.remove(&coord);
+            }
+        }
+    }
+}
+
+/// An index of keys by position, such as entity ids, kept consistent in both
+/// directions: from each key to its cell, and from each cell to the keys on
+/// it.
+///
+/// A [`dense`](SpatialIndex::dense) index keeps a bucket for every cell of
+/// fixed bounds, for bounded maps. A [`sparse`](SpatialIndex::sparse) one
+/// only stores the cells that hold keys, and places no limits on their
+/// coordinates. Both behave the same otherwise. Keys on the same cell are
+/// kept in the order they arrived there.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex<K> {
+    positions: HashMap<K, Coord>,
+    buckets: Buckets<K>,
+}
+
+impl<K: Eq + Hash> PartialEq for SpatialIndex<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.positions == other.positions && self.buckets == other.buckets
+    }
+}
+
+impl<K: Eq + Hash> Eq for SpatialIndex<K> {}
+
+impl<K: Eq + Hash + Clone> SpatialIndex<K> {
+    /// Constructs an empty index accepting keys within `bounds`.
+    pub fn dense(bounds: Rect) -> Self {
+        Self {
+            positions: HashMap::new(),
+            buckets: Buckets::Dense(VecGrid::new(bounds)),
+        }
+    }
+
+    /// Constructs an empty index accepting keys at any coordinate.
+    pub fn sparse() -> Self {
+        Self {
+            positions: HashMap::new(),
+            buckets: Buckets::Sparse(HashMap::new()),
+        }
+    }
+
+    /// Places `key` at `coord`, returning its previous position if it was
+    /// already in the index, in which case it's moved.
+    ///
+    /// Returns [`GridError::OutOfBounds`] without changing anything if the
+    /// index is dense and `coord` is outside of its bounds.
+    pub fn insert<C: Into<Coord>>(&mut self, key: K, coord: C) -> Result<Option<Coord>, GridError> {
+        let coord = coord.into();
+        self.buckets.check(coord)?;
+        let previous = self.positions.insert(key.clone(), coord);
+        if let Some(previous) = previous {
+            self.buckets.remove(previous, &key);
+        }
+        self.buckets.push(coord, key);
+        Ok(previous)
+    }
+
+    /// Moves `key` to `coord`, returning its previous position, or `None`
+    /// without inserting it if it isn't in the index.
+    ///
+    /// Returns [`GridError::OutOfBounds`] without changing anything if the
+    /// index is dense and `coord` is outside of its bounds.
+    pub fn move_to<C: Into<Coord>>(
+        &mut self,
+        key: &K,
+        coord: C,
+    ) -> Result<Option<Coord>, GridError> {
+        if !self.positions.contains_key(key) {
+            return Ok(None);
+        }
+        self.insert(key.clone(), coord)
+    }
+
+    /// Removes `key` from the index, returning its position.
+    pub fn remove(&mut self, key: &K) -> Option<Coord> {
+        let coord = self.positions.remove(key)?;
+        self.buckets.remove(coord, key);
+        Some(coord)
+    }
+
+    pub fn position_of(&self, key: &K) -> Option<Coord> {
+        self.positions.get(key).copied()
+    }
+
+    /// Returns an iterator over the keys at `coord`, in the order they
+    /// arrived there.
+    pub fn at<C: Into<Coord>>(&self, coord: C) -> impl Iterator<Item = &K> {
+        self.buckets.get(coord.into()).into_iter().flatten()
+    }
+
+    /// Returns an iterator over the keys covered by `rect` along with their
+    /// positions, in no particular order.
+    ///
+    /// Whichever is smaller of the cells of `rect` and the keys of the index
+    /// is scanned, so a huge rect over a sparse index stays cheap.
+    pub fn in_rect(&self, rect: Rect) -> Box<dyn Iterator<Item = (Coord, &K)> + '_> {
+        let area = rect.width().max(0) as u64 * rect.height().max(0) as u64;
+        match &self.buckets {
+            Buckets::Dense(grid) => Box::new(
+                grid.iter_rect(rect)
+                    .flat_map(|(coord, keys)| keys.iter().map(move |key| (coord, key))),
+            ),
+            Buckets::Sparse(map) if area <= self.positions.len() as u64 => {
+                Box::new(rect.iter().flat_map(move |coord| {
+                    map.get(&coord)
+                        .into_iter()
+                        .flatten()
+                        .map(move |key| (coord, key))
+                }))
+            }
+            Buckets::Sparse(_) => Box::new(
+                self.positions
+                    .iter()
+                    .filter(move |(_, &coord)| rect.contains(coord))
+                    .map(|(key, &coord)| (coord, key)),
+            ),
+        }
+    }
+
+    /// Returns an iterator over the keys within `radius` of `center` (by
+    /// euclidean distance, as in [`Grid::field_of_view`]) along with their
+    /// positions, in no particular order.
+    pub fn within_radius<C: Into<Coord>>(
+        &self,
+        center: C,
+        radius: i32,
+    ) -> impl Iterator<Item = (Coord, &K)> {
+        let center = center.into();
+        let rect = if radius < 0 {
+            Rect::new((0, 0))
+        } else {
+            Rect::new((radius * 2 + 1, radius * 2 + 1))
+                .translate(center - Coord::new(radius, radius))
+        };
+        let radius = radius as i64;
+        self.in_rect(rect).filter(move |&(coord, _)| {
+            let delta = coord - center;
+            (delta.x as i64).pow(2) + (delta.y as i64).pow(2) <= radius * radius
+        })
+    }
+}
+
+impl<K> SpatialIndex<K> {
+    /// Returns the number of keys in the index.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns an iterator over every key and its position, in no particular
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, Coord)> {
+        self.positions.iter().map(|(key, &coord)| (key, coord))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted<'a>(keys: impl Iterator<Item = (Coord, &'a char)>) -> Vec<(i32, i32, char)> {
+        let mut keys = keys
+            .map(|(coord, &key)| (coord.x, coord.y, key))
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
+    }
+
+    fn indexes() -> [SpatialIndex<char>; 2] {
+        [
+            SpatialIndex::dense(Rect::new((8, 6)).translate((-2, -1))),
+            SpatialIndex::sparse(),
+        ]
+    }
+
+    #[test]
+    fn keeps_both_directions() {
+        for index in indexes().iter_mut() {
+            assert_eq!(index.insert('a', (0, 0)), Ok(None));
+            assert_eq!(index.insert('b', (0, 0)), Ok(None));
+            assert_eq!(index.insert('c', (3, 2)), Ok(None));
+            assert_eq!(index.at((0, 0)).collect::<String>(), "ab");
+            assert_eq!(index.position_of(&'c'), Some(Coord::new(3, 2)));
+
+            assert_eq!(index.move_to(&'a', (3, 2)), Ok(Some(Coord::new(0, 0))));
+            assert_eq!(index.at((0, 0)).collect::<String>(), "b");
+            assert_eq!(index.at((3, 2)).collect::<String>(), "ca");
+            assert_eq!(index.move_to(&'z', (1, 1)), Ok(None));
+            assert_eq!(index.position_of(&'z'), None);
+            // Inserting a key again moves it.
+            assert_eq!(index.insert('b', (3, 2)), Ok(Some(Coord::new(0, 0))));
+            assert_eq!(index.at((0, 0)).count(), 0);
+
+            assert_eq!(index.remove(&'c'), Some(Coord::new(3, 2)));
+            assert_eq!(index.remove(&'c'), None);
+            assert_eq!(index.at((3, 2)).collect::<String>(), "ab");
+            assert_eq!(index.len(), 2);
+        }
+    }
+
+    #[test]
+    fn dense_bounds() {
+        let [mut dense, mut sparse] = indexes();
+        let error = || GridError::OutOfBounds {
+            coord: Coord::new(6, 0),
+            bounds: Rect::new((8, 6)).translate((-2, -1)),
+        };
+        assert_eq!(dense.insert('a', (6, 0)), Err(error()));
+        assert!(dense.is_empty());
+        dense.insert('a', (5, 4)).unwrap();
+        assert_eq!(dense.move_to(&'a', (6, 0)), Err(error()));
+        assert_eq!(dense.position_of(&'a'), Some(Coord::new(5, 4)));
+
+        assert_eq!(sparse.insert('a', (-1000, 1000)), Ok(None));
+        sparse.remove(&'a');
+        assert_eq!(sparse, SpatialIndex::sparse());
+    }
+
+    #[test]
+    fn range_queries() {
+        for index in indexes().iter_mut() {
+            for (key, coord) in "abcde"
+                .chars()
+                .zip([(0, 0), (1, 0), (2, 2), (-2, 3), (0, 0)].iter())
+            {
+                index.insert(key, *coord).unwrap();
+            }
+            let rect = Rect::new((3, 3)).translate((0, 0));
+            let expected = vec![(0, 0, 'a'), (0, 0, 'e'), (1, 0, 'b'), (2, 2, 'c')];
+            assert_eq!(sorted(index.in_rect(rect)), expected);
+            // Larger than the number of keys, so the sparse index scans the
+            // keys instead of the cells.
+            let rect = Rect::new((100, 100)).translate((-50, -50));
+            assert_eq!(sorted(index.in_rect(rect)).len(), 5);
+            assert_eq!(index.in_rect(Rect::new((0, 3))).count(), 0);
+
+            assert_eq!(sorted(index.within_radius((1, 1), 1)), vec![(1, 0, 'b')]);
+            assert_eq!(sorted(index.within_radius((1, 1), 2)).len(), 4);
+            assert_eq!(sorted(index.within_radius((0, 0), 0)).len(), 2);
+            assert_eq!(index.within_radius((0, 0), -1).count(), 0);
+        }
+    }
+
+    #[test]
+    fn stays_consistent() {
+        for index in indexes().iter_mut() {
+            let mut expected: HashMap<char, Coord> = HashMap::new();
+            let mut state = 11u32;
+            for _ in 0..500 {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                let key = char::from(b'a' + (state >> 8) as u8 % 12);
+                let coord =
+                    Coord::new(-2 + (state >> 12) as i32 % 8, -1 + (state >> 16) as i32 % 6);
+                match state >> 29 {
+                    0 | 1 => assert_eq!(index.remove(&key), expected.remove(&key)),
+                    2 | 3 => {
+                        let previous = expected.get(&key).copied();
+                        if previous.is_some() {
+                            expected.insert(key, coord);
+                        }
+                        assert_eq!(index.move_to(&key, coord), Ok(previous));
+                    }
+                    _ => assert_eq!(index.insert(key, coord), Ok(expected.insert(key, coord))),
+                }
+
+                assert_eq!(index.len(), expected.len());
+                for (&key, &coord) in expected.iter() {
+                    assert_eq!(index.position_of(&key), Some(coord));
+                    assert_eq!(index.at(coord).filter(|&&other| other == key).count(), 1);
+                }
+                let everything = Rect::new((8, 6)).translate((-2, -1));
+                let mut all = expected
+                    .iter()
+                    .map(|(&key, &coord)| (coord.x, coord.y, key))
+                    .collect::<Vec<_>>();
+                all.sort();
+                assert_eq!(
+                    sorted(
+                        everything
+                            .iter()
+                            .flat_map(|coord| index.at(coord).map(move |key| (coord, key)))
+                    ),
+                    all
+                );
+            }
+        }
+    }
+}
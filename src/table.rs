@@ -0,0 +1,248 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::fmt;
+
+use crate::vecgrid::VecGrid;
+
+/// The characters used to draw the frame of a [`Table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderStyle {
+    /// Thin box-drawing lines, `┌─┬─┐`.
+    Light,
+    /// Thick box-drawing lines, `┏━┳━┓`.
+    Heavy,
+    /// Double box-drawing lines, `╔═╦═╗`.
+    Double,
+    /// Plain ASCII, `+-+-+`, for terminals and fonts without box-drawing
+    /// characters.
+    Ascii,
+}
+
+/// The characters of a frame: horizontal and vertical lines, then the corners,
+/// tees and crossing of each row of line junctions from top to bottom.
+struct Frame {
+    horizontal: char,
+    vertical: char,
+    top: [char; 3],
+    middle: [char; 3],
+    bottom: [char; 3],
+}
+
+impl BorderStyle {
+    fn frame(self) -> Frame {
+        let (horizontal, vertical, junctions) = match self {
+            BorderStyle::Light => ('─', '│', ["┌┬┐", "├┼┤", "└┴┘"]),
+            BorderStyle::Heavy => ('━', '┃', ["┏┳┓", "┣╋┫", "┗┻┛"]),
+            BorderStyle::Double => ('═', '║', ["╔╦╗", "╠╬╣", "╚╩╝"]),
+            BorderStyle::Ascii => ('-', '|', ["+++", "+++", "+++"]),
+        };
+        let row = |junctions: &str| {
+            let mut chars = junctions.chars();
+            [
+                chars.next().unwrap(),
+                chars.next().unwrap(),
+                chars.next().unwrap(),
+            ]
+        };
+        Frame {
+            horizontal,
+            vertical,
+            top: row(junctions[0]),
+            middle: row(junctions[1]),
+            bottom: row(junctions[2]),
+        }
+    }
+}
+
+/// A grid drawn as a table with a frame around each cell, for debug output.
+/// Created with [`VecGrid::render_table`], and drawn through its `Display`
+/// impl.
+///
+/// Each cell's contents are centered in a fixed width, and cut off if they're
+/// longer. With [`with_coordinates`](Table::with_coordinates), the x
+/// coordinate of each column is written above the table and the y coordinate
+/// of each row to its left.
+#[derive(Debug, Clone, Copy)]
+pub struct Table<'a, T> {
+    grid: &'a VecGrid<T>,
+    cell_width: usize,
+    borders: BorderStyle,
+    coordinates: bool,
+    y_up: bool,
+}
+
+impl<T> VecGrid<T> {
+    /// Returns a table drawing of the grid, one character wide per cell with
+    /// light borders and no coordinates by default.
+    pub fn render_table(&self) -> Table<'_, T> {
+        Table {
+            grid: self,
+            cell_width: 1,
+            borders: BorderStyle::Light,
+            coordinates: false,
+            y_up: false,
+        }
+    }
+}
+
+impl<'a, T> Table<'a, T> {
+    /// Sets the number of characters each cell's contents are padded or cut
+    /// to. When coordinates are shown, cells are widened to fit the widest
+    /// x coordinate.
+    pub fn with_cell_width(mut self, cell_width: usize) -> Self {
+        self.cell_width = cell_width;
+        self
+    }
+
+    pub fn with_borders(mut self, borders: BorderStyle) -> Self {
+        self.borders = borders;
+        self
+    }
+
+    /// Sets whether the coordinates of each column and row are written along
+    /// the top and left of the table.
+    pub fn with_coordinates(mut self, coordinates: bool) -> Self {
+        self.coordinates = coordinates;
+        self
+    }
+
+    /// Sets whether rows are drawn from the bottom up, so that the row with
+    /// the highest y coordinate comes first, for code that treats +y as up.
+    pub fn y_up(mut self, y_up: bool) -> Self {
+        self.y_up = y_up;
+        self
+    }
+}
+
+impl<'a, T: fmt::Display> fmt::Display for Table<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bounds = self.grid.bounds;
+        if bounds.area() <= 0 {
+            return Ok(());
+        }
+        let frame = self.borders.frame();
+        let label_width =
+            |range: core::ops::Range<i32>| range.map(|n| n.to_string().len()).max().unwrap_or(0);
+        let (cell_width, margin) = if self.coordinates {
+            let cell_width = self.cell_width.max(label_width(bounds.x_range()));
+            (cell_width, label_width(bounds.y_range()) + 1)
+        } else {
+            (self.cell_width, 0)
+        };
+
+        if self.coordinates {
+            let mut labels = " ".repeat(margin);
+            for x in bounds.x_range() {
+                labels += &format!(" {:^width$}", x, width = cell_width);
+            }
+            writeln!(f, "{}", labels.trim_end())?;
+        }
+        let rule = |f: &mut fmt::Formatter<'_>, [left, middle, right]: [char; 3]| {
+            write!(f, "{:margin$}{}", "", left, margin = margin)?;
+            let segment = frame.horizontal.to_string().repeat(cell_width);
+            for x in bounds.x_range() {
+                let junction = if x + 1 == bounds.right { right } else { middle };
+                write!(f, "{}{}", segment, junction)?;
+            }
+            writeln!(f)
+        };
+
+        rule(f, frame.top)?;
+        for (index, (y, row)) in self.grid.rows_oriented(self.y_up).enumerate() {
+            if index > 0 {
+                rule(f, frame.middle)?;
+            }
+            if self.coordinates {
+                write!(f, "{:>width$} ", y, width = margin - 1)?;
+            }
+            write!(f, "{}", frame.vertical)?;
+            for cell in row {
+                let cell = cell.to_string();
+                let cell = cell.chars().take(cell_width).collect::<String>();
+                write!(f, "{:^width$}{}", cell, frame.vertical, width = cell_width)?;
+            }
+            writeln!(f)?;
+        }
+        rule(f, frame.bottom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::Rect;
+
+    #[test]
+    fn light_borders() {
+        let grid = VecGrid::with_generator(Rect::new((3, 2)), |(x, y)| x + y * 3);
+        assert_eq!(
+            grid.render_table().to_string(),
+            "\
+┌─┬─┬─┐
+│0│1│2│
+├─┼─┼─┤
+│3│4│5│
+└─┴─┴─┘
+"
+        );
+    }
+
+    #[test]
+    fn ascii_borders_with_coordinates() {
+        let grid = VecGrid::with_generator(Rect::new((2, 3)).translate((9, -1)), |(x, y)| {
+            if x == y + 10 {
+                "@@@@"
+            } else {
+                "."
+            }
+        });
+        let table = grid
+            .render_table()
+            .with_cell_width(3)
+            .with_borders(BorderStyle::Ascii)
+            .with_coordinates(true);
+        assert_eq!(
+            table.to_string(),
+            "     9  10
+   +---+---+
+-1 |@@@| . |
+   +---+---+
+ 0 | . |@@@|
+   +---+---+
+ 1 | . | . |
+   +---+---+
+"
+        );
+
+        // Cells widen to fit the column labels.
+        let narrow = grid.render_table().with_coordinates(true).to_string();
+        assert_eq!(narrow.lines().nth(1), Some("   ┌──┬──┐"));
+        assert_eq!(narrow.lines().nth(2), Some("-1 │@@│. │"));
+    }
+
+    #[test]
+    fn y_up_rows() {
+        let grid = VecGrid::with_generator(Rect::new((3, 2)), |(x, y)| x + y * 3);
+        assert_eq!(
+            grid.render_table()
+                .with_coordinates(true)
+                .y_up(true)
+                .to_string(),
+            "   0 1 2
+  ┌─┬─┬─┐
+1 │3│4│5│
+  ├─┼─┼─┤
+0 │0│1│2│
+  └─┴─┴─┘
+"
+        );
+    }
+
+    #[test]
+    fn empty_grids_draw_nothing() {
+        let grid = VecGrid::<u8>::new(Rect::new((0, 3)));
+        assert_eq!(grid.render_table().with_coordinates(true).to_string(), "");
+    }
+}
@@ -0,0 +1,363 @@
+use crate::{
+    bitgrid::BitGrid,
+    coord::Coord,
+    grid::{Grid, GridError, GridMut, IterCell, IterCellMut},
+    patterns::{Rect, RectIter},
+    vecgrid::{IterMut, VecGrid},
+};
+
+/// The cells written to since they were last taken, along with the smallest
+/// rect covering them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Dirty {
+    cells: BitGrid,
+    bounds: Option<Rect>,
+}
+
+impl Dirty {
+    fn new(bounds: Rect) -> Self {
+        Self {
+            cells: BitGrid::new(bounds),
+            bounds: None,
+        }
+    }
+
+    /// Marks `coord`, which must be inside the grid.
+    fn mark(&mut self, coord: Coord) {
+        self.cells.set(coord, true);
+        self.extend(Rect::new((1, 1)).translate(coord));
+    }
+
+    /// Marks every coord of `rect`, which must be inside the grid.
+    fn mark_rect(&mut self, rect: Rect) {
+        if rect.is_empty() {
+            return;
+        }
+        for coord in rect.iter() {
+            self.cells.set(coord, true);
+        }
+        self.extend(rect);
+    }
+
+    fn extend(&mut self, rect: Rect) {
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => Rect {
+                top: bounds.top.min(rect.top),
+                bottom: bounds.bottom.max(rect.bottom),
+                left: bounds.left.min(rect.left),
+                right: bounds.right.max(rect.right),
+            },
+            None => rect,
+        });
+    }
+}
+
+/// A [`VecGrid`] that keeps track of which cells have been written to, for
+/// redrawing or syncing only what changed.
+///
+/// Every write through the [`GridMut`] trait or the methods below marks the
+/// cells it could have changed as dirty, until they're collected with
+/// [`take_dirty`](TrackedGrid::take_dirty). Reads go straight to the inner
+/// grid, and cost the same as they would on it.
+///
+/// Cells are marked when they're handed out for writing, whether or not they
+/// end up being changed: [`iter_mut`](TrackedGrid::iter_mut) marks the whole
+/// grid, and [`selection_iter_mut`](TrackedGrid::selection_iter_mut) marks
+/// each cell as it's yielded. Use [`get_mut`](GridMut::get_mut) or
+/// [`set`](GridMut::set) to only mark the cells that are actually written.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TrackedGrid<T> {
+    grid: VecGrid<T>,
+    dirty: Dirty,
+}
+
+impl<T> Grid<T> for TrackedGrid<T> {
+    fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        self.grid.get(coord)
+    }
+
+    fn bounds(&self) -> Rect {
+        self.grid.bounds
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+    {
+        self.grid.iter()
+    }
+}
+
+impl<T> GridMut<T> for TrackedGrid<T> {
+    fn get_mut<C: Into<Coord>>(&mut self, coord: C) -> Option<&mut T> {
+        let coord = coord.into();
+        let cell = self.grid.get_mut(coord)?;
+        self.dirty.mark(coord);
+        Some(cell)
+    }
+
+    fn copy<C1, C2>(&mut self, src: C1, dest: C2) -> bool
+    where
+        T: Copy,
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        let dest = dest.into();
+        let copied = self.grid.copy(src, dest);
+        if copied {
+            self.dirty.mark(dest);
+        }
+        copied
+    }
+
+    fn swap<C1, C2>(&mut self, coord1: C1, coord2: C2) -> bool
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        let (coord1, coord2) = (coord1.into(), coord2.into());
+        let swapped = self.grid.swap(coord1, coord2);
+        if swapped {
+            self.dirty.mark(coord1);
+            self.dirty.mark(coord2);
+        }
+        swapped
+    }
+
+    fn mov(&mut self, src: Coord, dest: Coord) -> Option<T>
+    where
+        T: Default,
+    {
+        let previous = self.grid.mov(src, dest)?;
+        self.dirty.mark(src);
+        self.dirty.mark(dest);
+        Some(previous)
+    }
+}
+
+impl<T> TrackedGrid<T> {
+    /// Wraps `grid`, with none of its cells dirty.
+    pub fn new(grid: VecGrid<T>) -> Self {
+        let dirty = Dirty::new(grid.bounds);
+        Self { grid, dirty }
+    }
+
+    /// Returns the underlying grid. There's no mutable version, as writes to
+    /// it couldn't be tracked.
+    pub fn inner(&self) -> &VecGrid<T> {
+        &self.grid
+    }
+
+    pub fn into_inner(self) -> VecGrid<T> {
+        self.grid
+    }
+
+    /// Returns whether the cell at `coord` has been marked since the last
+    /// call to [`take_dirty`](TrackedGrid::take_dirty).
+    pub fn is_dirty<C: Into<Coord>>(&self, coord: C) -> bool {
+        self.dirty.cells.get(coord) == Some(true)
+    }
+
+    /// Returns the smallest rect covering every dirty cell, or `None` if
+    /// there aren't any.
+    pub fn dirty_bounds(&self) -> Option<Rect> {
+        self.dirty.bounds
+    }
+
+    /// Returns the coordinates of the dirty cells in row-major order, and
+    /// marks every cell as clean again.
+    ///
+    /// Only the [`dirty_bounds`](TrackedGrid::dirty_bounds) are scanned and
+    /// cleared, and nothing is allocated, so a few changes to a large grid are
+    /// cheap to collect every frame. Like `Vec::drain`, dropping the iterator
+    /// early still marks the remaining cells as clean.
+    pub fn take_dirty(&mut self) -> impl Iterator<Item = Coord> + '_ {
+        TakeDirty {
+            coords: self.dirty.bounds.take().map(|bounds| bounds.iter()),
+            cells: &mut self.dirty.cells,
+        }
+    }
+
+    /// Returns a mutable iterator over all cells in the grid, marking every
+    /// cell as dirty.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.dirty.mark_rect(self.grid.bounds);
+        self.grid.iter_mut()
+    }
+
+    /// Returns a mutable iterator over the cells specified by the coords
+    /// iterator, following the rules of [`VecGrid::selection_iter_mut`], and
+    /// marking each cell as dirty when it's yielded.
+    pub fn selection_iter_mut<I>(
+        &mut self,
+        coords: I,
+    ) -> impl Iterator<Item = Result<IterCellMut<'_, T>, GridError>>
+    where
+        I: IntoIterator,
+        I::Item: Into<Coord>,
+    {
+        let dirty = &mut self.dirty;
+        self.grid.selection_iter_mut(coords).inspect(move |cell| {
+            if let Ok((coord, _)) = cell {
+                dirty.mark(*coord);
+            }
+        })
+    }
+
+    /// Sets every cell inside `rect` to a clone of `value`. Any part of
+    /// `rect` outside the grid is ignored.
+    pub fn fill_region(&mut self, rect: Rect, value: T)
+    where
+        T: Clone,
+    {
+        let rect = match rect.intersection(&self.grid.bounds) {
+            Some(rect) => rect,
+            None => return,
+        };
+        self.dirty.mark_rect(rect);
+        for (_, cell) in self.grid.iter_rect_mut(rect) {
+            cell.clone_from(&value);
+        }
+    }
+
+    /// Copies each cell of `source` onto the cell with the same coordinate,
+    /// like [`VecGrid::blit`], returning the number of cells copied.
+    pub fn blit(&mut self, source: &VecGrid<T>) -> usize
+    where
+        T: Clone,
+    {
+        if let Some(overlap) = self.grid.bounds.intersection(&source.bounds) {
+            self.dirty.mark_rect(overlap);
+        }
+        self.grid.blit(source)
+    }
+}
+
+impl<T> From<VecGrid<T>> for TrackedGrid<T> {
+    fn from(grid: VecGrid<T>) -> Self {
+        Self::new(grid)
+    }
+}
+
+/// Yields the dirty cells within the dirty bounds, clearing each one as it's
+/// found, and the rest of the bounds when it's dropped.
+struct TakeDirty<'a> {
+    coords: Option<RectIter>,
+    cells: &'a mut BitGrid,
+}
+
+impl Iterator for TakeDirty<'_> {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Coord> {
+        let cells = &mut *self.cells;
+        self.coords.as_mut()?.find(|&coord| {
+            let dirty = cells.get(coord) == Some(true);
+            if dirty {
+                cells.set(coord, false);
+            }
+            dirty
+        })
+    }
+}
+
+impl Drop for TakeDirty<'_> {
+    fn drop(&mut self) {
+        for coord in self.coords.take().into_iter().flatten() {
+            self.cells.set(coord, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn tracked() -> TrackedGrid<u8> {
+        TrackedGrid::new(VecGrid::new(Rect::new((8, 6)).translate((-2, -3))))
+    }
+
+    #[test]
+    fn writes_mark_cells() {
+        let mut grid = tracked();
+        assert_eq!(grid.dirty_bounds(), None);
+        assert_eq!(grid.get((0, 0)), Some(&0));
+        assert!(grid.iter().all(|(coord, _)| !grid.is_dirty(coord)));
+
+        grid.set((1, 2), 5);
+        *grid.get_mut((-2, -1)).unwrap() = 3;
+        grid.swap((0, 0), (0, 1));
+        assert!(!grid.set((100, 0), 1));
+        assert!(grid.is_dirty((1, 2)));
+        assert_eq!(
+            grid.dirty_bounds(),
+            Some(Rect::new((4, 4)).translate((-2, -1)))
+        );
+
+        assert_eq!(
+            grid.take_dirty().collect::<Vec<_>>(),
+            vec![
+                Coord::new(-2, -1),
+                Coord::new(0, 0),
+                Coord::new(0, 1),
+                Coord::new(1, 2),
+            ]
+        );
+        assert_eq!(grid.dirty_bounds(), None);
+        assert_eq!(grid.take_dirty().count(), 0);
+        assert_eq!(grid.get((1, 2)), Some(&5));
+    }
+
+    #[test]
+    fn bulk_writes() {
+        let mut grid = tracked();
+        grid.fill_region(Rect::new((3, 10)).translate((4, 0)), 1);
+        assert_eq!(
+            grid.dirty_bounds(),
+            Some(Rect::new((2, 3)).translate((4, 0)))
+        );
+        assert_eq!(grid.take_dirty().count(), 6);
+        assert_eq!(grid.inner().count_value(&1), 6);
+
+        let patch = VecGrid::filled(Rect::new((2, 2)).translate((-3, -4)), 7);
+        assert_eq!(grid.blit(&patch), 1);
+        assert_eq!(
+            grid.take_dirty().collect::<Vec<_>>(),
+            vec![Coord::new(-2, -3)]
+        );
+
+        let line = crate::patterns::Line::new((0, -3), (2, -1));
+        for cell in grid.selection_iter_mut(line.iter()) {
+            *cell.unwrap().1 += 1;
+        }
+        assert_eq!(
+            grid.take_dirty().collect::<Vec<_>>(),
+            line.iter().collect::<Vec<_>>()
+        );
+
+        grid.iter_mut().next();
+        assert_eq!(grid.dirty_bounds(), Some(grid.bounds()));
+        assert_eq!(grid.take_dirty().count(), 48);
+    }
+
+    #[test]
+    fn take_dirty_reuses_the_bitmap() {
+        let mut grid = tracked();
+        let words = grid.dirty.cells.as_words().as_ptr();
+        grid.set((0, 0), 1);
+        grid.set((1, 1), 1);
+        grid.set((2, 2), 1);
+        assert_eq!(grid.take_dirty().next(), Some(Coord::new(0, 0)));
+        assert_eq!(grid.dirty_bounds(), None);
+        assert!(!grid.is_dirty((2, 2)));
+        assert_eq!(grid.dirty.cells.count_ones(), 0);
+        assert_eq!(grid.dirty.cells.as_words().as_ptr(), words);
+
+        grid.set((1, 1), 2);
+        assert_eq!(
+            grid.take_dirty().collect::<Vec<_>>(),
+            vec![Coord::new(1, 1)]
+        );
+    }
+}
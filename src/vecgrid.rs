@@ -1,27 +1,95 @@
-use std::{
-    collections::{HashSet, VecDeque},
-    fmt,
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    string::String,
+    vec,
+    vec::Vec,
 };
+use core::{
+    fmt, iter,
+    marker::PhantomData,
+    mem,
+    ops::{self, Range},
+    slice,
+};
+#[cfg(feature = "std")]
+use std::{collections::HashMap, hash::Hash};
 
+#[cfg(feature = "std")]
+use crate::grid::BorderMode;
 use crate::{
     coord::Coord,
-    grid::{Grid, GridError, IterCell, IterCellMut},
-    patterns::{Neighborhood, Rect},
+    grid::{
+        CellError, GetManyError, Grid, GridError, GridMut, IterCell, IterCellMut, PadMode,
+        ResampleMode, ShiftMode,
+    },
+    layout::{Layout, RowMajor},
+    patterns::{Connectivity, Neighborhood, Rect},
+    view::GridView,
 };
 
+/// Maximum number of rows and columns printed by the `Debug` impl of
+/// [`VecGrid`] before the output is truncated.
+const DEBUG_MAX_CELLS: usize = 16;
+
 /// The core type of this library. A 2D grid of cell type `T`.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct VecGrid<T> {
-    /// Row-major, linear storage of cell data.
+///
+/// Cells are stored row-major by default. A grid can instead be stored in
+/// another [`Layout`], such as [`ColumnMajor`](crate::ColumnMajor), to share its storage with code
+/// expecting that order. Most operations are only available on row-major
+/// grids, but reading and writing cells by coordinate works the same in every
+/// layout.
+///
+/// Two grids are equal (and hash equally) when they have the same bounds and
+/// identical cells.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct VecGrid<T, L = RowMajor> {
+    /// Linear storage of cell data, in the order given by the layout.
     pub cells: Vec<T>,
     pub bounds: Rect,
+    pub(crate) layout: PhantomData<L>,
 }
 
-impl<T> Grid<T> for VecGrid<T> {
+impl<T, L: Layout> Grid<T> for VecGrid<T, L> {
     fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
         self.cells.get(self.coord_to_index(coord)?)
     }
 
+    /// Returns the area covered by the grid, for use with the
+    /// [`patterns`](crate::patterns) module.
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+    {
+        L::iter(self)
+    }
+
+    /// Returns whether `coord` lies within the grid's bounds.
+    fn contains<C: Into<Coord>>(&self, coord: C) -> bool {
+        self.bounds.contains(coord)
+    }
+
+    fn selection_iter<'a, I>(
+        &'a self,
+        coords: I,
+    ) -> impl Iterator<Item = Result<IterCell<'a, T>, GridError>>
+    where
+        T: 'a,
+        I: IntoIterator,
+        I::Item: Into<Coord>,
+    {
+        SelectionIter {
+            grid: self,
+            coords: coords.into_iter(),
+        }
+    }
+}
+
+impl<T, L: Layout> GridMut<T> for VecGrid<T, L> {
     fn get_mut<C: Into<Coord>>(&mut self, coord: C) -> Option<&mut T> {
         let index = self.coord_to_index(coord)?;
         self.cells.get_mut(index)
@@ -74,29 +142,196 @@ impl<T> Grid<T> for VecGrid<T> {
 }
 
 impl<T> VecGrid<T> {
+    /// Constructs a grid with every cell set to `T::default()`.
     pub fn new(bounds: Rect) -> Self
     where
-        T: Default + Clone,
+        T: Default,
+    {
+        Self {
+            cells: (0..bounds.area().max(0)).map(|_| T::default()).collect(),
+            bounds,
+            layout: PhantomData,
+        }
+    }
+
+    /// Constructs a grid with every cell set to a clone of `value`.
+    pub fn filled(bounds: Rect, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![value; bounds.area().max(0) as usize],
+            bounds,
+            layout: PhantomData,
+        }
+    }
+
+    /// Constructs a grid from its bounds and its raw, row-major storage. See
+    /// [`as_slice`](VecGrid::as_slice) for the layout `cells` must follow.
+    ///
+    /// Returns [`GridError::WrongLength`] if `cells` doesn't hold exactly one
+    /// value per cell.
+    pub fn from_raw_parts(bounds: Rect, cells: Vec<T>) -> Result<Self, GridError> {
+        let expected = bounds.area().max(0) as usize;
+        if cells.len() != expected {
+            return Err(GridError::WrongLength {
+                expected,
+                received: cells.len(),
+            });
+        }
+        Ok(Self {
+            cells,
+            bounds,
+            layout: PhantomData,
+        })
+    }
+
+    /// Constructs a grid by calling `generator` with the coordinate of each
+    /// cell, in row-major order.
+    pub fn with_generator<C>(bounds: Rect, mut generator: impl FnMut(C) -> T) -> Self
+    where
+        C: From<Coord>,
     {
+        let cells = bounds
+            .iter()
+            .take(bounds.area().max(0) as usize)
+            .map(|coord| generator(coord.into()))
+            .collect();
         Self {
-            cells: vec![T::default(); bounds.area() as usize],
+            cells,
             bounds,
+            layout: PhantomData,
         }
     }
 
-    pub fn with_generator<C>(bounds: Rect, generator: impl Fn(C) -> T) -> Self
+    /// Like [`with_generator`](VecGrid::with_generator), but also passes the
+    /// position of each cell's center as a fraction of the grid's dimensions,
+    /// so both components lie strictly between 0 and 1. For example, the
+    /// cells of a 2x1 grid are centered at `(0.25, 0.5)` and `(0.75, 0.5)`.
+    pub fn with_generator_normalized<F>(bounds: Rect, mut generator: F) -> Self
+    where
+        F: FnMut(Coord, (f32, f32)) -> T,
+    {
+        let offset = bounds.offset();
+        let (width, height) = (bounds.width() as f32, bounds.height() as f32);
+        Self::with_generator(bounds, |coord: Coord| {
+            let local = coord - offset;
+            let position = (
+                (local.x as f32 + 0.5) / width,
+                (local.y as f32 + 0.5) / height,
+            );
+            generator(coord, position)
+        })
+    }
+
+    /// Fallible version of [`with_generator`](VecGrid::with_generator), which
+    /// stops at the first error and returns it along with the coordinate of
+    /// the cell that failed to generate.
+    pub fn try_with_generator<C, E>(
+        bounds: Rect,
+        mut generator: impl FnMut(C) -> Result<T, E>,
+    ) -> Result<Self, CellError<E>>
     where
         C: From<Coord>,
     {
-        let mut cells = Vec::with_capacity(bounds.area() as usize);
-        // TODO: Implement an iterator over all grid cells.
-        for y in bounds.y_range() {
-            for x in bounds.x_range() {
-                let coord = Coord::new(x, y);
-                cells.push(generator(coord.into()));
+        let cells = bounds
+            .iter()
+            .take(bounds.area().max(0) as usize)
+            .map(|coord| generator(coord.into()).map_err(|error| CellError { coord, error }))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            cells,
+            bounds,
+            layout: PhantomData,
+        })
+    }
+
+    /// Returns the coordinate of the grid's first cell, its `(left, top)`
+    /// corner. Grids built from a plain `Rect::new(dimensions)` have their
+    /// origin at `(0, 0)`, but any origin works, including negative ones, and
+    /// every coordinate-taking method works in those coordinates.
+    pub fn origin(&self) -> Coord {
+        self.bounds.offset()
+    }
+
+    /// Moves the grid so that its first cell is at `origin`, without touching
+    /// the cells themselves.
+    pub fn set_origin<C: Into<Coord>>(&mut self, origin: C) {
+        let origin = origin.into();
+        self.bounds = self.bounds.translate(origin - self.origin());
+    }
+
+    /// Copies each cell of `source` onto the cell with the same coordinate in
+    /// `self`, returning the number of cells copied. Cells of `source` outside
+    /// of `self` are skipped.
+    pub fn blit(&mut self, source: &VecGrid<T>) -> usize
+    where
+        T: Clone,
+    {
+        let overlap = match self.bounds.intersection(&source.bounds) {
+            Some(overlap) => overlap,
+            None => return 0,
+        };
+        let mut count = 0;
+        for ((_, cell), (_, value)) in self.iter_rect_mut(overlap).zip(source.iter_rect(overlap)) {
+            cell.clone_from(value);
+            count += 1;
+        }
+        count
+    }
+
+    /// Copies the cells covered by `src` so that its top-left corner lands on
+    /// `dest`, returning the number of cells copied. Parts of the destination
+    /// outside of the grid are skipped.
+    ///
+    /// The source and destination may overlap. Cells are copied in an order
+    /// such that each one is read before it's overwritten, so the destination
+    /// always ends up holding the source as it was before the copy.
+    ///
+    /// `src` must be non-empty and lie entirely within the grid, otherwise a
+    /// [`GridError::OutOfBounds`] naming one of its corners is returned and the
+    /// grid is left unchanged.
+    pub fn copy_region<C: Into<Coord>>(&mut self, src: Rect, dest: C) -> Result<usize, GridError>
+    where
+        T: Clone,
+    {
+        self.check_region(src)?;
+        let offset = dest.into() - src.offset();
+        let dest = match src.translate(offset).intersection(&self.bounds) {
+            Some(dest) if !dest.is_empty() => dest,
+            _ => return Ok(0),
+        };
+        let src = dest.translate(offset.negate());
+        let width = dest.width() as usize;
+
+        // Rows moving down are copied from the bottom up, so none of them is
+        // overwritten before it's read, and the other way around.
+        let mut down = src.y_range();
+        let mut up = src.y_range().rev();
+        let rows: &mut dyn Iterator<Item = i32> = if offset.y > 0 { &mut up } else { &mut down };
+        for y in rows {
+            let from = self.coord_to_index((src.left, y)).unwrap();
+            let to = self.coord_to_index((dest.left, y + offset.y)).unwrap();
+            if offset.y != 0 {
+                // Different rows never overlap.
+                let (head, tail) = self.cells.split_at_mut(from.max(to));
+                let (from, to) = if from < to {
+                    (&head[from..from + width], &mut tail[..width])
+                } else {
+                    (&tail[..width], &mut head[to..to + width])
+                };
+                to.clone_from_slice(from);
+            } else if from < to {
+                for i in (0..width).rev() {
+                    self.cells[to + i] = self.cells[from + i].clone();
+                }
+            } else {
+                for i in 0..width {
+                    self.cells[to + i] = self.cells[from + i].clone();
+                }
             }
         }
-        Self { cells, bounds }
+        Ok(dest.area() as usize)
     }
 
     /// Copies all values of `other` into `self` at offset `position`.
@@ -114,272 +349,4172 @@ impl<T> VecGrid<T> {
     where
         F: Fn(&T) -> U,
     {
-        VecGrid::<U>::with_generator(self.bounds, |coord: Coord| f(self.get(coord).unwrap()))
+        VecGrid {
+            cells: self.values().map(f).collect(),
+            bounds: self.bounds,
+            layout: PhantomData,
+        }
     }
 
-    /// Returns an iterator over all cells in the grid.
-    pub fn iter(&self) -> impl Iterator<Item = IterCell<T>> {
-        self.cells
-            .iter()
-            .clone()
-            .enumerate()
-            .map(move |cell| (self.index_to_coord(cell.0), cell.1))
+    /// Consumes the grid, moving each cell into `f` along with its coordinate
+    /// to build a new grid with the same bounds.
+    pub fn map_into<U, F>(self, mut f: F) -> VecGrid<U>
+    where
+        F: FnMut(Coord, T) -> U,
+    {
+        let bounds = self.bounds;
+        VecGrid {
+            cells: self
+                .into_iter()
+                .map(|(coord, cell)| f(coord, cell))
+                .collect(),
+            bounds,
+            layout: PhantomData,
+        }
     }
 
-    /// Returns a mutable iterator over all cells in the grid.
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = IterCellMut<T>> {
-        let rect = self.bounds;
-        self.cells
-            .iter_mut()
-            .enumerate()
-            .map(move |(index, cell)| (Self::index_to_coord_with_bounds(rect, index), cell))
+    /// Fallible version of [`map`](VecGrid::map), which stops at the first
+    /// error and returns it along with the coordinate of the cell that failed
+    /// to map.
+    pub fn try_map<U, E, F>(&self, mut f: F) -> Result<VecGrid<U>, CellError<E>>
+    where
+        F: FnMut(Coord, &T) -> Result<U, E>,
+    {
+        let cells = self
+            .iter()
+            .map(|(coord, cell)| f(coord, cell).map_err(|error| CellError { coord, error }))
+            .collect::<Result<_, _>>()?;
+        Ok(VecGrid {
+            cells,
+            bounds: self.bounds,
+            layout: PhantomData,
+        })
     }
 
-    /// Returns an iterator over the cells specified by the coords iterator.
-    pub fn selection_iter<I>(
-        &self,
-        coords: I,
-    ) -> impl Iterator<Item = Result<IterCell<T>, GridError>>
+    /// Creates a new grid with the same bounds, where each cell is computed
+    /// from the corresponding cell of `self` along with read access to its
+    /// neighbors.
+    pub fn map_stencil<U, F>(&self, mut f: F) -> VecGrid<U>
     where
-        I: Iterator<Item = Coord>,
+        F: FnMut(Coord, &T, Neighbors<'_, T>) -> U,
     {
-        SelectionIter { grid: self, coords }
+        let cells = self
+            .iter()
+            .map(|(coord, cell)| {
+                f(
+                    coord,
+                    cell,
+                    Neighbors {
+                        grid: self,
+                        center: coord,
+                    },
+                )
+            })
+            .collect();
+        VecGrid {
+            cells,
+            bounds: self.bounds,
+            layout: PhantomData,
+        }
     }
 
-    /// Returns a mutable iterator over the cells specified by the coords
-    /// iterator.
+    /// Returns a copy of the grid where each cell is repeated into a block of
+    /// `factor.x` by `factor.y` cells (nearest-neighbor scaling).
     ///
-    /// If there is an attempt to visit a given cell more than once (which would
-    /// create multiple simultaneous mutable references to the cell), a
-    /// [`GridError::AlreadyVisited`](GridError::AlreadyVisited) will be returned
-    /// in place of the cell contents.
-    pub fn selection_iter_mut<I>(
-        &mut self,
-        coords: I,
-    ) -> impl Iterator<Item = Result<IterCellMut<T>, GridError>>
+    /// The bounds of the output are the source bounds with each edge multiplied
+    /// by the factor, so output coord `c` is a copy of source coord
+    /// `(c.x.div_euclid(factor.x), c.y.div_euclid(factor.y))`.
+    ///
+    /// Returns [`GridError::InvalidScale`] if either component of `factor`
+    /// isn't positive, or if the output bounds or area wouldn't fit in an
+    /// `i32`.
+    pub fn upscale<C: Into<Coord>>(&self, factor: C) -> Result<VecGrid<T>, GridError>
     where
-        I: Iterator<Item = Coord>,
+        T: Clone,
     {
-        SelectionIterMut {
-            grid: self,
-            coords,
-            visited_coords: HashSet::new(),
+        let factor = factor.into();
+        if factor.x <= 0 || factor.y <= 0 {
+            return Err(GridError::InvalidScale(factor));
+        }
+        let scale = |edge: i32, scale: i32| {
+            edge.checked_mul(scale)
+                .ok_or(GridError::InvalidScale(factor))
+        };
+        let bounds = Rect {
+            top: scale(self.bounds.top, factor.y)?,
+            bottom: scale(self.bounds.bottom, factor.y)?,
+            left: scale(self.bounds.left, factor.x)?,
+            right: scale(self.bounds.right, factor.x)?,
+        };
+        let area = bounds
+            .right
+            .checked_sub(bounds.left)
+            .zip(bounds.bottom.checked_sub(bounds.top))
+            .and_then(|(width, height)| width.checked_mul(height))
+            .ok_or(GridError::InvalidScale(factor))?;
+        let mut cells = Vec::with_capacity(area as usize);
+        for row in self.rows() {
+            let row_start = cells.len();
+            for cell in row {
+                cells.extend(iter::repeat_n(cell, factor.x as usize).cloned());
+            }
+            for _ in 1..factor.y {
+                cells.extend_from_within(row_start..);
+            }
         }
+        Ok(VecGrid {
+            cells,
+            bounds,
+            layout: PhantomData,
+        })
     }
 
-    /// Returns an iterator beginning from `starting_coord` and continuing
-    /// through all recursively adjacent coords that satisfy the `predicate`. In
-    /// other words, this iterates through the cells according to a flood fill
-    /// algorithm.
+    /// Returns a copy of the grid stretched or squashed to `dimensions`, with
+    /// each output cell computed by `mode` from the source cells under its
+    /// center. [`Nearest`](crate::Nearest) copies the nearest cell of any
+    /// type, while [`Bilinear`](crate::Bilinear) interpolates float grids. The
+    /// output keeps the top-left corner of the source bounds.
     ///
-    /// Since there is no `mut` version of this iterator (which would require
-    /// simultaneous mutable and shared references to most of the cells), the
-    /// resulting iterator can be collected and then passed into
-    /// [`Grid::selection_iter_mut`](crate::grid::Grid::selection_iter_mut) to
-    /// gain access to mutable cell contents.
-    pub fn flood_iter<C: Into<Coord>>(
-        &self,
-        starting_coord: C,
-        predicate: impl Fn(&T) -> bool + 'static,
-    ) -> impl Iterator<Item = IterCell<T>> {
-        let mut coords_to_search = VecDeque::new();
-        coords_to_search.push_back(starting_coord.into());
+    /// Returns [`GridError::InvalidScale`] if `dimensions` has a negative
+    /// component, or if the grid is empty but `dimensions` isn't.
+    pub fn resample<C, M>(&self, dimensions: C, mode: M) -> Result<VecGrid<T>, GridError>
+    where
+        C: Into<Coord>,
+        M: ResampleMode<T>,
+    {
+        let (bounds, scale) = self.resample_bounds(dimensions.into())?;
+        Ok(VecGrid::with_generator(bounds, |coord: Coord| {
+            let local = coord - bounds.offset();
+            let x = (local.x as f64 + 0.5) * scale.0;
+            let y = (local.y as f64 + 0.5) * scale.1;
+            mode.sample(self, x, y)
+        }))
+    }
 
-        FloodIter {
-            grid: self,
-            predicate: Box::new(predicate),
-            searched_coords: vec![],
-            coords_to_search,
+    /// Partitions the grid into blocks of `block.x` by `block.y` cells and
+    /// reduces each one into a single cell of the output grid. The inverse of
+    /// [`upscale`](VecGrid::upscale).
+    ///
+    /// Output coord `c` covers the source cells from `c * block` up to (but not
+    /// including) `(c + 1) * block`. The reducer is passed that output coord
+    /// and a [`GridView`] of the block, in which coordinates are local to the
+    /// block. When the grid bounds aren't multiples of the block size, the
+    /// blocks along the edges are clipped to the grid and passed to the reducer
+    /// as smaller views rather than being dropped.
+    pub fn downsample<U, C, F>(&self, block: C, mut reduce: F) -> Result<VecGrid<U>, GridError>
+    where
+        C: Into<Coord>,
+        F: FnMut(Coord, GridView<'_, T>) -> U,
+    {
+        let block = block.into();
+        if block.x <= 0 || block.y <= 0 {
+            return Err(GridError::InvalidScale(block));
         }
+        let ceil_div = |a: i32, b: i32| -(-a).div_euclid(b);
+        let bounds = Rect {
+            top: self.bounds.top.div_euclid(block.y),
+            bottom: ceil_div(self.bounds.bottom, block.y),
+            left: self.bounds.left.div_euclid(block.x),
+            right: ceil_div(self.bounds.right, block.x),
+        };
+        Ok(VecGrid::with_generator(bounds, |coord: Coord| {
+            let start = coord * block;
+            let end = start + block;
+            let region = Rect {
+                top: start.y.max(self.bounds.top),
+                bottom: end.y.min(self.bounds.bottom),
+                left: start.x.max(self.bounds.left),
+                right: end.x.min(self.bounds.right),
+            };
+            reduce(coord, self.view(region).unwrap())
+        }))
     }
 
-    /// Converts a 2D Grid coordinate into a linear Vec index.
-    fn coord_to_index<C: Into<Coord>>(&self, coord: C) -> Option<usize> {
-        let coord = coord.into();
-        if !self.bounds.contains(coord) {
-            return None;
+    /// Computes the next generation of a cellular automaton into `scratch`,
+    /// then swaps it with `self`, so that `self` holds the new generation and
+    /// `scratch` holds the previous one.
+    ///
+    /// `rule` is called for each cell with its coordinate, its current value,
+    /// and read access to the entire previous generation. As long as `scratch`
+    /// has the same bounds as `self` (which it will after the first step), no
+    /// allocation takes place; otherwise it's rebuilt with the right bounds.
+    pub fn step_automaton<F>(&mut self, scratch: &mut VecGrid<T>, mut rule: F)
+    where
+        F: FnMut(Coord, &T, &VecGrid<T>) -> T,
+    {
+        if scratch.bounds == self.bounds {
+            for ((coord, cell), next) in self.iter().zip(scratch.cells.iter_mut()) {
+                *next = rule(coord, cell, self);
+            }
+        } else {
+            *scratch = VecGrid::with_generator(self.bounds, |coord: Coord| {
+                rule(coord, self.get(coord).unwrap(), self)
+            });
         }
-        let offset_coord = coord - self.bounds.offset();
-        Some((offset_coord.x + offset_coord.y * self.bounds.width()) as usize)
+        mem::swap(self, scratch);
     }
 
-    /// Converts a linear Vec index into a 2D Grid coordinate.
-    fn index_to_coord(&self, index: usize) -> Coord {
-        Self::index_to_coord_with_bounds(self.bounds, index)
+    /// Creates a new grid by combining each cell of `self` with the cell at
+    /// the same coordinate in `other`.
+    ///
+    /// Both grids must have the same bounds.
+    pub fn zip_map<U, V, F>(&self, other: &VecGrid<U>, mut f: F) -> Result<VecGrid<V>, GridError>
+    where
+        F: FnMut(&T, &U) -> V,
+    {
+        self.check_bounds_match(other.bounds)?;
+        Ok(VecGrid {
+            cells: self
+                .cells
+                .iter()
+                .zip(other.cells.iter())
+                .map(|(a, b)| f(a, b))
+                .collect(),
+            bounds: self.bounds,
+            layout: PhantomData,
+        })
     }
 
-    /// Use `index_to_coord` if possible. This exists so that `iter_mut` can
-    /// avoid borrowing `self`.
-    fn index_to_coord_with_bounds(bounds: Rect, index: usize) -> Coord {
-        let y = (index as f32 / bounds.width() as f32).floor() as i32;
-        let x = index as i32 - (y * bounds.width()) as i32;
-        Coord::new(x, y) + bounds.offset()
+    /// Combines each cell of `upper` into the cell at the same coordinate in
+    /// `self`, calling `combine` with the coordinate, the cell to update and
+    /// the cell above it.
+    ///
+    /// Unlike [`blit`](VecGrid::blit), the existing value can be kept or
+    /// blended rather than overwritten, and unlike
+    /// [`zip_map`](VecGrid::zip_map), nothing is allocated, so compositing
+    /// layers every frame stays cheap. Both grids must have the same bounds.
+    pub fn overlay<U, F>(&mut self, upper: &VecGrid<U>, mut combine: F) -> Result<(), GridError>
+    where
+        F: FnMut(Coord, &mut T, &U),
+    {
+        self.check_bounds_match(upper.bounds)?;
+        for ((coord, cell), value) in self.iter_mut().zip(upper.cells.iter()) {
+            combine(coord, cell, value);
+        }
+        Ok(())
     }
-}
-
-pub struct SelectionIter<'a, T, I> {
-    grid: &'a VecGrid<T>,
-    coords: I,
-}
-
-impl<'a, T, I> Iterator for SelectionIter<'a, T, I>
-where
-    I: Iterator<Item = Coord>,
-{
-    type Item = Result<IterCell<'a, T>, GridError>;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(coord) = self.coords.next() {
-            if let Some(cell) = self.grid.get(coord) {
-                return Some(Ok((coord, cell)));
-            }
-            return Some(Err(GridError::OutOfBounds(coord)));
+    /// Like [`overlay`](VecGrid::overlay), but for a smaller layer placed with
+    /// its top-left corner on `origin`, whatever its own bounds. Cells of
+    /// `upper` landing outside of `self` are skipped, and the number of cells
+    /// combined is returned.
+    pub fn overlay_at<U, C, F>(&mut self, upper: &VecGrid<U>, origin: C, mut combine: F) -> usize
+    where
+        C: Into<Coord>,
+        F: FnMut(Coord, &mut T, &U),
+    {
+        let offset = origin.into() - upper.bounds.offset();
+        let overlap = match upper.bounds.translate(offset).intersection(&self.bounds) {
+            Some(overlap) => overlap,
+            None => return 0,
+        };
+        let source = upper.iter_rect(overlap.translate(offset.negate()));
+        let mut count = 0;
+        for ((coord, cell), (_, value)) in self.iter_rect_mut(overlap).zip(source) {
+            combine(coord, cell, value);
+            count += 1;
         }
-        None
+        count
     }
-}
 
-pub struct SelectionIterMut<'a, T, I> {
-    grid: &'a mut VecGrid<T>,
-    coords: I,
-    visited_coords: HashSet<Coord>,
-}
+    /// Returns an iterator over every cell that differs between `self` and
+    /// `other`, in row-major order, yielding the coord followed by the value
+    /// in `self` and the value in `other`.
+    ///
+    /// Both grids must have the same bounds.
+    pub fn diff<'a>(
+        &'a self,
+        other: &'a VecGrid<T>,
+    ) -> Result<impl Iterator<Item = (Coord, &'a T, &'a T)>, GridError>
+    where
+        T: PartialEq,
+    {
+        self.check_bounds_match(other.bounds)?;
+        Ok(self
+            .iter()
+            .zip(other.cells.iter())
+            .filter(|((_coord, a), b)| a != b)
+            .map(|((coord, a), b)| (coord, a, b)))
+    }
 
-impl<'a, T, I> Iterator for SelectionIterMut<'a, T, I>
-where
-    I: Iterator<Item = Coord>,
-{
-    type Item = Result<IterCellMut<'a, T>, GridError>;
+    /// Returns the number of cells that differ between `self` and `other`.
+    ///
+    /// Both grids must have the same bounds.
+    pub fn diff_count(&self, other: &VecGrid<T>) -> Result<usize, GridError>
+    where
+        T: PartialEq,
+    {
+        self.check_bounds_match(other.bounds)?;
+        Ok(self
+            .cells
+            .iter()
+            .zip(other.cells.iter())
+            .filter(|(a, b)| a != b)
+            .count())
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(coord) = self.coords.next() {
-            if self.visited_coords.contains(&coord) {
-                return Some(Err(GridError::AlreadyVisited(coord)));
-            }
-            if let Some(cell) = self.grid.get_mut(coord).map(|cell| cell as *mut T) {
-                // SAFETY: We guarantee that only one mut reference to a cell
-                // will be given out at a time by checking each against a list
-                // of visited cells and only returning those that havent already
-                // visited.
-                // This is likely not actually completely safe, given that I
-                // don't know which cases to look out for.
-                let opt_cell = unsafe { cell.as_mut() };
-                if let Some(cell) = opt_cell {
-                    self.visited_coords.insert(coord);
-                    return Some(Ok((coord, cell)));
-                }
-            }
-            return Some(Err(GridError::OutOfBounds(coord)));
+    /// Multiplies every cell by `factor`.
+    pub fn scale(&mut self, factor: T)
+    where
+        T: Copy + ops::MulAssign,
+    {
+        for cell in self.cells.iter_mut() {
+            *cell *= factor;
         }
-        None
     }
-}
-
-pub struct FloodIter<'a, T> {
-    grid: &'a VecGrid<T>,
-    predicate: Box<dyn Fn(&T) -> bool>,
-    searched_coords: Vec<Coord>,
-    coords_to_search: VecDeque<Coord>,
-}
-
-impl<'a, T> Iterator for FloodIter<'a, T> {
-    type Item = IterCell<'a, T>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        while self.coords_to_search.len() > 0 {
-            let coord = self.coords_to_search.pop_front().unwrap();
-            let is_cell_included = self
-                .grid
-                .get(coord)
-                .and_then(|cell| Some((self.predicate)(cell)))
-                .unwrap_or(false);
 
-            self.searched_coords.push(coord);
+    /// Adds `delta` to every cell.
+    pub fn offset(&mut self, delta: T)
+    where
+        T: Copy + ops::AddAssign,
+    {
+        for cell in self.cells.iter_mut() {
+            *cell += delta;
+        }
+    }
 
-            if !is_cell_included {
-                continue;
+    /// Limits every cell to the range `min..=max`.
+    pub fn clamp_values(&mut self, min: T, max: T)
+    where
+        T: Copy + PartialOrd,
+    {
+        for cell in self.cells.iter_mut() {
+            if *cell < min {
+                *cell = min;
+            } else if *cell > max {
+                *cell = max;
             }
-
-            let neighbor_coords = Neighborhood::new(coord)
-                .iter_ortho()
-                .filter(|&coord| {
-                    !(self.searched_coords.contains(&coord)
-                        || self.coords_to_search.contains(&coord))
-                        && self.grid.bounds.contains(coord)
-                })
-                .collect::<Vec<Coord>>();
-
-            self.coords_to_search.extend(neighbor_coords);
-
-            return Some((coord, self.grid.get(coord).unwrap()));
         }
+    }
 
-        None
+    /// Returns the first cell holding the smallest value, or `None` if the
+    /// grid is empty. Values that can't be compared with themselves, such as
+    /// NaN, are skipped.
+    pub fn min_value(&self) -> Option<IterCell<'_, T>>
+    where
+        T: PartialOrd,
+    {
+        self.extreme_value(|cell, best| cell < best)
     }
-}
 
-impl fmt::Display for VecGrid<String> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let default = &"�".to_owned();
-        for y in self.bounds.y_range() {
-            for x in self.bounds.x_range() {
-                let s = self.get((x, y)).unwrap_or(default);
+    /// Returns the first cell holding the largest value, or `None` if the grid
+    /// is empty. Values that can't be compared with themselves, such as NaN,
+    /// are skipped.
+    pub fn max_value(&self) -> Option<IterCell<'_, T>>
+    where
+        T: PartialOrd,
+    {
+        self.extreme_value(|cell, best| cell > best)
+    }
 
-                if let Err(e) = write!(f, "{}", s) {
-                    return Err(e);
-                }
-            }
-            if let Err(e) = write!(f, "\n") {
-                return Err(e);
-            }
+    /// Returns an iterator over the coordinates of every cell satisfying
+    /// `predicate`, in row-major order.
+    ///
+    /// The grid stays borrowed while the iterator is alive; collect it into a
+    /// `Vec` first to mutate the grid based on the results.
+    pub fn coords_where<'a, F>(&'a self, mut predicate: F) -> impl Iterator<Item = Coord> + 'a
+    where
+        F: FnMut(Coord, &T) -> bool + 'a,
+    {
+        self.iter()
+            .filter(move |&(coord, cell)| predicate(coord, cell))
+            .map(|(coord, _)| coord)
+    }
+
+    /// Returns a grid with the same bounds, marking the cells which satisfy
+    /// `predicate`.
+    pub fn threshold<F>(&self, mut predicate: F) -> VecGrid<bool>
+    where
+        F: FnMut(Coord, &T) -> bool,
+    {
+        VecGrid {
+            cells: self
+                .iter()
+                .map(|(coord, cell)| predicate(coord, cell))
+                .collect(),
+            bounds: self.bounds,
+            layout: PhantomData,
         }
-        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Returns a grid with the same bounds, marking the cells holding at least
+    /// `value`.
+    pub fn threshold_at(&self, value: &T) -> VecGrid<bool>
+    where
+        T: PartialOrd,
+    {
+        self.threshold(|_, cell| cell >= value)
+    }
 
-    #[test]
-    fn bounds_and_dimensions() {
-        let grid = VecGrid::<()>::new(Rect::new((8, 8)));
-        assert_eq!(grid.bounds.width(), 8);
+    /// Calls `f` on every cell where `mask` is `true`, returning the number of
+    /// cells visited.
+    ///
+    /// Both grids must have the same bounds. The mask can be any boolean grid,
+    /// such as a `VecGrid<bool>` or a [`BitGrid`](crate::BitGrid).
+    pub fn apply_masked<M, F>(&mut self, mask: &M, f: F) -> Result<usize, GridError>
+    where
+        M: Grid<bool>,
+        F: FnMut(Coord, &mut T),
+    {
+        self.apply_where_mask_is(true, mask, f)
     }
 
-    #[test]
-    fn out_of_bounds_coords() {
-        let grid = VecGrid::<()>::new(Rect::new((8, 8)));
-        // This would pass `coord_to_index` if there was no bounds check.
-        assert_eq!(grid.get(Coord::new(-1, 4)), None);
+    /// Inverse of [`apply_masked`](VecGrid::apply_masked), calling `f` on every
+    /// cell where `mask` is `false`.
+    pub fn apply_unmasked<M, F>(&mut self, mask: &M, f: F) -> Result<usize, GridError>
+    where
+        M: Grid<bool>,
+        F: FnMut(Coord, &mut T),
+    {
+        self.apply_where_mask_is(false, mask, f)
+    }
+
+    /// Overwrites every cell, in row-major order, with the values from
+    /// `values`.
+    ///
+    /// Returns [`GridError::WrongLength`] if `values` doesn't yield exactly one
+    /// value per cell. Values are written as they arrive, so a short iterator
+    /// leaves the cells after the last value untouched, while the values of a
+    /// long iterator are all consumed to report how many there were.
+    pub fn fill_from<I>(&mut self, values: I) -> Result<(), GridError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self::write_cells(self.cells.iter_mut(), values)
+    }
+
+    /// Like [`fill_from`](VecGrid::fill_from), but only overwrites the cells
+    /// inside `rect`, in row-major order. Returns [`GridError::OutOfBounds`]
+    /// with a corner of `rect` that lies outside the grid, without writing
+    /// anything, if `rect` doesn't fit.
+    pub fn overwrite_region_from<I>(&mut self, rect: Rect, values: I) -> Result<(), GridError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let corner = if self.contains((rect.left, rect.top)) {
+            Coord::new(rect.right - 1, rect.bottom - 1)
+        } else {
+            Coord::new(rect.left, rect.top)
+        };
+        let bounds = self.bounds;
+        let mut view = self.view_mut(rect).ok_or(GridError::OutOfBounds {
+            coord: corner,
+            bounds,
+        })?;
+        Self::write_cells(view.iter_mut().map(|(_, cell)| cell), values)
+    }
+
+    /// Sets every cell equal to `from` to a clone of `to`, returning the number
+    /// of cells changed.
+    ///
+    /// This is named to avoid shadowing the single-cell
+    /// [`Grid::replace`](crate::grid::Grid::replace).
+    pub fn replace_all(&mut self, from: &T, to: T) -> usize
+    where
+        T: Clone + PartialEq,
+    {
+        let mut count = 0;
+        for cell in self.cells.iter_mut().filter(|cell| *cell == from) {
+            *cell = to.clone();
+            count += 1;
+        }
+        count
+    }
+
+    /// Sets every cell to a clone of `value`.
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        self.cells.fill(value);
+    }
+
+    /// Returns the number of cells equal to `value`.
+    pub fn count_value(&self, value: &T) -> usize
+    where
+        T: PartialEq,
+    {
+        self.values().filter(|&cell| cell == value).count()
+    }
+
+    /// Sets every cell satisfying `predicate` to a clone of `to`, returning the
+    /// number of cells changed.
+    pub fn replace_where<F>(&mut self, mut predicate: F, to: T) -> usize
+    where
+        T: Clone,
+        F: FnMut(Coord, &T) -> bool,
+    {
+        let mut count = 0;
+        for (coord, cell) in self.iter_mut() {
+            if predicate(coord, cell) {
+                *cell = to.clone();
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Counts how many cells hold each distinct value.
+    ///
+    /// Each distinct value is cloned once, when it's first encountered.
+    #[cfg(feature = "std")]
+    pub fn value_counts(&self) -> HashMap<T, usize>
+    where
+        T: Eq + Hash + Clone,
+    {
+        let mut counts = HashMap::new();
+        for cell in self.cells.iter() {
+            match counts.get_mut(cell) {
+                Some(count) => *count += 1,
+                None => {
+                    counts.insert(cell.clone(), 1);
+                }
+            }
+        }
+        counts
+    }
+
+    /// Like [`value_counts`](VecGrid::value_counts), but ordered by value.
+    pub fn value_counts_ordered(&self) -> BTreeMap<T, usize>
+    where
+        T: Ord + Clone,
+    {
+        let mut counts = BTreeMap::new();
+        for cell in self.cells.iter() {
+            match counts.get_mut(cell) {
+                Some(count) => *count += 1,
+                None => {
+                    counts.insert(cell.clone(), 1);
+                }
+            }
+        }
+        counts
+    }
+
+    /// Counts how many cells map to each distinct key, which is useful for
+    /// bucketing values that can't be hashed themselves, such as floats.
+    #[cfg(feature = "std")]
+    pub fn counts_by<K, F>(&self, mut key: F) -> HashMap<K, usize>
+    where
+        K: Eq + Hash,
+        F: FnMut(&T) -> K,
+    {
+        let mut counts = HashMap::new();
+        for cell in self.cells.iter() {
+            *counts.entry(key(cell)).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Returns the cell nearest to `coord`, clamping each component into the
+    /// grid bounds. For example, `(-5, 3)` on a 10x10 grid reads `(0, 3)`.
+    ///
+    /// Returns `None` only if the grid has zero area, since there is no cell
+    /// to clamp to.
+    pub fn get_clamped<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        self.get(self.clamp_coord(coord.into())?)
+    }
+
+    /// Samples the grid at a fractional coordinate by interpolating between
+    /// the four surrounding cells, treating each cell's value as lying at the
+    /// center of its integer coordinate. Sampling exactly at an integer
+    /// coordinate returns that cell's value unchanged.
+    ///
+    /// Cells past the edge of the grid are resolved according to `border`.
+    /// Returns `None` if either component isn't finite, or if the grid has
+    /// zero area.
+    #[cfg(feature = "std")]
+    pub fn sample_bilinear(&self, x: f64, y: f64, border: BorderMode) -> Option<f64>
+    where
+        T: Copy + Into<f64>,
+    {
+        if !(x.is_finite() && y.is_finite()) {
+            return None;
+        }
+        let (left, top) = (x.floor(), y.floor());
+        let (x_progress, y_progress) = (x - left, y - top);
+        let corner = Coord::new(left as i32, top as i32);
+        let sample = |offset: Coord| {
+            let coord = corner + offset;
+            let coord = match border {
+                BorderMode::Clamp => self.clamp_coord(coord),
+                BorderMode::Wrap => self.wrap_coord(coord),
+            };
+            coord
+                .and_then(|coord| self.get(coord))
+                .map(|&value| value.into())
+        };
+        // Skipping zero-weight neighbors keeps integer samples exact.
+        let lerp = |from: f64, to: f64, progress: f64| {
+            if progress == 0.0 {
+                from
+            } else {
+                from + (to - from) * progress
+            }
+        };
+
+        let top_edge = lerp(
+            sample(Coord::new(0, 0))?,
+            sample(Coord::new(1, 0))?,
+            x_progress,
+        );
+        let bottom_edge = lerp(
+            sample(Coord::new(0, 1))?,
+            sample(Coord::new(1, 1))?,
+            x_progress,
+        );
+        Some(lerp(top_edge, bottom_edge, y_progress))
+    }
+
+    /// Returns a grid with the same bounds where each cell holds the mean of
+    /// the `(2 * radius + 1)²` window centered on it, with cells past the edge
+    /// replicating the nearest edge cell. A radius of zero (or less) returns
+    /// the values unchanged.
+    ///
+    /// The blur is separable and uses running sums, so its cost doesn't grow
+    /// with the radius.
+    pub fn box_blur(&self, radius: i32) -> VecGrid<f64>
+    where
+        T: Copy + Into<f64>,
+    {
+        let mut output = VecGrid {
+            cells: Vec::new(),
+            bounds: self.bounds,
+            layout: PhantomData,
+        };
+        self.box_blur_into(radius, &mut output, &mut Vec::new());
+        output
+    }
+
+    /// Like [`box_blur`](VecGrid::box_blur), but writes into `output`, using
+    /// `scratch` for the intermediate pass. Both are resized as needed, so
+    /// reusing them across calls avoids allocating.
+    pub fn box_blur_into(&self, radius: i32, output: &mut VecGrid<f64>, scratch: &mut Vec<f64>)
+    where
+        T: Copy + Into<f64>,
+    {
+        /// Writes the mean of each window along one row or column, given its
+        /// first index, the stride between its cells and its length.
+        fn blur_line(
+            input: &[f64],
+            output: &mut [f64],
+            start: usize,
+            stride: usize,
+            len: usize,
+            radius: usize,
+        ) {
+            let sample = |i: isize| input[start + i.clamp(0, len as isize - 1) as usize * stride];
+            let radius = radius as isize;
+            let window = (2 * radius + 1) as f64;
+            let mut sum: f64 = (-radius..=radius).map(sample).sum();
+            for i in 0..len as isize {
+                output[start + i as usize * stride] = sum / window;
+                sum += sample(i + radius + 1) - sample(i - radius);
+            }
+        }
+
+        output.bounds = self.bounds;
+        output.cells.clear();
+        output
+            .cells
+            .extend(self.cells.iter().map(|&value| value.into()));
+        let radius = radius.max(0) as usize;
+        if radius == 0 || output.cells.is_empty() {
+            return;
+        }
+
+        let width = self.bounds.width() as usize;
+        let height = self.bounds.height() as usize;
+        scratch.clear();
+        scratch.resize(output.cells.len(), 0.0);
+        for y in 0..height {
+            blur_line(&output.cells, scratch, y * width, 1, width, radius);
+        }
+        for x in 0..width {
+            blur_line(scratch, &mut output.cells, x, width, height, radius);
+        }
+    }
+
+    /// Returns an iterator over all cells in the grid.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            cells: self.cells.iter(),
+            cursor: RowMajorCursor::new(self.bounds),
+        }
+    }
+
+    /// Returns a mutable iterator over all cells in the grid.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            cells: self.cells.iter_mut(),
+            cursor: RowMajorCursor::new(self.bounds),
+        }
+    }
+
+    /// Returns an iterator over the cells inside `rect`, in row-major order.
+    /// Any part of `rect` outside the grid is ignored.
+    ///
+    /// The rect is clipped to the grid once up front, so unlike passing
+    /// [`Rect::iter`] to [`selection_iter`](VecGrid::selection_iter), no
+    /// bounds checks happen per cell.
+    pub fn iter_rect(&self, rect: Rect) -> impl Iterator<Item = IterCell<'_, T>> {
+        let (rows, columns) = self.clip_rect(rect);
+        let width = self.bounds.width().max(1) as usize;
+        let offset = self.bounds.offset();
+        self.cells
+            .chunks(width)
+            .enumerate()
+            .skip(rows.start)
+            .take(rows.len())
+            .flat_map(move |(y, row)| {
+                let columns = columns.clone();
+                row[columns.clone()]
+                    .iter()
+                    .zip(columns)
+                    .map(move |(cell, x)| (offset + Coord::new(x as i32, y as i32), cell))
+            })
+    }
+
+    /// Mutable version of [`iter_rect`](VecGrid::iter_rect).
+    pub fn iter_rect_mut(&mut self, rect: Rect) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        let (rows, columns) = self.clip_rect(rect);
+        let width = self.bounds.width().max(1) as usize;
+        let offset = self.bounds.offset();
+        self.cells
+            .chunks_mut(width)
+            .enumerate()
+            .skip(rows.start)
+            .take(rows.len())
+            .flat_map(move |(y, row)| {
+                let columns = columns.clone();
+                row[columns.clone()]
+                    .iter_mut()
+                    .zip(columns)
+                    .map(move |(cell, x)| (offset + Coord::new(x as i32, y as i32), cell))
+            })
+    }
+
+    /// Returns an iterator over each row of the grid, from top to bottom.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        GridView::from_grid(self).rows()
+    }
+
+    /// Returns an iterator over the y coordinate and cells of each row, from
+    /// top to bottom, or from bottom to top if `y_up` is true.
+    pub(crate) fn rows_oriented(&self, y_up: bool) -> impl Iterator<Item = (i32, &[T])> {
+        let rows = self.bounds.y_range().zip(self.rows());
+        let (down, up) = if y_up {
+            (None, Some(rows.rev()))
+        } else {
+            (Some(rows), None)
+        };
+        down.into_iter().flatten().chain(up.into_iter().flatten())
+    }
+
+    /// Folds each row of the grid into a single value, from top to bottom.
+    pub fn row_reduce<A, F>(&self, init: A, mut f: F) -> Vec<A>
+    where
+        A: Clone,
+        F: FnMut(A, &T) -> A,
+    {
+        self.rows()
+            .map(|row| row.iter().fold(init.clone(), &mut f))
+            .collect()
+    }
+
+    /// Folds each column of the grid into a single value, from left to right.
+    pub fn column_reduce<A, F>(&self, init: A, mut f: F) -> Vec<A>
+    where
+        A: Clone,
+        F: FnMut(A, &T) -> A,
+    {
+        let width = self.bounds.width().max(0) as usize;
+        (0..width)
+            .map(|x| {
+                self.cells
+                    .iter()
+                    .skip(x)
+                    .step_by(width)
+                    .fold(init.clone(), &mut f)
+            })
+            .collect()
+    }
+
+    /// Returns the sum of each row, from top to bottom.
+    pub fn row_sums(&self) -> Vec<T>
+    where
+        T: Copy + iter::Sum,
+    {
+        self.rows().map(|row| row.iter().copied().sum()).collect()
+    }
+
+    /// Returns the sum of each column, from left to right.
+    pub fn column_sums(&self) -> Vec<T>
+    where
+        T: Copy + iter::Sum,
+    {
+        let width = self.bounds.width().max(0) as usize;
+        (0..width)
+            .map(|x| self.cells.iter().skip(x).step_by(width).copied().sum())
+            .collect()
+    }
+
+    /// Returns how many cells of each row satisfy `predicate`, from top to
+    /// bottom.
+    pub fn row_counts<F>(&self, mut predicate: F) -> Vec<usize>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.row_reduce(0, |count, cell| count + predicate(cell) as usize)
+    }
+
+    /// Returns how many cells of each column satisfy `predicate`, from left to
+    /// right.
+    pub fn column_counts<F>(&self, mut predicate: F) -> Vec<usize>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.column_reduce(0, |count, cell| count + predicate(cell) as usize)
+    }
+
+    /// Returns an iterator over all cells in the grid, column by column.
+    pub fn iter_column_major(&self) -> impl Iterator<Item = IterCell<'_, T>> {
+        self.selection_iter(self.bounds.iter_column_major())
+            .filter_map(Result::ok)
+    }
+
+    /// Returns a mutable iterator over all cells in the grid, column by column.
+    pub fn iter_column_major_mut(&mut self) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        let coords = self.bounds.iter_column_major();
+        self.selection_iter_mut(coords).filter_map(Result::ok)
+    }
+
+    /// Returns an iterator over all cells in the grid, row by row, with every
+    /// other row reversed. See [`Rect::iter_snake`](crate::patterns::Rect::iter_snake).
+    pub fn iter_snake(&self) -> impl Iterator<Item = IterCell<'_, T>> {
+        self.selection_iter(self.bounds.iter_snake())
+            .filter_map(Result::ok)
+    }
+
+    /// Returns a mutable iterator over all cells in the grid, row by row, with
+    /// every other row reversed. See
+    /// [`Rect::iter_snake`](crate::patterns::Rect::iter_snake).
+    pub fn iter_snake_mut(&mut self) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        let coords = self.bounds.iter_snake();
+        self.selection_iter_mut(coords).filter_map(Result::ok)
+    }
+
+    /// Returns an iterator over all cells in the grid, spiraling from the
+    /// outside in. See [`Rect::iter_spiral`](crate::patterns::Rect::iter_spiral).
+    pub fn iter_spiral(&self) -> impl Iterator<Item = IterCell<'_, T>> {
+        self.selection_iter(self.bounds.iter_spiral())
+            .filter_map(Result::ok)
+    }
+
+    /// Returns mutable references to the cells at each of `coords` at once,
+    /// in the same order, like [`slice::get_disjoint_mut`].
+    ///
+    /// Fails with a [`GetManyError`] naming the first coord that's out of
+    /// bounds or repeats an earlier one, since handing out two mutable
+    /// references to the same cell isn't allowed. The coords are compared
+    /// pairwise, which is meant for the handful of cells this is useful for.
+    pub fn get_many_mut<const N: usize>(
+        &mut self,
+        coords: [Coord; N],
+    ) -> Result<[&mut T; N], GetManyError> {
+        let mut indices = [0; N];
+        for (index, &coord) in coords.iter().enumerate() {
+            let error = match self.coord_to_index(coord) {
+                None => GridError::OutOfBounds {
+                    coord,
+                    bounds: self.bounds,
+                },
+                Some(_) if coords[..index].contains(&coord) => GridError::AlreadyVisited(coord),
+                Some(cell) => {
+                    indices[index] = cell;
+                    continue;
+                }
+            };
+            return Err(GetManyError { index, error });
+        }
+        Ok(self.cells.get_disjoint_mut(indices).unwrap())
+    }
+
+    /// Returns a mutable iterator over the cells specified by the coords
+    /// iterator.
+    ///
+    /// Exactly one item is yielded per coord, in the same order. The first
+    /// occurrence of a coord yields the cell, while any later attempt to visit
+    /// the same cell (which would create multiple simultaneous mutable
+    /// references to it) yields a
+    /// [`GridError::AlreadyVisited`](GridError::AlreadyVisited) in place of the
+    /// cell contents. Out of bounds coords always yield a
+    /// [`GridError::OutOfBounds`](GridError::OutOfBounds), however many times
+    /// they occur.
+    pub fn selection_iter_mut<I>(
+        &mut self,
+        coords: I,
+    ) -> impl Iterator<Item = Result<IterCellMut<'_, T>, GridError>>
+    where
+        I: IntoIterator,
+        I::Item: Into<Coord>,
+    {
+        SelectionIterMut {
+            cells: self.cells.as_mut_ptr(),
+            len: self.cells.len(),
+            bounds: self.bounds,
+            coords: coords.into_iter(),
+            visited: BTreeSet::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the cells nearest to each of the coords,
+    /// clamping out of bounds coords to the grid edge as in
+    /// [`get_clamped`](VecGrid::get_clamped). The yielded coord is that of the
+    /// cell actually read.
+    ///
+    /// Exactly one cell is yielded per coord, unless the grid has zero area, in
+    /// which case nothing is yielded.
+    pub fn selection_iter_clamped<I>(&self, coords: I) -> impl Iterator<Item = IterCell<'_, T>>
+    where
+        I: IntoIterator,
+        I::Item: Into<Coord>,
+    {
+        coords.into_iter().filter_map(move |coord| {
+            let clamped = self.clamp_coord(coord.into())?;
+            Some((clamped, self.get(clamped)?))
+        })
+    }
+
+    /// Shrinks the grid to the cells covered by `rect`, keeping their
+    /// coordinates.
+    ///
+    /// `rect` must be non-empty and lie entirely within the grid, otherwise a
+    /// [`GridError::OutOfBounds`] naming one of its corners is returned and the
+    /// grid is left unchanged.
+    pub fn crop(&mut self, rect: Rect) -> Result<(), GridError> {
+        self.check_region(rect)?;
+        let (rows, columns) = self.clip_rect(rect);
+        let width = self.bounds.width() as usize;
+        let mut index = 0;
+        self.cells.retain(|_| {
+            let keep = rows.contains(&(index / width)) && columns.contains(&(index % width));
+            index += 1;
+            keep
+        });
+        self.bounds = rect;
+        Ok(())
+    }
+
+    /// Changes the grid bounds to `bounds`, keeping the cells covered by both
+    /// the old and new bounds at their coordinates and filling the rest with
+    /// clones of `fill`.
+    pub fn resize(&mut self, bounds: Rect, fill: T)
+    where
+        T: Clone,
+    {
+        let old = mem::replace(self, VecGrid::filled(bounds, fill));
+        for (coord, cell) in old {
+            if let Some(slot) = self.get_mut(coord) {
+                *slot = cell;
+            }
+        }
+    }
+
+    /// Returns a copy of the grid with a margin of `margin` cells added on
+    /// every side, filled according to `mode`.
+    ///
+    /// The original cells keep their coordinates, so the output bounds are
+    /// the source bounds expanded by `margin`, and the original content lies
+    /// at `bounds.expand(-margin)` in them.
+    ///
+    /// Returns [`GridError::InvalidMargin`] if `margin` is negative, or
+    /// [`GridError::Empty`] if the grid has zero area and `mode` needs cells to
+    /// copy into a non-empty margin.
+    pub fn padded(&self, margin: i32, mode: PadMode<T>) -> Result<VecGrid<T>, GridError>
+    where
+        T: Clone,
+    {
+        if margin < 0 {
+            return Err(GridError::InvalidMargin(margin));
+        }
+        let bounds = self.bounds.expand(margin);
+        let grid = match mode {
+            PadMode::Constant(value) => {
+                let mut grid = VecGrid::filled(bounds, value);
+                grid.blit(self);
+                grid
+            }
+            PadMode::ClampEdge | PadMode::Wrap if margin > 0 && self.cells.is_empty() => {
+                return Err(GridError::Empty);
+            }
+            PadMode::ClampEdge => VecGrid::with_generator(bounds, |coord: Coord| {
+                self.get(self.clamp_coord(coord).unwrap()).unwrap().clone()
+            }),
+            PadMode::Wrap => VecGrid::with_generator(bounds, |coord: Coord| {
+                self.get(self.wrap_coord(coord).unwrap()).unwrap().clone()
+            }),
+        };
+        Ok(grid)
+    }
+
+    /// Returns a copy of the cells left after removing a margin of `margin`
+    /// cells from every side, undoing [`padded`](VecGrid::padded).
+    ///
+    /// Returns [`GridError::InvalidMargin`] if `margin` is negative or there's
+    /// less than twice `margin` cells across either axis.
+    pub fn unpadded(&self, margin: i32) -> Result<VecGrid<T>, GridError>
+    where
+        T: Clone,
+    {
+        let dimensions = self.bounds.dimensions();
+        if margin < 0 || dimensions.x.min(dimensions.y) < margin * 2 {
+            return Err(GridError::InvalidMargin(margin));
+        }
+        let bounds = self.bounds.expand(-margin);
+        let cells = self
+            .iter_rect(bounds)
+            .map(|(_, cell)| cell.clone())
+            .collect();
+        Ok(VecGrid {
+            cells,
+            bounds,
+            layout: PhantomData,
+        })
+    }
+
+    /// Appends `row` below the bottom row of the grid.
+    ///
+    /// The row must be exactly as long as the grid is wide, except when the
+    /// grid is empty, in which case the row establishes the grid width. An
+    /// empty grid that is tall but has no width, such as `0×5`, becomes one
+    /// row tall.
+    pub fn push_row(&mut self, row: Vec<T>) -> Result<(), GridError> {
+        if self.cells.is_empty() {
+            self.bounds.bottom = self.bounds.top;
+            self.bounds.right = self.bounds.left + row.len() as i32;
+        } else if row.len() != self.bounds.width() as usize {
+            return Err(GridError::WrongLength {
+                expected: self.bounds.width() as usize,
+                received: row.len(),
+            });
+        }
+        self.cells.extend(row);
+        self.bounds.bottom += 1;
+        Ok(())
+    }
+
+    /// Appends `column` to the right of the rightmost column of the grid.
+    ///
+    /// The column must be exactly as long as the grid is tall, except when the
+    /// grid is empty, in which case the column establishes the grid height. An
+    /// empty grid that is wide but has no height, such as `5×0`, becomes one
+    /// column wide.
+    pub fn push_column(&mut self, column: Vec<T>) -> Result<(), GridError> {
+        if self.cells.is_empty() {
+            self.bounds.right = self.bounds.left;
+            self.bounds.bottom = self.bounds.top + column.len() as i32;
+        } else if column.len() != self.bounds.height() as usize {
+            return Err(GridError::WrongLength {
+                expected: self.bounds.height() as usize,
+                received: column.len(),
+            });
+        }
+        // Rebuild the storage in a single pass, since every row gets longer.
+        let width = self.bounds.width() as usize;
+        let mut cells = Vec::with_capacity(self.cells.len() + column.len());
+        let mut old_cells = self.cells.drain(..);
+        for value in column {
+            cells.extend(old_cells.by_ref().take(width));
+            cells.push(value);
+        }
+        drop(old_cells);
+        self.cells = cells;
+        self.bounds.right += 1;
+        Ok(())
+    }
+
+    /// Returns a mutable iterator over the in-bounds cells of the orthogonal
+    /// and diagonal (Moore) neighborhood of `coord`.
+    ///
+    /// `coord` itself doesn't need to be in bounds.
+    pub fn neighbors_mut<C: Into<Coord>>(
+        &mut self,
+        coord: C,
+    ) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        self.selection_iter_mut(Connectivity::Eight.neighbors(coord))
+            .filter_map(Result::ok)
+    }
+
+    /// Returns an iterator over every cell on the outer edge of the grid,
+    /// each exactly once, in the order given by
+    /// [`Rect::iter_border`](crate::patterns::Rect::iter_border).
+    pub fn border_iter(&self) -> impl Iterator<Item = IterCell<'_, T>> {
+        self.selection_iter(self.bounds.iter_border())
+            .filter_map(Result::ok)
+    }
+
+    /// Returns a mutable iterator over every cell on the outer edge of the
+    /// grid, each exactly once, in the order given by
+    /// [`Rect::iter_border`](crate::patterns::Rect::iter_border).
+    pub fn border_iter_mut(&mut self) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        let border = self.bounds.iter_border();
+        self.selection_iter_mut(border).filter_map(Result::ok)
+    }
+
+    /// Moves every cell's contents by `offset`, so that the contents of
+    /// `(x, y)` end up at `(x + offset.x, y + offset.y)`. See [`ShiftMode`] for
+    /// what happens to the contents pushed past the edge.
+    ///
+    /// Under [`ShiftMode::Wrap`], shifting by a multiple of the dimensions is
+    /// a no-op, while under [`ShiftMode::Fill`], shifting by at least the
+    /// dimensions fills the whole grid.
+    pub fn shift<C: Into<Coord>>(&mut self, offset: C, mode: ShiftMode<T>)
+    where
+        T: Clone,
+    {
+        let offset = offset.into();
+        let width = self.bounds.width().max(0) as usize;
+        let height = self.bounds.height().max(0) as usize;
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        match mode {
+            ShiftMode::Wrap => {
+                let dx = offset.x.rem_euclid(width as i32) as usize;
+                let dy = offset.y.rem_euclid(height as i32) as usize;
+                self.cells.rotate_right(dy * width);
+                for row in self.cells.chunks_mut(width) {
+                    row.rotate_right(dx);
+                }
+            }
+            ShiftMode::Fill(value) => {
+                if offset.x.unsigned_abs() as usize >= width
+                    || offset.y.unsigned_abs() as usize >= height
+                {
+                    self.cells.fill(value);
+                    return;
+                }
+                let dy = offset.y.unsigned_abs() as usize * width;
+                if offset.y > 0 {
+                    self.cells.rotate_right(dy);
+                    self.cells[..dy].fill(value.clone());
+                } else {
+                    self.cells.rotate_left(dy);
+                    let len = self.cells.len();
+                    self.cells[(len - dy)..].fill(value.clone());
+                }
+                let dx = offset.x.unsigned_abs() as usize;
+                for row in self.cells.chunks_mut(width) {
+                    if offset.x > 0 {
+                        row.rotate_right(dx);
+                        row[..dx].fill(value.clone());
+                    } else {
+                        row.rotate_left(dx);
+                        row[(width - dx)..].fill(value.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rotates the contents of the square region `rect` a quarter turn
+    /// clockwise (with y growing downwards), in place.
+    ///
+    /// Returns [`GridError::NotSquare`] if `rect` isn't square, or
+    /// [`GridError::OutOfBounds`] with the first corner of `rect` outside the
+    /// grid, leaving the grid untouched either way.
+    pub fn rotate_region_cw(&mut self, rect: Rect) -> Result<(), GridError> {
+        if rect.width() != rect.height() {
+            return Err(GridError::NotSquare(rect));
+        }
+        self.check_region(rect)?;
+        let size = rect.width();
+        let bounds = self.bounds;
+        let index = |x: i32, y: i32| {
+            let coord = Coord::new(rect.left + x, rect.top + y);
+            Self::coord_to_index_with_bounds(bounds, coord).unwrap()
+        };
+        for layer in 0..size / 2 {
+            let last = size - 1 - layer;
+            for i in layer..last {
+                let top = index(i, layer);
+                let right = index(last, i);
+                let bottom = index(size - 1 - i, last);
+                let left = index(layer, size - 1 - i);
+                // Cycles the four cells: top to right to bottom to left.
+                self.cells.swap(top, right);
+                self.cells.swap(top, bottom);
+                self.cells.swap(top, left);
+            }
+        }
+        Ok(())
+    }
+
+    /// Mirrors the contents of `rect` left to right, in place.
+    ///
+    /// Returns [`GridError::OutOfBounds`] with the first corner of `rect`
+    /// outside the grid, leaving the grid untouched.
+    pub fn mirror_region_horizontal(&mut self, rect: Rect) -> Result<(), GridError> {
+        self.check_region(rect)?;
+        for y in rect.y_range() {
+            let start = self.coord_to_index((rect.left, y)).unwrap();
+            self.cells[start..(start + rect.width() as usize)].reverse();
+        }
+        Ok(())
+    }
+
+    /// Mirrors the contents of `rect` top to bottom, in place.
+    ///
+    /// Returns [`GridError::OutOfBounds`] with the first corner of `rect`
+    /// outside the grid, leaving the grid untouched.
+    pub fn mirror_region_vertical(&mut self, rect: Rect) -> Result<(), GridError> {
+        self.check_region(rect)?;
+        let width = rect.width() as usize;
+        for offset in 0..(rect.height() / 2) {
+            let upper = self.coord_to_index((rect.left, rect.top + offset)).unwrap();
+            let lower = self
+                .coord_to_index((rect.left, rect.bottom - 1 - offset))
+                .unwrap();
+            // The upper row always comes first, so splitting between them
+            // lets both be borrowed at once.
+            let (head, tail) = self.cells.split_at_mut(lower);
+            head[upper..(upper + width)].swap_with_slice(&mut tail[..width]);
+        }
+        Ok(())
+    }
+
+    /// Swaps the contents of rows `a` and `b`. Swapping a row with itself is a
+    /// no-op.
+    pub fn swap_rows(&mut self, a: i32, b: i32) -> Result<(), GridError> {
+        let a_range = self.row_index_range(a)?;
+        let b_range = self.row_index_range(b)?;
+        if a == b {
+            return Ok(());
+        }
+        let (low, high) = if a < b {
+            (a_range, b_range)
+        } else {
+            (b_range, a_range)
+        };
+        let (head, tail) = self.cells.split_at_mut(high.start);
+        head[low].swap_with_slice(&mut tail[..high.len()]);
+        Ok(())
+    }
+
+    /// Swaps the contents of columns `a` and `b`. Swapping a column with
+    /// itself is a no-op.
+    pub fn swap_columns(&mut self, a: i32, b: i32) -> Result<(), GridError> {
+        for column in [a, b] {
+            if !self.bounds.x_range().contains(&column) {
+                return Err(GridError::ColumnOutOfBounds {
+                    column,
+                    columns: self.bounds.x_range(),
+                });
+            }
+        }
+        if a == b {
+            return Ok(());
+        }
+        let width = self.bounds.width() as usize;
+        let a_offset = (a - self.bounds.left) as usize;
+        let b_offset = (b - self.bounds.left) as usize;
+        for row_start in (0..self.cells.len()).step_by(width) {
+            self.cells.swap(row_start + a_offset, row_start + b_offset);
+        }
+        Ok(())
+    }
+
+    /// Traces the boundaries between cells satisfying `inside` and the rest
+    /// using marching squares, returning one closed loop of points per
+    /// boundary. Points lie halfway between the centers of adjacent cells, and
+    /// each loop is implicitly closed from its last point back to its first.
+    ///
+    /// Inside cells always lie to the left of the direction of travel (with y
+    /// growing downwards), so outer boundaries run counter-clockwise and the
+    /// boundaries of holes run clockwise. Cells outside the grid count as
+    /// outside, so every loop closes. In the ambiguous saddle case, where two
+    /// inside cells only touch diagonally, they're traced as separate regions.
+    pub fn contours<F>(&self, inside: F) -> Vec<Vec<(f32, f32)>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let is_inside = |coord: (i32, i32)| self.get(coord).is_some_and(&inside);
+
+        // Points are kept in doubled coordinates so they can be compared
+        // exactly.
+        let mut next_points = BTreeMap::new();
+        let mut starts = Vec::new();
+        for y in (self.bounds.top - 1)..self.bounds.bottom {
+            for x in (self.bounds.left - 1)..self.bounds.right {
+                // The square's corners in clockwise order, along with the
+                // midpoints of the edges leading clockwise away from each.
+                let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)].map(is_inside);
+                let midpoints = [
+                    (2 * x + 1, 2 * y),
+                    (2 * x + 2, 2 * y + 1),
+                    (2 * x + 1, 2 * y + 2),
+                    (2 * x, 2 * y + 1),
+                ];
+                let enters = |edge: usize| !corners[edge] && corners[(edge + 1) % 4];
+                let exits = |edge: usize| corners[edge] && !corners[(edge + 1) % 4];
+                for edge in (0..4).filter(|&edge| enters(edge)) {
+                    // Pairing each entry with the next exit clockwise is what
+                    // keeps diagonal saddles apart.
+                    let exit = (1..4)
+                        .map(|offset| (edge + offset) % 4)
+                        .find(|&edge| exits(edge));
+                    next_points.insert(midpoints[edge], midpoints[exit.unwrap()]);
+                    starts.push(midpoints[edge]);
+                }
+            }
+        }
+
+        let mut contours = Vec::new();
+        for start in starts {
+            let mut contour = Vec::new();
+            let mut point = start;
+            while let Some(next_point) = next_points.remove(&point) {
+                contour.push((point.0 as f32 / 2.0, point.1 as f32 / 2.0));
+                point = next_point;
+            }
+            if !contour.is_empty() {
+                contours.push(contour);
+            }
+        }
+        contours
+    }
+
+    /// Returns an iterator beginning from `starting_coord` and continuing
+    /// through all recursively adjacent coords that satisfy the `predicate`. In
+    /// other words, this iterates through the cells according to a flood fill
+    /// algorithm.
+    ///
+    /// Since there is no `mut` version of this iterator (which would require
+    /// simultaneous mutable and shared references to most of the cells), the
+    /// resulting iterator can be collected and then passed into
+    /// [`Grid::selection_iter_mut`](crate::grid::Grid::selection_iter_mut) to
+    /// gain access to mutable cell contents.
+    pub fn flood_iter<C: Into<Coord>>(
+        &self,
+        starting_coord: C,
+        predicate: impl Fn(&T) -> bool + 'static,
+    ) -> impl Iterator<Item = IterCell<'_, T>> {
+        let mut coords_to_search = VecDeque::new();
+        coords_to_search.push_back(starting_coord.into());
+
+        FloodIter {
+            grid: self,
+            predicate: Box::new(predicate),
+            searched_coords: vec![],
+            coords_to_search,
+        }
+    }
+
+    /// Returns a grid with the same bounds marking every cell reachable from
+    /// `start` by moving between adjacent cells satisfying `passable`,
+    /// including `start` itself. If `start` is out of bounds or impassable,
+    /// nothing is marked.
+    ///
+    /// Cells are adjacent when one lies in the other's `neighborhood`, which
+    /// can be a [`Connectivity`] or any [`Neighborhood`].
+    pub fn reachable_from<C, N, F>(&self, start: C, neighborhood: N, passable: F) -> VecGrid<bool>
+    where
+        C: Into<Coord>,
+        N: AsRef<Neighborhood>,
+        F: Fn(Coord, &T) -> bool,
+    {
+        let (start, neighborhood) = (start.into(), neighborhood.as_ref());
+        // Spans can only be filled a row at a time when every orthogonal
+        // neighbor is adjacent, and reach past the span only diagonally.
+        let reach = if neighborhood.is_von_neumann() {
+            0
+        } else if neighborhood.is_moore() {
+            1
+        } else {
+            let mut region = VecGrid::filled(self.bounds, false);
+            let mut visited = VecGrid::filled(self.bounds, false);
+            self.flood_region(start, neighborhood, passable, &mut visited, |coord| {
+                region.set(coord, true);
+            });
+            return region;
+        };
+        self.scanline_region(start, reach, passable)
+    }
+
+    /// Sets the region of cells satisfying `predicate` that contains `start`
+    /// to clones of `value`, returning the number of cells changed. Nothing is
+    /// changed if `start` is out of bounds or doesn't satisfy `predicate`.
+    pub fn flood_fill<C, N, F>(
+        &mut self,
+        start: C,
+        neighborhood: N,
+        predicate: F,
+        value: T,
+    ) -> usize
+    where
+        C: Into<Coord>,
+        N: AsRef<Neighborhood>,
+        F: Fn(&T) -> bool,
+        T: Clone,
+    {
+        let region = self.reachable_from(start, neighborhood, |_, cell| predicate(cell));
+        self.apply_masked(&region, |_, cell| *cell = value.clone())
+            .unwrap()
+    }
+
+    /// Labels each region of adjacent cells satisfying `in_region` with a
+    /// distinct index, numbered from 0 in the row-major order of each
+    /// region's first cell. Cells outside every region are labeled `None`.
+    pub fn connected_components<N, F>(
+        &self,
+        neighborhood: N,
+        in_region: F,
+    ) -> VecGrid<Option<usize>>
+    where
+        N: AsRef<Neighborhood>,
+        F: Fn(&T) -> bool,
+    {
+        let neighborhood = neighborhood.as_ref();
+        let mut labels = VecGrid::filled(self.bounds, None);
+        let mut visited = VecGrid::filled(self.bounds, false);
+        let mut count = 0;
+        for (coord, cell) in self.iter() {
+            if visited.get(coord) == Some(&true) || !in_region(cell) {
+                continue;
+            }
+            self.flood_region(
+                coord,
+                neighborhood,
+                |_, cell| in_region(cell),
+                &mut visited,
+                |coord| {
+                    labels.set(coord, Some(count));
+                },
+            );
+            count += 1;
+        }
+        labels
+    }
+
+    /// Marks the region reachable from `start` through cells satisfying
+    /// `include`, filling whole horizontal spans at a time and only queueing
+    /// one seed per run of fillable cells in the rows above and below, which
+    /// keeps the frontier far smaller than a per-cell search. `reach` is how
+    /// far spans reach sideways into the next row: 0 for four connected
+    /// regions, and 1 for eight connected ones.
+    fn scanline_region<F>(&self, start: Coord, reach: i32, include: F) -> VecGrid<bool>
+    where
+        F: Fn(Coord, &T) -> bool,
+    {
+        let mut region = VecGrid::filled(self.bounds, false);
+        let fillable = |coord: Coord, region: &VecGrid<bool>| {
+            region.get(coord) == Some(&false) && include(coord, self.get(coord).unwrap())
+        };
+        let mut seeds = vec![start];
+        while let Some(seed) = seeds.pop() {
+            if !fillable(seed, &region) {
+                continue;
+            }
+            let y = seed.y;
+            let mut left = seed.x;
+            while fillable(Coord::new(left - 1, y), &region) {
+                left -= 1;
+            }
+            let mut right = seed.x;
+            while fillable(Coord::new(right + 1, y), &region) {
+                right += 1;
+            }
+            for x in left..=right {
+                region.set((x, y), true);
+            }
+
+            for next_y in [y - 1, y + 1] {
+                let mut in_run = false;
+                for x in (left - reach)..=(right + reach) {
+                    let coord = Coord::new(x, next_y);
+                    if fillable(coord, &region) {
+                        if !in_run {
+                            seeds.push(coord);
+                        }
+                        in_run = true;
+                    } else {
+                        in_run = false;
+                    }
+                }
+            }
+        }
+        region
+    }
+
+    /// Traces the outer boundary of the region of cells satisfying
+    /// `in_region` using Moore-neighbor tracing, returning the boundary cells
+    /// in order around the region, clockwise with y growing downwards. The
+    /// loop is implicitly closed: the last cell is adjacent to the first.
+    ///
+    /// Tracing begins from the region's edge directly left of `start`, which
+    /// is always the first cell returned. Cells outside the grid are outside
+    /// the region. A single-cell region traces to just that cell, and cells
+    /// the perimeter passes through twice, such as the base of a one cell
+    /// wide protrusion, appear twice. Returns `None` if `start` isn't in the
+    /// region.
+    pub fn trace_boundary<C, F>(&self, start: C, in_region: F) -> Option<Vec<Coord>>
+    where
+        C: Into<Coord>,
+        F: Fn(Coord, &T) -> bool,
+    {
+        // Clockwise from the west, with y growing downwards.
+        const CLOCKWISE: [Coord; 8] = [
+            Coord::new(-1, 0),
+            Coord::new(-1, -1),
+            Coord::new(0, -1),
+            Coord::new(1, -1),
+            Coord::new(1, 0),
+            Coord::new(1, 1),
+            Coord::new(0, 1),
+            Coord::new(-1, 1),
+        ];
+        let is_inside = |coord: Coord| self.get(coord).is_some_and(|cell| in_region(coord, cell));
+        // Moves to the next boundary cell clockwise around `coord`, given the
+        // direction of the last outside cell examined, returning the new cell
+        // along with the direction of that outside cell relative to it.
+        let step = |coord: Coord, backtrack: usize| {
+            (1..8)
+                .map(|turn| (backtrack + turn) % 8)
+                .find_map(|direction| {
+                    let next = coord + CLOCKWISE[direction];
+                    if !is_inside(next) {
+                        return None;
+                    }
+                    let outside = coord + CLOCKWISE[(direction + 7) % 8];
+                    let backtrack = CLOCKWISE
+                        .iter()
+                        .position(|&offset| next + offset == outside)?;
+                    Some((next, backtrack))
+                })
+        };
+
+        let mut start = start.into();
+        if !is_inside(start) {
+            return None;
+        }
+        while is_inside(start + CLOCKWISE[0]) {
+            start += CLOCKWISE[0];
+        }
+
+        let mut path = vec![start];
+        let first = match step(start, 0) {
+            Some(first) => first,
+            None => return Some(path),
+        };
+        // The walk is over once it's about to repeat its very first move.
+        let mut state = first;
+        loop {
+            path.push(state.0);
+            state = step(state.0, state.1).unwrap();
+            if state == first {
+                break;
+            }
+        }
+        path.pop();
+        Some(path)
+    }
+
+    /// Returns the smallest rect containing every cell satisfying
+    /// `predicate`, or `None` if no cell does. Each edge of the rect touches
+    /// at least one matching cell.
+    ///
+    /// The top and bottom rows are found by scanning inwards from each end,
+    /// and each row in between is only scanned up to the left and right edges
+    /// found so far.
+    pub fn bounding_box<F>(&self, mut predicate: F) -> Option<Rect>
+    where
+        F: FnMut(Coord, &T) -> bool,
+    {
+        let width = self.bounds.width().max(0) as usize;
+        let height = self.bounds.height().max(0) as usize;
+        let offset = self.bounds.offset();
+        let mut matches = |x: usize, y: usize| {
+            let coord = offset + Coord::new(x as i32, y as i32);
+            predicate(coord, &self.cells[y * width + x])
+        };
+
+        let top = (0..height).find(|&y| (0..width).any(|x| matches(x, y)))?;
+        let bottom = (top..height)
+            .rev()
+            .find(|&y| (0..width).any(|x| matches(x, y)))?;
+        let mut left = width;
+        let mut right = 0;
+        for y in top..=bottom {
+            if let Some(x) = (0..left).find(|&x| matches(x, y)) {
+                left = x;
+            }
+            if let Some(x) = (right.max(left)..width).rev().find(|&x| matches(x, y)) {
+                right = x;
+            }
+        }
+        Some(Rect::with_corners(
+            offset + Coord::new(left as i32, top as i32),
+            offset + Coord::new(right as i32 + 1, bottom as i32 + 1),
+        ))
+    }
+
+    /// Returns a rect of the largest possible area whose cells all satisfy
+    /// `predicate`, or `None` if no cell does. Among rects of equal area, the
+    /// one whose bottom edge is highest wins, then the leftmost.
+    ///
+    /// This treats each row as the base of a histogram of matching cells above
+    /// it and finds the largest rectangle under each histogram with a stack,
+    /// visiting every cell a constant number of times.
+    pub fn largest_rect<F>(&self, predicate: F) -> Option<Rect>
+    where
+        F: Fn(Coord, &T) -> bool,
+    {
+        let width = self.bounds.width().max(0) as usize;
+        let mut heights = vec![0; width];
+        let mut best: Option<Rect> = None;
+        // Columns whose heights are still increasing, with where each run of
+        // at least that height started.
+        let mut stack: Vec<(usize, i32)> = Vec::with_capacity(width + 1);
+        for (y, row) in self.bounds.y_range().zip(self.rows()) {
+            for (x, (height, cell)) in heights.iter_mut().zip(row).enumerate() {
+                let coord = Coord::new(self.bounds.left + x as i32, y);
+                *height = if predicate(coord, cell) {
+                    *height + 1
+                } else {
+                    0
+                };
+            }
+
+            stack.clear();
+            for x in 0..=width {
+                let height = heights.get(x).copied().unwrap_or(0);
+                let mut start = x;
+                while let Some(&(run_start, run_height)) = stack.last() {
+                    if run_height < height {
+                        break;
+                    }
+                    stack.pop();
+                    let left = self.bounds.left + run_start as i32;
+                    let rect = Rect::with_corners(
+                        (left, y + 1 - run_height),
+                        (left + (x - run_start) as i32, y + 1),
+                    );
+                    let is_better = match best {
+                        Some(best) => {
+                            rect.area() > best.area()
+                                || (rect.area() == best.area()
+                                    && rect.bottom == best.bottom
+                                    && rect.left < best.left)
+                        }
+                        None => rect.area() > 0,
+                    };
+                    if is_better {
+                        best = Some(rect);
+                    }
+                    start = run_start;
+                }
+                stack.push((start, height));
+            }
+        }
+        best
+    }
+
+    /// Covers every cell satisfying `predicate` with disjoint rects, chosen
+    /// greedily: starting from each uncovered matching cell in row-major
+    /// order, a rect is grown as far right as possible, then as far down as
+    /// its full width allows. The result isn't necessarily the smallest
+    /// possible cover.
+    pub fn decompose_rects<F>(&self, predicate: F) -> Vec<Rect>
+    where
+        F: Fn(Coord, &T) -> bool,
+    {
+        let mut remaining = self.threshold(predicate);
+        let is_remaining =
+            |remaining: &VecGrid<bool>, x: i32, y: i32| remaining.get((x, y)) == Some(&true);
+        let mut rects = Vec::new();
+        for y in self.bounds.y_range() {
+            for x in self.bounds.x_range() {
+                if !is_remaining(&remaining, x, y) {
+                    continue;
+                }
+                let mut right = x + 1;
+                while is_remaining(&remaining, right, y) {
+                    right += 1;
+                }
+                let mut bottom = y + 1;
+                while (x..right).all(|x| is_remaining(&remaining, x, bottom)) {
+                    bottom += 1;
+                }
+                let rect = Rect::with_corners((x, y), (right, bottom));
+                for coord in rect.iter() {
+                    remaining.set(coord, false);
+                }
+                rects.push(rect);
+            }
+        }
+        rects
+    }
+
+    /// Breadth-first search from `start` through cells satisfying `include`,
+    /// skipping and marking cells in `visited`, which must share our bounds.
+    fn flood_region<F, V>(
+        &self,
+        start: Coord,
+        neighborhood: &Neighborhood,
+        include: F,
+        visited: &mut VecGrid<bool>,
+        mut visit: V,
+    ) where
+        F: Fn(Coord, &T) -> bool,
+        V: FnMut(Coord),
+    {
+        let mut frontier = VecDeque::new();
+        let mut try_push = |coord: Coord, frontier: &mut VecDeque<Coord>| {
+            if let (Some(cell), Some(seen)) = (self.get(coord), visited.get_mut(coord)) {
+                if !*seen && include(coord, cell) {
+                    *seen = true;
+                    frontier.push_back(coord);
+                }
+            }
+        };
+        try_push(start, &mut frontier);
+        while let Some(coord) = frontier.pop_front() {
+            visit(coord);
+            for neighbor in neighborhood.iter_around(coord) {
+                try_push(neighbor, &mut frontier);
+            }
+        }
+    }
+
+    fn apply_where_mask_is<M, F>(
+        &mut self,
+        selected: bool,
+        mask: &M,
+        mut f: F,
+    ) -> Result<usize, GridError>
+    where
+        M: Grid<bool>,
+        F: FnMut(Coord, &mut T),
+    {
+        self.check_bounds_match(mask.bounds())?;
+        let mut count = 0;
+        for (coord, cell) in self.iter_mut() {
+            if mask.get(coord) == Some(&selected) {
+                f(coord, cell);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Writes `values` into `cells` one by one, checking that their lengths
+    /// match.
+    fn write_cells<'a, I>(
+        cells: impl Iterator<Item = &'a mut T>,
+        values: I,
+    ) -> Result<(), GridError>
+    where
+        T: 'a,
+        I: IntoIterator<Item = T>,
+    {
+        let mut values = values.into_iter();
+        let mut expected = 0;
+        let mut received = 0;
+        for cell in cells {
+            expected += 1;
+            if let Some(value) = values.next() {
+                *cell = value;
+                received += 1;
+            }
+        }
+        received += values.count();
+        if received != expected {
+            return Err(GridError::WrongLength { expected, received });
+        }
+        Ok(())
+    }
+
+    /// Returns the output bounds for resampling to `dimensions`, along with the
+    /// number of source cells per output cell along each axis.
+    fn resample_bounds(&self, dimensions: Coord) -> Result<(Rect, (f64, f64)), GridError> {
+        let bounds = Rect::new(dimensions).translate(self.bounds.offset());
+        if dimensions.x < 0 || dimensions.y < 0 || (self.bounds.area() <= 0 && bounds.area() > 0) {
+            return Err(GridError::InvalidScale(dimensions));
+        }
+        let source = self.bounds.dimensions();
+        let scale = (
+            source.x as f64 / dimensions.x.max(1) as f64,
+            source.y as f64 / dimensions.y.max(1) as f64,
+        );
+        Ok((bounds, scale))
+    }
+
+    /// Returns the local rows and columns of the part of `rect` inside the
+    /// grid, which are empty if there is no such part.
+    fn clip_rect(&self, rect: Rect) -> (Range<usize>, Range<usize>) {
+        match rect.intersection(&self.bounds) {
+            Some(clipped) => {
+                let local = clipped.translate(self.bounds.offset().negate());
+                (
+                    local.top as usize..local.bottom as usize,
+                    local.left as usize..local.right as usize,
+                )
+            }
+            None => (0..0, 0..0),
+        }
+    }
+
+    /// Checks that `rect` is non-empty and lies entirely within the grid.
+    fn check_region(&self, rect: Rect) -> Result<(), GridError> {
+        Self::check_region_with_bounds(self.bounds, rect)
+    }
+
+    pub(crate) fn check_region_with_bounds(bounds: Rect, rect: Rect) -> Result<(), GridError> {
+        for &corner in [
+            Coord::new(rect.left, rect.top),
+            Coord::new(rect.right - 1, rect.bottom - 1),
+        ]
+        .iter()
+        {
+            if !bounds.contains(corner) {
+                return Err(GridError::OutOfBounds {
+                    coord: corner,
+                    bounds,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the first comparable cell that no later cell is `better` than.
+    fn extreme_value<F>(&self, better: F) -> Option<IterCell<'_, T>>
+    where
+        T: PartialOrd,
+        F: Fn(&T, &T) -> bool,
+    {
+        self.iter()
+            .filter(|(_, cell)| cell.partial_cmp(cell).is_some())
+            .fold(None, |best, (coord, cell)| match best {
+                Some((_, best_cell)) if !better(cell, best_cell) => best,
+                _ => Some((coord, cell)),
+            })
+    }
+
+    /// Returns the range of linear Vec indices covered by row `y`.
+    fn row_index_range(&self, y: i32) -> Result<Range<usize>, GridError> {
+        if !self.bounds.y_range().contains(&y) {
+            return Err(GridError::RowOutOfBounds {
+                row: y,
+                rows: self.bounds.y_range(),
+            });
+        }
+        let width = self.bounds.width() as usize;
+        let start = (y - self.bounds.top) as usize * width;
+        Ok(start..start + width)
+    }
+
+    /// Returns an error if `bounds` differs from the grid bounds.
+    fn check_bounds_match(&self, bounds: Rect) -> Result<(), GridError> {
+        if self.bounds != bounds {
+            return Err(GridError::BoundsMismatch {
+                expected: self.bounds,
+                received: bounds,
+            });
+        }
+        Ok(())
+    }
+
+    /// Clamps `coord` to the nearest coordinate within the grid bounds.
+    fn clamp_coord(&self, coord: Coord) -> Option<Coord> {
+        if self.bounds.area() <= 0 {
+            return None;
+        }
+        Some(Coord::new(
+            coord.x.clamp(self.bounds.left, self.bounds.right - 1),
+            coord.y.clamp(self.bounds.top, self.bounds.bottom - 1),
+        ))
+    }
+
+    /// Wraps a coordinate into the grid bounds, as if the grid were a torus.
+    pub(crate) fn wrap_coord(&self, coord: Coord) -> Option<Coord> {
+        Self::wrap_coord_with_bounds(self.bounds, coord)
+    }
+
+    pub(crate) fn wrap_coord_with_bounds(bounds: Rect, coord: Coord) -> Option<Coord> {
+        if bounds.area() <= 0 {
+            return None;
+        }
+        Some(coord.wrapping_add_in(Coord::ZERO, bounds))
+    }
+
+    /// Use `coord_to_index` if possible. This exists so that the iterators can
+    /// avoid borrowing `self`.
+    pub(crate) fn coord_to_index_with_bounds(bounds: Rect, coord: Coord) -> Option<usize> {
+        if !bounds.contains(coord) {
+            return None;
+        }
+        let offset_coord = coord - bounds.offset();
+        Some((offset_coord.x + offset_coord.y * bounds.width()) as usize)
+    }
+
+    /// Converts a linear Vec index into a 2D Grid coordinate. This takes the
+    /// bounds rather than `self` so that the iterators can avoid borrowing the
+    /// grid.
+    pub(crate) fn index_to_coord_with_bounds(bounds: Rect, index: usize) -> Coord {
+        let width = bounds.width() as usize;
+        Coord::new((index % width) as i32, (index / width) as i32) + bounds.offset()
+    }
+}
+
+/// Tracks the coordinate of the next cell of a row-major iterator, stepping
+/// along rows without dividing by the width.
+#[derive(Debug, Clone, Copy)]
+struct RowMajorCursor {
+    next: Coord,
+    left: i32,
+    right: i32,
+}
+
+impl RowMajorCursor {
+    fn new(bounds: Rect) -> Self {
+        Self {
+            next: bounds.offset(),
+            left: bounds.left,
+            right: bounds.right,
+        }
+    }
+
+    /// Returns the current coordinate and moves on to the following one. Only
+    /// called once per cell, so running past the last row never happens.
+    fn advance(&mut self) -> Coord {
+        let coord = self.next;
+        self.next.x += 1;
+        if self.next.x == self.right {
+            self.next.x = self.left;
+            self.next.y += 1;
+        }
+        coord
+    }
+}
+
+/// Iterates over all cells of a [`VecGrid`] in row-major order.
+pub struct Iter<'a, T> {
+    cells: slice::Iter<'a, T>,
+    cursor: RowMajorCursor,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = IterCell<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cell = self.cells.next()?;
+        Some((self.cursor.advance(), cell))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.cells.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+/// Mutably iterates over all cells of a [`VecGrid`] in row-major order.
+pub struct IterMut<'a, T> {
+    cells: slice::IterMut<'a, T>,
+    cursor: RowMajorCursor,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = IterCellMut<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cell = self.cells.next()?;
+        Some((self.cursor.advance(), cell))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.cells.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+/// Moves all cells out of a [`VecGrid`] in row-major order.
+pub struct IntoIter<T> {
+    cells: vec::IntoIter<T>,
+    cursor: RowMajorCursor,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = (Coord, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cell = self.cells.next()?;
+        Some((self.cursor.advance(), cell))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.cells.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for VecGrid<T> {
+    type Item = (Coord, T);
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            cells: self.cells.into_iter(),
+            cursor: RowMajorCursor::new(self.bounds),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a VecGrid<T> {
+    type Item = IterCell<'a, T>;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut VecGrid<T> {
+    type Item = IterCellMut<'a, T>;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Builds a grid from an array of rows, with its origin at `(0, 0)`. See also
+/// the [`grid!`](crate::grid!) macro.
+impl<T, const W: usize, const H: usize> From<[[T; W]; H]> for VecGrid<T> {
+    fn from(rows: [[T; W]; H]) -> Self {
+        Self {
+            cells: IntoIterator::into_iter(rows).flatten().collect(),
+            bounds: Rect::new((W as i32, H as i32)),
+            layout: PhantomData,
+        }
+    }
+}
+
+/// Implements operations that only make sense for floating point grids.
+macro_rules! impl_float_ops {
+    ($float:ty) => {
+        impl VecGrid<$float> {
+            /// Linearly rescales every cell so that the smallest value becomes
+            /// 0 and the largest becomes 1. If every cell holds the same value,
+            /// they're all set to 0. NaN cells are left as they are.
+            pub fn normalize(&mut self) {
+                let (min, max) = match (self.min_value(), self.max_value()) {
+                    (Some((_, &min)), Some((_, &max))) => (min, max),
+                    _ => return,
+                };
+                let range = max - min;
+                for cell in self.cells.iter_mut() {
+                    *cell = if range > 0.0 {
+                        (*cell - min) / range
+                    } else {
+                        *cell * 0.0
+                    };
+                }
+            }
+        }
+    };
+}
+
+impl_float_ops!(f32);
+impl_float_ops!(f64);
+
+/// Implements an element-wise operator between two grids, along with its
+/// assigning form. Both panic if the grids have different bounds; use
+/// [`VecGrid::zip_map`] to handle that case instead.
+macro_rules! impl_elementwise_op {
+    ($op:ident, $method:ident, $assign_op:ident, $assign_method:ident) => {
+        impl<'a, T: Copy + ops::$op<Output = T>> ops::$op<&'a VecGrid<T>> for &'a VecGrid<T> {
+            type Output = VecGrid<T>;
+
+            fn $method(self, rhs: &'a VecGrid<T>) -> VecGrid<T> {
+                match self.zip_map(rhs, |&a, &b| ops::$op::$method(a, b)) {
+                    Ok(grid) => grid,
+                    Err(error) => panic!("{:?}", error),
+                }
+            }
+        }
+
+        impl<'a, T: Copy + ops::$assign_op> ops::$assign_op<&'a VecGrid<T>> for VecGrid<T> {
+            fn $assign_method(&mut self, rhs: &'a VecGrid<T>) {
+                if let Err(error) = self.check_bounds_match(rhs.bounds) {
+                    panic!("{:?}", error);
+                }
+                for (a, &b) in self.cells.iter_mut().zip(rhs.cells.iter()) {
+                    ops::$assign_op::$assign_method(a, b);
+                }
+            }
+        }
+    };
+}
+
+impl_elementwise_op!(Add, add, AddAssign, add_assign);
+impl_elementwise_op!(Sub, sub, SubAssign, sub_assign);
+impl_elementwise_op!(Mul, mul, MulAssign, mul_assign);
+
+pub struct SelectionIter<'a, T, I, L = RowMajor> {
+    grid: &'a VecGrid<T, L>,
+    coords: I,
+}
+
+impl<'a, T, I, L: Layout> Iterator for SelectionIter<'a, T, I, L>
+where
+    I: Iterator,
+    I::Item: Into<Coord>,
+{
+    type Item = Result<IterCell<'a, T>, GridError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let coord = self.coords.next()?.into();
+        match self.grid.get(coord) {
+            Some(cell) => Some(Ok((coord, cell))),
+            None => Some(Err(GridError::OutOfBounds {
+                coord,
+                bounds: self.grid.bounds,
+            })),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.coords.size_hint()
+    }
+}
+
+pub struct SelectionIterMut<'a, T, I> {
+    /// Points to the grid's backing storage. A raw pointer is used (rather than
+    /// `&'a mut VecGrid<T>`) so that looking up a cell doesn't reborrow the
+    /// entire grid while previously returned cells are still in use.
+    cells: *mut T,
+    /// The length of the backing storage, which may not match `bounds` since
+    /// both fields of the grid are public.
+    len: usize,
+    bounds: Rect,
+    coords: I,
+    /// Indices of the cells handed out so far.
+    visited: BTreeSet<usize>,
+    marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T, I> Iterator for SelectionIterMut<'a, T, I>
+where
+    I: Iterator,
+    I::Item: Into<Coord>,
+{
+    type Item = Result<IterCellMut<'a, T>, GridError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let coord = self.coords.next()?.into();
+        let index = match VecGrid::<T>::coord_to_index_with_bounds(self.bounds, coord) {
+            Some(index) if index < self.len => index,
+            _ => {
+                return Some(Err(GridError::OutOfBounds {
+                    coord,
+                    bounds: self.bounds,
+                }))
+            }
+        };
+        // `insert` returns false if the index was already present.
+        if !self.visited.insert(index) {
+            return Some(Err(GridError::AlreadyVisited(coord)));
+        }
+        // SAFETY: The index was checked against the length of the backing
+        // storage, which is mutably borrowed for `'a`, and each index is
+        // handed out at most once since it's checked against `visited`.
+        let cell = unsafe { &mut *self.cells.add(index) };
+        Some(Ok((coord, cell)))
+    }
+}
+
+/// Read access to the cells surrounding a single cell, as passed to the
+/// closure of [`VecGrid::map_stencil`].
+pub struct Neighbors<'a, T> {
+    grid: &'a VecGrid<T>,
+    center: Coord,
+}
+
+impl<'a, T> Neighbors<'a, T> {
+    /// Returns the coordinate of the cell whose neighbors these are.
+    pub fn center(&self) -> Coord {
+        self.center
+    }
+
+    /// Returns the cell at `offset` from the center, such as
+    /// [`Coord::NORTH`], or `None` if it's out of bounds.
+    pub fn get<C: Into<Coord>>(&self, offset: C) -> Option<&'a T> {
+        self.grid.get(self.center + offset.into())
+    }
+
+    /// Returns an iterator over the in-bounds cells of the orthogonal and
+    /// diagonal (Moore) neighborhood.
+    pub fn iter(&self) -> impl Iterator<Item = IterCell<'a, T>> {
+        self.grid.neighbors(self.center)
+    }
+
+    /// Returns an iterator over the in-bounds cells of the orthogonal (Von
+    /// Neumann) neighborhood.
+    pub fn iter_ortho(&self) -> impl Iterator<Item = IterCell<'a, T>> {
+        self.grid.ortho_neighbors(self.center)
+    }
+}
+
+impl<'a, T> Clone for Neighbors<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for Neighbors<'a, T> {}
+
+pub struct FloodIter<'a, T> {
+    grid: &'a VecGrid<T>,
+    predicate: Box<dyn Fn(&T) -> bool>,
+    searched_coords: Vec<Coord>,
+    coords_to_search: VecDeque<Coord>,
+}
+
+impl<'a, T> Iterator for FloodIter<'a, T> {
+    type Item = IterCell<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.coords_to_search.is_empty() {
+            let coord = self.coords_to_search.pop_front().unwrap();
+            let is_cell_included = self
+                .grid
+                .get(coord)
+                .map(|cell| (self.predicate)(cell))
+                .unwrap_or(false);
+
+            self.searched_coords.push(coord);
+
+            if !is_cell_included {
+                continue;
+            }
+
+            let neighbor_coords = Connectivity::Four
+                .neighbors(coord)
+                .filter(|&coord| {
+                    !(self.searched_coords.contains(&coord)
+                        || self.coords_to_search.contains(&coord))
+                        && self.grid.bounds.contains(coord)
+                })
+                .collect::<Vec<Coord>>();
+
+            self.coords_to_search.extend(neighbor_coords);
+
+            return Some((coord, self.grid.get(coord).unwrap()));
+        }
+
+        None
+    }
+}
+
+/// Prints the grid bounds followed by the cells laid out row by row, truncating
+/// large grids to their first 16 rows and columns. Every layout prints the
+/// same way.
+impl<T: fmt::Debug, L: Layout> fmt::Debug for VecGrid<T, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bounds = self.bounds;
+        let width = bounds.width().max(0) as usize;
+        let height = bounds.height().max(0) as usize;
+        writeln!(f, "VecGrid({}x{} at {}) [", width, height, bounds.offset())?;
+        for y in bounds.y_range().take(DEBUG_MAX_CELLS) {
+            write!(f, "    [")?;
+            let row = bounds.x_range().filter_map(|x| self.get((x, y)));
+            for (i, cell) in row.take(DEBUG_MAX_CELLS).enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{:?}", cell)?;
+            }
+            if width > DEBUG_MAX_CELLS {
+                write!(f, ", ... {} more", width - DEBUG_MAX_CELLS)?;
+            }
+            writeln!(f, "],")?;
+        }
+        if height > DEBUG_MAX_CELLS {
+            writeln!(f, "    ... {} more rows", height - DEBUG_MAX_CELLS)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<T, L: Layout> VecGrid<T, L> {
+    /// Writes the grid into `writer` row by row, calling `cell` to write each
+    /// cell, and ending every row with a newline.
+    ///
+    /// Nothing is allocated along the way, so redrawing a grid into a reused
+    /// `String` every frame is cheap.
+    pub fn render_to<W, F>(&self, writer: &mut W, cell: F) -> fmt::Result
+    where
+        W: fmt::Write,
+        F: Fn(&mut W, Coord, &T) -> fmt::Result,
+    {
+        let mut cells = L::iter(self);
+        for y in self.bounds.y_range() {
+            for (x, (_, value)) in self.bounds.x_range().zip(&mut cells) {
+                cell(writer, Coord::new(x, y), value)?;
+            }
+            writer.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+impl<L: Layout> fmt::Display for VecGrid<String, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render_to(f, |f, _, s| f.write_str(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, num::NonZeroU8};
+
+    use super::*;
+    use crate::{grid::Nearest, patterns::Line};
+
+    #[test]
+    fn bounds_and_dimensions() {
+        let grid = VecGrid::<()>::new(Rect::new((8, 8)));
+        assert_eq!(grid.bounds.width(), 8);
+    }
+
+    #[test]
+    fn out_of_bounds_coords() {
+        let grid = VecGrid::<()>::new(Rect::new((8, 8)));
+        // This would pass `coord_to_index` if there was no bounds check.
+        assert_eq!(grid.get(Coord::new(-1, 4)), None);
     }
 
     #[test]
     fn test_index_to_coord() {
         let grid = VecGrid::<()>::new(Rect::new((8, 4)));
-        assert_eq!(grid.index_to_coord(12), Coord::new(4, 1));
+        assert_eq!(
+            VecGrid::<()>::index_to_coord_with_bounds(grid.bounds, 12),
+            Coord::new(4, 1)
+        );
+    }
+
+    #[test]
+    fn selection_iter_mut() {
+        let mut grid: VecGrid<bool> = VecGrid::new(Rect::new((4, 4)));
+        // Set all neighbors of (2, 2) to `true`.
+        for res_cell in grid.selection_iter_mut(Connectivity::Eight.neighbors((2, 2))) {
+            *res_cell.unwrap().1 = true;
+        }
+        assert_eq!(grid.get(Coord::new(2, 2)), Some(&false)); // center
+        assert_eq!(grid.get(Coord::new(3, 2)), Some(&true)); // right
+        assert_eq!(grid.get(Coord::new(2, 1)), Some(&true)); // bottom
+        assert_eq!(grid.get(Coord::new(1, 2)), Some(&true)); // left
+        assert_eq!(grid.get(Coord::new(2, 3)), Some(&true)); // top
+    }
+
+    #[test]
+    fn custom_neighborhood_regions() {
+        let knight = Neighborhood::custom([
+            (1, 2),
+            (2, 1),
+            (2, -1),
+            (1, -2),
+            (-1, -2),
+            (-2, -1),
+            (-2, 1),
+            (-1, 2),
+        ]);
+        // A knight can reach every square of a 3x3 board but the center.
+        let board = VecGrid::filled(Rect::new((3, 3)), ());
+        let reachable = board.reachable_from((0, 0), &knight, |_, _| true);
+        assert_eq!(reachable.count_value(&true), 8);
+        assert_eq!(reachable.get((1, 1)), Some(&false));
+
+        let mut checkers = VecGrid::with_generator(Rect::new((4, 4)), |(x, y)| (x + y) % 2);
+        let diagonal = Neighborhood::diagonal();
+        let components = checkers.connected_components(&diagonal, |&cell| cell == 0);
+        assert_eq!(components.values().flatten().max(), Some(&0));
+        let components = checkers.connected_components(Connectivity::Four, |&cell| cell == 0);
+        assert_eq!(components.values().flatten().max(), Some(&7));
+
+        // Knight moves always change the color of the square.
+        assert_eq!(
+            checkers.flood_fill((1, 0), &knight, |&cell| cell == 1, 2),
+            1
+        );
+        assert_eq!(checkers.flood_fill((0, 0), &knight, |_| true, 3), 16);
+    }
+
+    #[test]
+    fn fill_and_count_value() {
+        let mut grid =
+            VecGrid::with_generator(Rect::new((5, 4)).translate((3, -1)), |(x, y)| (x + y) % 3);
+        assert_eq!(grid.count_value(&0), 7);
+        assert_eq!(grid.count_value(&1), 6);
+        assert_eq!(grid.count_value(&2), 7);
+        assert_eq!(grid.count_value(&3), 0);
+        assert_eq!(
+            grid.map(|&cell| cell * 2),
+            VecGrid::with_generator(grid.bounds, |(x, y)| (x + y) % 3 * 2)
+        );
+
+        grid.fill(9);
+        assert_eq!(grid, VecGrid::filled(grid.bounds, 9));
+        assert_eq!(grid.count_value(&9), 20);
+    }
+
+    #[test]
+    fn values_follow_iter_order() {
+        let mut grid =
+            VecGrid::with_generator(Rect::new((4, 3)).translate((-2, 5)), |(x, y)| x * 10 + y);
+        assert_eq!(grid.values().count(), grid.iter().count());
+        assert!(grid.values().eq(grid.iter().map(|(_, cell)| cell)));
+        assert_eq!(
+            grid.iter().map(|(coord, _)| coord).collect::<Vec<_>>(),
+            grid.bounds.iter().collect::<Vec<_>>()
+        );
+        assert!(grid
+            .iter()
+            .all(|(coord, &cell)| cell == coord.x * 10 + coord.y));
+
+        for value in grid.values_mut() {
+            *value += 1;
+        }
+        assert!(grid
+            .iter_mut()
+            .all(|(coord, &mut cell)| cell == coord.x * 10 + coord.y + 1));
+        assert!(grid
+            .clone()
+            .into_iter()
+            .eq(grid.iter().map(|(coord, &cell)| (coord, cell))));
+    }
+
+    #[test]
+    fn selection_iter_takes_any_coords() {
+        let grid = VecGrid::with_generator(Rect::new((3, 3)), |(x, y)| x + y * 3);
+        let coords = vec![Coord::new(2, 0), Coord::new(3, 0)];
+        let cells = grid.selection_iter(&coords).collect::<Vec<_>>();
+        assert_eq!(
+            cells,
+            vec![
+                Ok((Coord::new(2, 0), &2)),
+                Err(GridError::OutOfBounds {
+                    coord: Coord::new(3, 0),
+                    bounds: grid.bounds
+                })
+            ]
+        );
+        let cells = grid
+            .selection_iter([(1, 1), (0, 2)])
+            .map(|cell| *cell.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(cells, vec![4, 6]);
+        assert_eq!(
+            grid.selection_iter(Connectivity::Eight.neighbors((1, 1)))
+                .count(),
+            8
+        );
+    }
+
+    #[test]
+    fn selection_iter_mut_already_visited() {
+        let mut grid: VecGrid<bool> = VecGrid::new(Rect::new((3, 3)));
+        let mut iter = grid.selection_iter_mut([(2, 2), (2, 2)]);
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap() == Err(GridError::AlreadyVisited(Coord::new(2, 2))));
+    }
+
+    #[test]
+    fn get_clamped() {
+        let grid = VecGrid::with_generator(Rect::new((10, 10)), |coord: Coord| coord);
+        assert_eq!(grid.get_clamped((-5, 3)), Some(&Coord::new(0, 3)));
+        assert_eq!(grid.get_clamped((12, 20)), Some(&Coord::new(9, 9)));
+        assert_eq!(grid.get_clamped((4, 4)), Some(&Coord::new(4, 4)));
+        assert_eq!(
+            VecGrid::<()>::new(Rect::new((0, 0))).get_clamped((0, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn selection_iter_clamped() {
+        let grid = VecGrid::with_generator(Rect::new((2, 2)), |coord: Coord| coord);
+        let cells = grid
+            .selection_iter_clamped(Connectivity::Eight.neighbors((0, 0)))
+            .collect::<Vec<_>>();
+        assert_eq!(cells.len(), 8);
+        assert!(cells
+            .iter()
+            .all(|(coord, cell)| grid.bounds.contains(*coord) && *coord == **cell));
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = VecGrid::with_generator(Rect::new((4, 4)), |coord: Coord| coord);
+        assert_eq!(grid.neighbors((1, 1)).count(), 8);
+        assert_eq!(grid.neighbors((0, 0)).count(), 3);
+        assert_eq!(grid.ortho_neighbors((0, 0)).count(), 2);
+        // Only (3, 3) borders the out of bounds coord diagonally.
+        let outside = grid.neighbors((4, 4)).collect::<Vec<_>>();
+        assert_eq!(outside, vec![(Coord::new(3, 3), &Coord::new(3, 3))]);
+    }
+
+    #[test]
+    fn neighbors_mut() {
+        let mut grid: VecGrid<bool> = VecGrid::new(Rect::new((3, 3)));
+        for (_coord, cell) in grid.neighbors_mut((0, 0)) {
+            *cell = true;
+        }
+        assert_eq!(grid.iter().filter(|(_coord, &cell)| cell).count(), 3);
+    }
+
+    #[test]
+    fn swap_rows() {
+        let mut grid = VecGrid::with_generator(Rect::new((3, 3)), |coord: Coord| coord);
+        assert_eq!(grid.swap_rows(0, 2), Ok(()));
+        assert_eq!(grid.get((1, 0)), Some(&Coord::new(1, 2)));
+        assert_eq!(grid.get((1, 2)), Some(&Coord::new(1, 0)));
+        assert_eq!(grid.get((1, 1)), Some(&Coord::new(1, 1)));
+        assert_eq!(grid.swap_rows(1, 1), Ok(()));
+        assert_eq!(
+            grid.swap_rows(1, 3),
+            Err(GridError::RowOutOfBounds { row: 3, rows: 0..3 })
+        );
+    }
+
+    #[test]
+    fn swap_columns() {
+        let mut grid = VecGrid::with_generator(Rect::new((3, 2)), |coord: Coord| coord);
+        assert_eq!(grid.swap_columns(2, 0), Ok(()));
+        assert_eq!(grid.get((0, 1)), Some(&Coord::new(2, 1)));
+        assert_eq!(grid.get((2, 1)), Some(&Coord::new(0, 1)));
+        assert_eq!(
+            grid.swap_columns(-1, 0),
+            Err(GridError::ColumnOutOfBounds {
+                column: -1,
+                columns: 0..3
+            })
+        );
+    }
+
+    #[test]
+    fn border_iter_mut() {
+        let mut grid: VecGrid<u8> = VecGrid::new(Rect::new((4, 3)));
+        for (_coord, cell) in grid.border_iter_mut() {
+            *cell += 1;
+        }
+        assert_eq!(grid.border_iter().count(), 10);
+        assert!(grid.border_iter().all(|(_coord, &cell)| cell == 1));
+        assert_eq!(grid.get((1, 1)), Some(&0));
+        assert_eq!(grid.get((2, 1)), Some(&0));
+    }
+
+    #[test]
+    fn traversal_orders() {
+        let mut grid: VecGrid<usize> = VecGrid::new(Rect::new((3, 4)));
+        for (i, (_coord, cell)) in grid.iter_snake_mut().enumerate() {
+            *cell = i;
+        }
+        assert_eq!(grid.get((0, 1)), Some(&5));
+        assert_eq!(grid.iter_column_major().count(), 12);
+        assert_eq!(grid.iter_spiral().last(), Some((Coord::new(1, 2), &7)));
+        for (_coord, cell) in grid.iter_column_major_mut() {
+            *cell = 0;
+        }
+        assert!(grid.iter().all(|(_coord, &cell)| cell == 0));
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut grid = VecGrid::with_generator(Rect::new((3, 2)), |coord: Coord| coord.to_string());
+        for (_coord, cell) in &mut grid {
+            cell.push('!');
+        }
+        let mut iter = (&grid).into_iter();
+        assert_eq!(iter.len(), 6);
+        assert!(iter.all(|(coord, cell)| *cell == format!("{}!", coord)));
+
+        let mut owned = grid.into_iter();
+        assert_eq!(owned.len(), 6);
+        assert_eq!(owned.next(), Some((Coord::new(0, 0), "(0, 0)!".to_owned())));
+        assert_eq!(owned.len(), 5);
+        assert_eq!(owned.last(), Some((Coord::new(2, 1), "(2, 1)!".to_owned())));
+    }
+
+    #[test]
+    fn push_row() {
+        let mut grid: VecGrid<i32> = VecGrid::new(Rect::new((0, 0)));
+        assert_eq!(grid.push_row(vec![1, 2, 3]), Ok(()));
+        assert_eq!(grid.push_row(vec![4, 5, 6]), Ok(()));
+        assert_eq!(
+            grid.push_row(vec![7, 8]),
+            Err(GridError::WrongLength {
+                expected: 3,
+                received: 2
+            })
+        );
+        assert_eq!(grid.bounds, Rect::new((3, 2)));
+        assert_eq!(grid.get((0, 1)), Some(&4));
+        assert_eq!(grid.get((2, 1)), Some(&6));
+    }
+
+    #[test]
+    fn push_row_onto_empty_grids() {
+        let mut tall: VecGrid<i32> = VecGrid::new(Rect::new((0, 5)));
+        assert_eq!(tall.push_row(vec![1, 2]), Ok(()));
+        assert_eq!(tall.bounds, Rect::new((2, 1)));
+        assert_eq!(tall.cells, vec![1, 2]);
+
+        let mut wide: VecGrid<i32> = VecGrid::new(Rect::new((5, 0)));
+        assert_eq!(wide.push_row(vec![1, 2]), Ok(()));
+        assert_eq!(wide.bounds, Rect::new((2, 1)));
+        assert_eq!(wide.get((1, 0)), Some(&2));
+    }
+
+    #[test]
+    fn push_column() {
+        let mut grid: VecGrid<i32> = VecGrid::new(Rect::new((0, 0)));
+        assert_eq!(grid.push_column(vec![1, 4]), Ok(()));
+        assert_eq!(grid.push_column(vec![2, 5]), Ok(()));
+        assert_eq!(grid.push_column(vec![3, 6]), Ok(()));
+        assert!(grid.push_column(vec![7]).is_err());
+        assert_eq!(grid.bounds, Rect::new((3, 2)));
+        assert_eq!(grid.cells, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn push_column_onto_empty_grids() {
+        let mut wide: VecGrid<i32> = VecGrid::new(Rect::new((5, 0)));
+        assert_eq!(wide.push_column(vec![1, 2]), Ok(()));
+        assert_eq!(wide.bounds, Rect::new((1, 2)));
+        assert_eq!(wide.get((0, 1)), Some(&2));
+
+        let mut tall: VecGrid<i32> = VecGrid::new(Rect::new((0, 5)));
+        assert_eq!(tall.push_column(vec![1, 2]), Ok(()));
+        assert_eq!(tall.bounds, Rect::new((1, 2)));
+        assert_eq!(tall.cells, vec![1, 2]);
+    }
+
+    #[test]
+    fn equality_and_hash() {
+        let grid = VecGrid::with_generator(Rect::new((3, 3)), |coord: Coord| coord.x * coord.y);
+        let mut other = grid.clone();
+        assert_eq!(grid, other);
+        other.set((1, 1), 0);
+        assert_ne!(grid, other);
+        // Same cells, different dimensions.
+        let wide = VecGrid::with_generator(Rect::new((9, 1)), |_: Coord| 0);
+        let tall = VecGrid::with_generator(Rect::new((1, 9)), |_: Coord| 0);
+        assert_ne!(wide, tall);
+
+        let set = vec![grid.clone(), grid, other, wide, tall]
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(set.len(), 4);
+    }
+
+    #[test]
+    fn debug_layout() {
+        let grid = VecGrid::with_generator(Rect::new((3, 2)), |coord: Coord| coord.x + coord.y * 3);
+        assert_eq!(
+            format!("{:?}", grid),
+            "VecGrid(3x2 at (0, 0)) [\n    [0, 1, 2],\n    [3, 4, 5],\n]"
+        );
+        let large: VecGrid<u8> = VecGrid::new(Rect::new((20, 18)));
+        let output = format!("{:?}", large);
+        assert_eq!(output.lines().count(), 1 + DEBUG_MAX_CELLS + 2);
+        assert!(output.contains(", ... 4 more],"));
+        assert!(output.contains("    ... 2 more rows"));
+    }
+
+    #[test]
+    fn filled() {
+        let grid = VecGrid::filled(Rect::new((3, 2)), NonZeroU8::new(7).unwrap());
+        assert_eq!(grid.cells.len(), 6);
+        assert!(grid.iter().all(|(_coord, cell)| cell.get() == 7));
+        assert!(VecGrid::filled(Rect::new((0, 4)), 'x').cells.is_empty());
+    }
+
+    #[test]
+    fn try_with_generator() {
+        let mut calls = 0;
+        let result = VecGrid::try_with_generator(Rect::new((4, 4)), |coord: Coord| {
+            calls += 1;
+            if coord == Coord::new(1, 2) {
+                Err("bad cell")
+            } else {
+                Ok(coord.x)
+            }
+        });
+        assert_eq!(
+            result,
+            Err(CellError {
+                coord: Coord::new(1, 2),
+                error: "bad cell"
+            })
+        );
+        // Generation stops at the first error.
+        assert_eq!(calls, 10);
+
+        let grid = VecGrid::try_with_generator(Rect::new((2, 2)), |(x, y)| Ok::<_, ()>(x + y));
+        assert_eq!(grid.unwrap().cells, vec![0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn rows() {
+        let grid = VecGrid::with_generator(Rect::new((2, 3)), |coord: Coord| coord.y);
+        let rows = grid.rows().collect::<Vec<_>>();
+        assert_eq!(rows, vec![&[0, 0][..], &[1, 1], &[2, 2]]);
     }
 
     #[test]
-    fn selection_iter_mut() {
-        let mut grid: VecGrid<bool> = VecGrid::new(Rect::new((4, 4)));
-        // Set all neighbors of (2, 2) to `true`.
-        for res_cell in grid.selection_iter_mut(Neighborhood::new((2, 2)).iter()) {
-            *res_cell.unwrap().1 = true;
+    fn upscale() {
+        let grid = VecGrid::with_generator(Rect::new((2, 2)), |coord: Coord| coord);
+        let scaled = grid.upscale((2, 1)).unwrap();
+        assert_eq!(scaled.bounds, Rect::new((4, 2)));
+        for (coord, cell) in scaled.iter() {
+            assert_eq!(*cell, Coord::new(coord.x.div_euclid(2), coord.y));
+        }
+        assert_eq!(grid.upscale((1, 1)).unwrap(), grid);
+        assert_eq!(
+            grid.upscale((0, 2)),
+            Err(GridError::InvalidScale(Coord::new(0, 2)))
+        );
+        assert_eq!(
+            grid.upscale((3, -1)),
+            Err(GridError::InvalidScale(Coord::new(3, -1)))
+        );
+
+        let offset = VecGrid::with_generator(Rect::with_corners((-1, -1), (1, 1)), |c: Coord| c);
+        assert_eq!(
+            offset.upscale((i32::MAX, 1)),
+            Err(GridError::InvalidScale(Coord::new(i32::MAX, 1)))
+        );
+        assert_eq!(
+            offset.upscale((1, 1 << 30)),
+            Err(GridError::InvalidScale(Coord::new(1, 1 << 30)))
+        );
+        assert_eq!(
+            offset.upscale((50_000, 50_000)),
+            Err(GridError::InvalidScale(Coord::new(50_000, 50_000)))
+        );
+        let scaled = offset.upscale((3, 2)).unwrap();
+        assert_eq!(scaled.bounds, Rect::with_corners((-3, -2), (3, 2)));
+        for (coord, cell) in scaled.iter() {
+            assert_eq!(
+                *cell,
+                Coord::new(coord.x.div_euclid(3), coord.y.div_euclid(2))
+            );
         }
-        assert_eq!(grid.get(Coord::new(2, 2)), Some(&false)); // center
-        assert_eq!(grid.get(Coord::new(3, 2)), Some(&true)); // right
-        assert_eq!(grid.get(Coord::new(2, 1)), Some(&true)); // bottom
-        assert_eq!(grid.get(Coord::new(1, 2)), Some(&true)); // left
-        assert_eq!(grid.get(Coord::new(2, 3)), Some(&true)); // top
     }
 
     #[test]
-    fn selection_iter_mut_already_visited() {
-        let mut grid: VecGrid<bool> = VecGrid::new(Rect::new((3, 3)));
-        let coords = [(2, 2), (2, 2)].iter().map(|&x| x.into());
+    fn downsample() {
+        let grid = VecGrid::with_generator(Rect::new((5, 4)), |coord: Coord| coord.x + coord.y * 5);
+        let sums = grid
+            .downsample((2, 2), |_coord, block| {
+                block.iter().map(|(_, &cell)| cell).sum::<i32>()
+            })
+            .unwrap();
+        assert_eq!(sums.bounds, Rect::new((3, 2)));
+        assert_eq!(sums.get((0, 0)), Some(&(1 + 5 + 6)));
+        // The right edge blocks are only a single column wide.
+        assert_eq!(sums.get((2, 0)), Some(&(4 + 9)));
+        assert_eq!(sums.get((2, 1)), Some(&(14 + 19)));
+
+        let sizes = grid
+            .downsample((2, 3), |_coord, block| block.dimensions())
+            .unwrap();
+        assert_eq!(sizes.get((0, 0)), Some(&Coord::new(2, 3)));
+        assert_eq!(sizes.get((2, 1)), Some(&Coord::new(1, 1)));
+        assert!(grid.downsample((0, 1), |_coord, _block| ()).is_err());
+
+        // Downsampling undoes upscaling.
+        let scaled = grid.upscale((3, 2)).unwrap();
+        let restored = scaled.downsample((3, 2), |_coord, block| *block.get((0, 0)).unwrap());
+        assert_eq!(restored.unwrap(), grid);
+    }
+
+    #[test]
+    fn selection_iter_mut_duplicates() {
+        let mut grid: VecGrid<u8> = VecGrid::new(Rect::new((3, 3)));
+        // A line ending at (2, 2) chained with the neighborhood of (2, 2)
+        // contains (1, 1) and (2, 2) twice.
+        let coords = Line::new((0, 0), (2, 2))
+            .iter()
+            .chain(Connectivity::Eight.neighbors((2, 2)));
+        let results = grid
+            .selection_iter_mut(coords)
+            .map(|result| result.map(|(coord, _cell)| coord))
+            .collect::<Vec<_>>();
+        assert_eq!(results.len(), 11);
+        assert_eq!(results[0], Ok(Coord::new(0, 0)));
+        assert_eq!(results[2], Ok(Coord::new(2, 2)));
+        // The Moore neighborhood is iterated starting from north, clockwise.
+        assert_eq!(
+            results[3],
+            Err(GridError::OutOfBounds {
+                coord: Coord::new(2, 3),
+                bounds: grid.bounds
+            })
+        );
+        assert_eq!(results[8], Err(GridError::AlreadyVisited(Coord::new(1, 1))));
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 5);
+    }
+
+    #[test]
+    fn selection_iter_mut_mismatched_bounds() {
+        // The fields are public, so the bounds can claim more cells than the
+        // grid holds. Those cells must be reported as out of bounds.
+        let mut grid: VecGrid<u8> = VecGrid::new(Rect::new((2, 2)));
+        grid.bounds = Rect::new((100, 100));
+        let results = grid
+            .selection_iter_mut(vec![(1, 0), (50, 50)])
+            .map(|result| result.map(|(coord, _)| coord))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![
+                Ok(Coord::new(1, 0)),
+                Err(GridError::OutOfBounds {
+                    coord: Coord::new(50, 50),
+                    bounds: Rect::new((100, 100))
+                })
+            ]
+        );
+    }
+
+    #[test]
+    fn selection_iter_mut_repeated_out_of_bounds() {
+        let mut grid: VecGrid<u8> = VecGrid::new(Rect::new((2, 2)));
+        let coords = vec![(5, 5), (5, 5), (1, 1), (1, 1)];
         let mut iter = grid.selection_iter_mut(coords);
-        assert!(iter.next().unwrap().is_ok());
-        assert!(iter.next().unwrap() == Err(GridError::AlreadyVisited(Coord::new(2, 2))));
+        assert_eq!(
+            iter.next(),
+            Some(Err(GridError::OutOfBounds {
+                coord: Coord::new(5, 5),
+                bounds: Rect::new((2, 2))
+            }))
+        );
+        assert_eq!(
+            iter.next(),
+            Some(Err(GridError::OutOfBounds {
+                coord: Coord::new(5, 5),
+                bounds: Rect::new((2, 2))
+            }))
+        );
+        let (_coord, first) = iter.next().unwrap().unwrap();
+        assert_eq!(
+            iter.next(),
+            Some(Err(GridError::AlreadyVisited(Coord::new(1, 1))))
+        );
+        assert_eq!(iter.next(), None);
+        // The first reference is still usable after the duplicate was rejected.
+        *first = 1;
+        drop(iter);
+        assert_eq!(grid.get((1, 1)), Some(&1));
+    }
+
+    #[test]
+    fn step_automaton() {
+        // A vertical blinker.
+        let mut grid =
+            VecGrid::with_generator(Rect::new((5, 5)), |(x, y)| x == 2 && (1..4).contains(&y));
+        let mut scratch = VecGrid::new(Rect::new((0, 0)));
+        let rule = |coord: Coord, &alive: &bool, prev: &VecGrid<bool>| {
+            let live_neighbors = prev.neighbors(coord).filter(|(_coord, &cell)| cell).count();
+            live_neighbors == 3 || (alive && live_neighbors == 2)
+        };
+        let original = grid.clone();
+        grid.step_automaton(&mut scratch, rule);
+        assert_eq!(scratch, original);
+        let horizontal = grid
+            .iter()
+            .filter(|(_coord, &cell)| cell)
+            .map(|(coord, _cell)| coord);
+        assert_eq!(
+            horizontal.collect::<Vec<_>>(),
+            vec![Coord::new(1, 2), Coord::new(2, 2), Coord::new(3, 2)]
+        );
+        // The scratch buffer is reused from now on.
+        let scratch_ptr = scratch.cells.as_ptr();
+        grid.step_automaton(&mut scratch, rule);
+        assert_eq!(grid, original);
+        assert_eq!(grid.cells.as_ptr(), scratch_ptr);
+    }
+
+    #[test]
+    fn diff() {
+        let grid = VecGrid::with_generator(Rect::new((3, 3)), |(x, y)| x + y);
+        let mut other = grid.clone();
+        assert_eq!(grid.diff(&other).unwrap().count(), 0);
+        other.set((2, 0), 9);
+        other.set((0, 1), 7);
+        let diffs = grid.diff(&other).unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            diffs,
+            vec![(Coord::new(2, 0), &2, &9), (Coord::new(0, 1), &1, &7)]
+        );
+        assert_eq!(grid.diff_count(&other), Ok(2));
+
+        let small = VecGrid::filled(Rect::new((2, 3)), 0);
+        assert!(grid.diff(&small).is_err());
+        assert_eq!(
+            grid.diff_count(&small),
+            Err(GridError::BoundsMismatch {
+                expected: Rect::new((3, 3)),
+                received: Rect::new((2, 3))
+            })
+        );
+    }
+
+    #[test]
+    fn replace_all() {
+        let mut grid = VecGrid::with_generator(Rect::new((4, 4)), |(x, y)| (x + y) % 3);
+        assert_eq!(grid.replace_all(&0, 5), 6);
+        assert_eq!(grid.replace_all(&0, 5), 0);
+        assert_eq!(grid.get((0, 0)), Some(&5));
+        assert_eq!(
+            grid.replace_where(|coord, &cell| coord.x == 0 && cell != 5, 8),
+            2
+        );
+        assert_eq!(grid.get((0, 1)), Some(&8));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn value_counts() {
+        let grid =
+            VecGrid::with_generator(Rect::new((3, 2)), |(x, _y)| ['a', 'b', 'a'][x as usize]);
+        let counts = grid.value_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&'a'], 4);
+        assert_eq!(counts[&'b'], 2);
+        let ordered = grid.value_counts_ordered().into_iter().collect::<Vec<_>>();
+        assert_eq!(ordered, vec![('a', 4), ('b', 2)]);
+
+        let heights = VecGrid::with_generator(Rect::new((4, 1)), |(x, _y)| x as f32 * 0.3);
+        let buckets = heights.counts_by(|&height| height >= 0.5);
+        assert_eq!(buckets[&false], 2);
+        assert_eq!(buckets[&true], 2);
+    }
+
+    #[test]
+    fn bounds_and_contains() {
+        let grid = VecGrid::filled(Rect::new((3, 2)).translate((-1, 0)), 0);
+        assert_eq!(grid.bounds(), Rect::with_corners((-1, 0), (2, 2)));
+        assert!(grid.contains((-1, 0)));
+        assert!(grid.contains((1, 1)));
+        assert!(!grid.contains((2, 1)));
+        assert!(!grid.contains((0, -1)));
+    }
+
+    #[test]
+    fn raw_parts() {
+        let bounds = Rect::new((3, 2)).translate((1, 1));
+        let mut grid = VecGrid::with_generator(bounds, |(x, y)| x + y * 10);
+        assert_eq!(grid.as_slice(), &[11, 12, 13, 21, 22, 23]);
+        // Index 0 is the top-left corner and the stride is the grid width.
+        grid.as_mut_slice()[3] = 0;
+        assert_eq!(grid.get((1, 2)), Some(&0));
+
+        let (raw_bounds, cells) = grid.clone().into_raw_parts();
+        assert_eq!(raw_bounds, bounds);
+        assert_eq!(VecGrid::from_raw_parts(raw_bounds, cells), Ok(grid));
+
+        assert_eq!(
+            VecGrid::from_raw_parts(bounds, vec![0; 5]),
+            Err(GridError::WrongLength {
+                expected: 6,
+                received: 5
+            })
+        );
+    }
+
+    #[test]
+    fn line_of_sight() {
+        #[rustfmt::skip]
+        let walls = VecGrid::from_raw_parts(Rect::new((5, 3)), vec![
+            false, false, false, false, false,
+            false, false, true,  false, false,
+            false, false, false, false, false,
+        ]).unwrap();
+        let blocks = |_, &wall: &bool| wall;
+
+        assert!(walls.line_of_sight((0, 0), (4, 0), blocks));
+        assert!(!walls.line_of_sight((0, 1), (4, 1), blocks));
+        assert!(!walls.line_of_sight((4, 1), (0, 1), blocks));
+        // The endpoints themselves don't block.
+        assert!(walls.line_of_sight((0, 1), (2, 1), blocks));
+        // Cells outside the grid do.
+        assert!(!walls.line_of_sight((0, 0), (0, -2), blocks));
+
+        for from in walls.bounds().iter() {
+            for to in walls.bounds().iter() {
+                assert_eq!(
+                    walls.line_of_sight(from, to, blocks),
+                    walls.line_of_sight(to, from, blocks)
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn field_of_view() {
+        #[rustfmt::skip]
+        let walls = VecGrid::from_raw_parts(Rect::new((5, 3)), vec![
+            false, false, false, false, false,
+            false, false, true,  false, false,
+            false, false, false, false, false,
+        ]).unwrap();
+        let opaque = |_, &wall: &bool| wall;
+
+        #[rustfmt::skip]
+        let expected = VecGrid::from_raw_parts(Rect::new((5, 3)), vec![
+            true, true, true, false, false,
+            true, true, true, false, false,
+            true, true, true, false, false,
+        ]).unwrap();
+        // (3, 1) is within the radius but hidden behind the wall.
+        assert_eq!(walls.field_of_view((0, 1), 3, opaque), expected);
+
+        let radius_zero = walls.field_of_view((4, 2), 0, opaque);
+        assert_eq!(radius_zero.value_counts()[&true], 1);
+        assert_eq!(radius_zero.get((4, 2)), Some(&true));
+
+        let outside = walls.field_of_view((-1, 0), 10, opaque);
+        assert!(outside.iter().all(|(_, &visible)| !visible));
+    }
+
+    #[test]
+    fn distance_map() {
+        #[rustfmt::skip]
+        let walls = VecGrid::from_raw_parts(Rect::new((4, 3)), vec![
+            false, true,  false, false,
+            false, true,  false, true,
+            false, false, false, true,
+        ]).unwrap();
+        let passable = |_, &wall: &bool| !wall;
+
+        #[rustfmt::skip]
+        let expected = VecGrid::from_raw_parts(Rect::new((4, 3)), vec![
+            Some(0), None,    Some(4), Some(4),
+            Some(1), None,    Some(3), None,
+            Some(2), Some(2), Some(3), None,
+        ]).unwrap();
+        let goals = vec![Coord::new(0, 0), Coord::new(1, 0), Coord::new(9, 9)];
+        assert_eq!(walls.distance_map(goals, passable), expected);
+    }
+
+    #[test]
+    fn flow_field() {
+        #[rustfmt::skip]
+        let walls = VecGrid::from_raw_parts(Rect::new((4, 3)), vec![
+            false, true,  false, false,
+            false, true,  false, true,
+            false, false, false, true,
+        ]).unwrap();
+        let passable = |_, &wall: &bool| !wall;
+
+        let up = Some(Coord::new(0, -1));
+        let down = Some(Coord::new(0, 1));
+        let up_left = Some(Coord::new(-1, -1));
+        let down_left = Some(Coord::new(-1, 1));
+        let left = Some(Coord::new(-1, 0));
+        #[rustfmt::skip]
+        let expected = VecGrid::from_raw_parts(Rect::new((4, 3)), vec![
+            None, None,    down,      down_left,
+            up,   None,    down_left, None,
+            up,   up_left, left,      None,
+        ]).unwrap();
+        let flow = walls.flow_field(vec![Coord::new(0, 0)], passable);
+        assert_eq!(flow, expected);
+
+        // Following the flow from any reachable cell ends at the goal.
+        let mut coord = Coord::new(3, 0);
+        while let Some(&Some(offset)) = flow.get(coord) {
+            coord += offset;
+        }
+        assert_eq!(coord, Coord::new(0, 0));
+    }
+
+    #[test]
+    fn weighted_paths() {
+        // 0 is impassable, the middle of the second row is expensive.
+        #[rustfmt::skip]
+        let costs = VecGrid::from_raw_parts(Rect::new((5, 3)), vec![
+            1, 1, 1, 1, 1,
+            1, 9, 9, 9, 1,
+            0, 0, 0, 0, 0,
+        ]).unwrap();
+        let cost = |_, &cost: &u32| if cost == 0 { None } else { Some(cost) };
+
+        let expected_path = vec![
+            Coord::new(0, 1),
+            Coord::new(0, 0),
+            Coord::new(1, 0),
+            Coord::new(2, 0),
+            Coord::new(3, 0),
+            Coord::new(4, 0),
+            Coord::new(4, 1),
+        ];
+        assert_eq!(
+            costs.dijkstra_path((0, 1), (4, 1), cost),
+            Some((expected_path.clone(), 6))
+        );
+
+        let goal = Coord::new(4, 1);
+        let manhattan = |coord: Coord| ((goal.x - coord.x).abs() + (goal.y - coord.y).abs()) as u32;
+        assert_eq!(
+            costs.astar_path((0, 1), goal, cost, manhattan),
+            Some((expected_path, 6))
+        );
+
+        assert_eq!(
+            costs.dijkstra_path((2, 2), (2, 2), cost),
+            Some((vec![Coord::new(2, 2)], 0))
+        );
+        assert_eq!(costs.dijkstra_path((0, 0), (2, 2), cost), None);
+        assert_eq!(costs.dijkstra_path((0, 0), (5, 0), cost), None);
+    }
+
+    #[test]
+    fn nearest_and_budgeted_paths() {
+        let map = "\
+.......
+.###...
+.#.#...
+.###...
+.......";
+        let cells = map.lines().flat_map(|line| line.chars()).collect();
+        let map = VecGrid::from_raw_parts(Rect::new((7, 5)), cells).unwrap();
+        let probes = core::cell::Cell::new(0);
+        let passable = |_, &c: &char| {
+            probes.set(probes.get() + 1);
+            c == '.'
+        };
+        let is_walk = |path: &[Coord]| {
+            path.windows(2).all(|step| {
+                let delta = step[1] - step[0];
+                delta.x.abs() + delta.y.abs() == 1 && map.get(step[1]) == Some(&'.')
+            })
+        };
+
+        // The nearest goal is walled in, so the path leads to a farther one.
+        let goals = vec![Coord::new(2, 2), Coord::new(6, 4), Coord::new(9, 9)];
+        let path = map.path_to_nearest((4, 2), goals, passable).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.first(), Some(&Coord::new(4, 2)));
+        assert_eq!(path.last(), Some(&Coord::new(6, 4)));
+        assert!(is_walk(&path));
+        let goals = vec![Coord::new(6, 0), Coord::new(6, 4), Coord::new(0, 2)];
+        assert_eq!(
+            map.path_to_nearest((4, 0), goals, passable).unwrap().len(),
+            3
+        );
+        assert_eq!(
+            map.path_to_nearest((4, 2), vec![(2, 2).into()], passable),
+            None
+        );
+        assert_eq!(map.path_to_nearest((4, 2), vec![], passable), None);
+        assert_eq!(
+            map.path_to_nearest((4, 2), vec![(4, 2).into()], passable),
+            Some(vec![Coord::new(4, 2)])
+        );
+
+        // The way around the walls is twice as long as it looks.
+        assert_eq!(map.path_with_budget((0, 2), (4, 2), passable, 7), None);
+        let path = map.path_with_budget((0, 2), (4, 2), passable, 8).unwrap();
+        assert_eq!(path.len(), 9);
+        assert!(is_walk(&path));
+        assert_eq!(
+            map.path_with_budget((0, 2), (4, 2), passable, 100),
+            Some(path)
+        );
+        probes.set(0);
+        assert_eq!(map.path_with_budget((0, 0), (6, 4), passable, 9), None);
+        assert_eq!(probes.get(), 0);
+        assert_eq!(map.path_with_budget((0, 0), (2, 2), passable, 9), None);
+    }
+
+    #[test]
+    fn contours() {
+        let mut grid = VecGrid::filled(Rect::new((3, 3)), false);
+        grid.set((1, 1), true);
+        assert_eq!(
+            grid.contours(|&inside| inside),
+            vec![vec![(1.0, 0.5), (0.5, 1.0), (1.0, 1.5), (1.5, 1.0)]]
+        );
+
+        // Diagonal neighbors are separate regions.
+        grid.set((0, 0), true);
+        assert_eq!(grid.contours(|&inside| inside).len(), 2);
+
+        // A ring has an outer boundary and a hole.
+        let ring =
+            VecGrid::with_generator(Rect::new((3, 3)), |coord: Coord| coord != Coord::new(1, 1));
+        let loops = ring.contours(|&inside| inside);
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0].len(), 12);
+        assert_eq!(loops[1].len(), 4);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn sample_bilinear() {
+        #[rustfmt::skip]
+        let heights = VecGrid::from_raw_parts(Rect::new((2, 2)), vec![
+            0.0f32, 1.0,
+            2.0,    4.0,
+        ]).unwrap();
+
+        assert_eq!(
+            heights.sample_bilinear(1.0, 1.0, BorderMode::Clamp),
+            Some(4.0)
+        );
+        assert_eq!(
+            heights.sample_bilinear(0.5, 0.0, BorderMode::Clamp),
+            Some(0.5)
+        );
+        assert_eq!(
+            heights.sample_bilinear(0.5, 0.5, BorderMode::Clamp),
+            Some(1.75)
+        );
+        assert_eq!(
+            heights.sample_bilinear(-3.0, 1.5, BorderMode::Clamp),
+            Some(2.0)
+        );
+        // Halfway between the right column and the left column it wraps to.
+        assert_eq!(
+            heights.sample_bilinear(1.5, 0.0, BorderMode::Wrap),
+            Some(0.5)
+        );
+        assert_eq!(
+            heights.sample_bilinear(-1.0, -1.0, BorderMode::Wrap),
+            Some(4.0)
+        );
+
+        assert_eq!(
+            heights.sample_bilinear(f64::NAN, 0.0, BorderMode::Clamp),
+            None
+        );
+        assert_eq!(
+            heights.sample_bilinear(0.0, f64::INFINITY, BorderMode::Wrap),
+            None
+        );
+        let empty = VecGrid::<f32>::new(Rect::new((0, 0)));
+        assert_eq!(empty.sample_bilinear(0.0, 0.0, BorderMode::Clamp), None);
+    }
+
+    #[test]
+    fn box_blur() {
+        let row = VecGrid::from_raw_parts(Rect::new((3, 1)), vec![0u8, 3, 6]).unwrap();
+        assert_eq!(row.box_blur(1).cells, vec![1.0, 3.0, 5.0]);
+        assert_eq!(row.box_blur(0).cells, vec![0.0, 3.0, 6.0]);
+
+        // Matches a naive mean over the clamped window.
+        let bounds = Rect::new((7, 5)).translate((2, -1));
+        let heights = VecGrid::with_generator(bounds, |(x, y)| ((x * 7 + y * 13) % 10) as f32);
+        let mut blurred = VecGrid::filled(Rect::new((1, 1)), 0.0);
+        let mut scratch = Vec::new();
+        for radius in 1..4 {
+            heights.box_blur_into(radius, &mut blurred, &mut scratch);
+            assert_eq!(blurred.bounds, bounds);
+            for (coord, &value) in blurred.iter() {
+                let window = Rect::with_corners(
+                    coord - Coord::new(radius, radius),
+                    coord + Coord::new(radius + 1, radius + 1),
+                );
+                let sum: f64 = window
+                    .iter()
+                    .map(|c| *heights.get_clamped(c).unwrap() as f64)
+                    .sum();
+                let expected = sum / window.area() as f64;
+                assert!(
+                    (value - expected).abs() < 1e-9,
+                    "{} != {} at {:?}",
+                    value,
+                    expected,
+                    coord
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn row_and_column_reductions() {
+        let grid =
+            VecGrid::with_generator(Rect::new((3, 2)).translate((1, 1)), |(x, y)| x + y * 10);
+        assert_eq!(grid.row_sums(), vec![36, 66]);
+        assert_eq!(grid.column_sums(), vec![32, 34, 36]);
+        assert_eq!(
+            grid.row_reduce(i32::MIN, |max, &cell| max.max(cell)),
+            vec![13, 23]
+        );
+        assert_eq!(
+            grid.column_reduce(Vec::new(), |mut cells, &cell| {
+                cells.push(cell);
+                cells
+            }),
+            vec![vec![11, 21], vec![12, 22], vec![13, 23]]
+        );
+        assert_eq!(grid.row_counts(|&cell| cell % 2 == 1), vec![2, 2]);
+        assert_eq!(grid.column_counts(|&cell| cell > 20), vec![1, 1, 1]);
+
+        let empty = VecGrid::<i32>::new(Rect::new((0, 2)));
+        assert_eq!(empty.row_sums(), vec![0, 0]);
+        assert!(empty.column_sums().is_empty());
+    }
+
+    #[test]
+    fn shift() {
+        let original =
+            VecGrid::with_generator(Rect::new((3, 2)).translate((1, 1)), |(x, y)| x + y * 10);
+
+        let mut grid = original.clone();
+        grid.shift((1, 1), ShiftMode::Wrap);
+        assert_eq!(grid.cells, vec![23, 21, 22, 13, 11, 12]);
+        grid.shift((2, 5), ShiftMode::Wrap);
+        assert_eq!(grid, original);
+        grid.shift((0, 0), ShiftMode::Fill(0));
+        assert_eq!(grid, original);
+
+        grid.shift((-1, 1), ShiftMode::Fill(0));
+        assert_eq!(grid.cells, vec![0, 0, 0, 12, 13, 0]);
+        grid.shift((0, -1), ShiftMode::Fill(9));
+        assert_eq!(grid.cells, vec![12, 13, 0, 9, 9, 9]);
+        grid.shift((3, 0), ShiftMode::Fill(7));
+        assert_eq!(grid.cells, vec![7; 6]);
+    }
+
+    #[test]
+    fn map_stencil() {
+        let grid = VecGrid::with_generator(Rect::new((3, 3)), |(x, y)| x + y * 3);
+        let sums = grid.map_stencil(|_, &cell, neighbors| {
+            cell * 100 + neighbors.iter_ortho().map(|(_, &n)| n).sum::<i32>()
+        });
+        #[rustfmt::skip]
+        assert_eq!(sums.cells, vec![
+            4,   106, 206,
+            310, 416, 514,
+            610, 718, 812,
+        ]);
+
+        let edges = grid.map_stencil(|coord, _, neighbors| {
+            assert_eq!(neighbors.center(), coord);
+            (
+                neighbors.get(Coord::WEST).copied(),
+                neighbors.iter().count(),
+            )
+        });
+        assert_eq!(edges.get((0, 0)), Some(&(None, 3)));
+        assert_eq!(edges.get((1, 1)), Some(&(Some(3), 8)));
+    }
+
+    #[test]
+    fn map_into() {
+        let grid = VecGrid::with_generator(Rect::new((2, 2)), |(x, y)| Box::new(x + y * 2));
+        let mapped = grid.map_into(|coord, cell| (coord, *cell));
+        assert_eq!(mapped.get((1, 1)), Some(&(Coord::new(1, 1), 3)));
+    }
+
+    #[test]
+    fn try_map() {
+        let grid =
+            VecGrid::with_generator(Rect::new((3, 1)), |(x, _y)| ["1", "2", "x"][x as usize]);
+        assert_eq!(
+            grid.try_map(|_, cell| cell.parse::<i32>())
+                .map_err(|error| error.coord),
+            Err(Coord::new(2, 0))
+        );
+        let parsed = grid.try_map(|_, cell| cell.parse::<i32>().or(Ok::<_, ()>(0)));
+        assert_eq!(parsed.map(|grid| grid.cells), Ok(vec![1, 2, 0]));
+    }
+
+    #[test]
+    fn zip_map() {
+        let a = VecGrid::with_generator(Rect::new((2, 2)), |(x, y)| x + y * 2);
+        let b = VecGrid::filled(Rect::new((2, 2)), 10);
+        assert_eq!(
+            a.zip_map(&b, |&a, &b| a < b).map(|grid| grid.cells),
+            Ok(vec![true; 4])
+        );
+        let offset = VecGrid::filled(Rect::new((2, 2)).translate((1, 0)), 10);
+        assert_eq!(
+            a.zip_map(&offset, |&a, &b| a + b),
+            Err(GridError::BoundsMismatch {
+                expected: a.bounds,
+                received: offset.bounds
+            })
+        );
+    }
+
+    #[test]
+    fn elementwise_ops() {
+        let a = VecGrid::with_generator(Rect::new((2, 2)), |(x, y)| x + y * 2);
+        let b = VecGrid::filled(Rect::new((2, 2)), 10);
+        assert_eq!((&a + &b).cells, vec![10, 11, 12, 13]);
+        assert_eq!((&a - &b).cells, vec![-10, -9, -8, -7]);
+        assert_eq!((&a * &b).cells, vec![0, 10, 20, 30]);
+
+        let mut c = a.clone();
+        c += &b;
+        c -= &a;
+        c *= &b;
+        assert_eq!(c.cells, vec![100; 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn elementwise_ops_bounds_mismatch() {
+        let a = VecGrid::filled(Rect::new((2, 2)), 1);
+        let b = VecGrid::filled(Rect::new((2, 3)), 1);
+        let _ = &a + &b;
+    }
+
+    #[test]
+    fn scalar_ops() {
+        let mut grid =
+            VecGrid::from_raw_parts(Rect::new((4, 1)), vec![1.0f32, -2.0, 4.0, 3.0]).unwrap();
+        grid.scale(2.0);
+        grid.offset(1.0);
+        assert_eq!(grid.cells, vec![3.0, -3.0, 9.0, 7.0]);
+        assert_eq!(grid.min_value(), Some((Coord::new(1, 0), &-3.0)));
+        assert_eq!(grid.max_value(), Some((Coord::new(2, 0), &9.0)));
+        grid.clamp_values(0.0, 8.0);
+        assert_eq!(grid.cells, vec![3.0, 0.0, 8.0, 7.0]);
+        grid.normalize();
+        assert_eq!(grid.cells, vec![0.375, 0.0, 1.0, 0.875]);
+
+        let mut constant = VecGrid::filled(Rect::new((2, 2)), 5.0f64);
+        constant.normalize();
+        assert_eq!(constant.cells, vec![0.0; 4]);
+
+        let with_nan =
+            VecGrid::from_raw_parts(Rect::new((3, 1)), vec![f32::NAN, 2.0, 1.0]).unwrap();
+        assert_eq!(with_nan.min_value(), Some((Coord::new(2, 0), &1.0)));
+        assert_eq!(VecGrid::<f32>::new(Rect::new((0, 0))).max_value(), None);
+    }
+
+    #[test]
+    fn threshold() {
+        let heights = VecGrid::from_raw_parts(Rect::new((4, 1)), vec![0.2, 0.5, 0.7, 0.1]).unwrap();
+        assert_eq!(
+            heights.threshold_at(&0.5).cells,
+            vec![false, true, true, false]
+        );
+        let mask = heights.threshold(|coord, &height| coord.x > 0 && height < 0.6);
+        assert_eq!(mask.bounds, heights.bounds);
+        assert_eq!(mask.cells, vec![false, true, false, true]);
+    }
+
+    #[test]
+    fn apply_masked() {
+        let mut grid = VecGrid::filled(Rect::new((3, 1)), 0);
+        let mask = VecGrid::from_raw_parts(Rect::new((3, 1)), vec![true, false, true]).unwrap();
+        assert_eq!(
+            grid.apply_masked(&mask, |coord, cell| *cell = coord.x + 1),
+            Ok(2)
+        );
+        assert_eq!(grid.apply_unmasked(&mask, |_, cell| *cell = 9), Ok(1));
+        assert_eq!(grid.cells, vec![1, 9, 3]);
+
+        let wrong_bounds = VecGrid::filled(Rect::new((2, 1)), true);
+        assert!(grid
+            .apply_masked(&wrong_bounds, |_, cell| *cell = 0)
+            .is_err());
+        assert_eq!(grid.cells, vec![1, 9, 3]);
+    }
+
+    #[test]
+    fn coords_where() {
+        let mut grid = VecGrid::with_generator(Rect::new((3, 2)), |(x, y)| x * y);
+        let mut matches = grid.coords_where(|coord, &cell| coord.x > 0 && cell == 0);
+        assert_eq!(matches.next(), Some(Coord::new(1, 0)));
+        assert_eq!(matches.next(), Some(Coord::new(2, 0)));
+        assert_eq!(matches.next(), None);
+        drop(matches);
+
+        let coords = grid.coords_where(|_, &cell| cell > 0).collect::<Vec<_>>();
+        for coord in coords {
+            grid.set(coord, -1);
+        }
+        assert_eq!(grid.cells, vec![0, 0, 0, 0, -1, -1]);
+    }
+
+    #[test]
+    fn fill_from() {
+        let mut grid = VecGrid::filled(Rect::new((3, 2)), 0);
+        assert_eq!(grid.fill_from(1..7), Ok(()));
+        assert_eq!(grid.cells, vec![1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(
+            grid.fill_from(vec![9; 2]),
+            Err(GridError::WrongLength {
+                expected: 6,
+                received: 2
+            })
+        );
+        assert_eq!(grid.cells, vec![9, 9, 3, 4, 5, 6]);
+        assert_eq!(
+            grid.fill_from(0..10),
+            Err(GridError::WrongLength {
+                expected: 6,
+                received: 10
+            })
+        );
+    }
+
+    #[test]
+    fn overwrite_region_from() {
+        let mut grid = VecGrid::filled(Rect::new((3, 3)).translate((1, 1)), 0);
+        let region = Rect::with_corners((2, 2), (4, 4));
+        assert_eq!(grid.overwrite_region_from(region, 1..5), Ok(()));
+        #[rustfmt::skip]
+        assert_eq!(grid.cells, vec![
+            0, 0, 0,
+            0, 1, 2,
+            0, 3, 4,
+        ]);
+
+        let outside = Rect::with_corners((2, 2), (5, 3));
+        assert_eq!(
+            grid.overwrite_region_from(outside, 0..3),
+            Err(GridError::OutOfBounds {
+                coord: Coord::new(4, 2),
+                bounds: grid.bounds
+            })
+        );
+        assert_eq!(
+            grid.overwrite_region_from(region, 0..3),
+            Err(GridError::WrongLength {
+                expected: 4,
+                received: 3
+            })
+        );
+    }
+
+    #[test]
+    fn with_generator_normalized() {
+        let grid = VecGrid::with_generator_normalized(
+            Rect::new((2, 1)).translate((3, 3)),
+            |_, position| position,
+        );
+        assert_eq!(grid.cells, vec![(0.25, 0.5), (0.75, 0.5)]);
+
+        let column =
+            VecGrid::with_generator_normalized(Rect::new((1, 4)), |coord, (x, y)| (coord, x, y));
+        assert_eq!(column.get((0, 3)), Some(&(Coord::new(0, 3), 0.5, 0.875)));
+    }
+
+    #[test]
+    fn resample() {
+        let grid =
+            VecGrid::with_generator(Rect::new((4, 2)).translate((1, 1)), |(x, y)| x + y * 10);
+
+        let squashed = grid.resample((2, 1), Nearest).unwrap();
+        assert_eq!(squashed.bounds, Rect::new((2, 1)).translate((1, 1)));
+        assert_eq!(squashed.cells, vec![22, 24]);
+        #[rustfmt::skip]
+        assert_eq!(grid.resample((6, 3), Nearest).unwrap().cells, vec![
+            11, 12, 12, 13, 14, 14,
+            21, 22, 22, 23, 24, 24,
+            21, 22, 22, 23, 24, 24,
+        ]);
+        assert_eq!(grid.resample((1, 1), Nearest).unwrap().cells, vec![23]);
+        assert_eq!(
+            grid.resample((0, 3), Nearest).unwrap().cells,
+            Vec::<i32>::new()
+        );
+        assert_eq!(
+            grid.resample((-1, 3), Nearest),
+            Err(GridError::InvalidScale(Coord::new(-1, 3)))
+        );
+        let empty = VecGrid::<i32>::new(Rect::new((0, 0)));
+        assert_eq!(
+            empty.resample((1, 1), Nearest),
+            Err(GridError::InvalidScale(Coord::new(1, 1)))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn resample_bilinear() {
+        use crate::grid::Bilinear;
+
+        let grid = VecGrid::from_raw_parts(Rect::new((2, 1)), vec![0.0f32, 4.0]).unwrap();
+        assert_eq!(
+            grid.resample((4, 1), Bilinear).unwrap().cells,
+            vec![0.0, 1.0, 3.0, 4.0]
+        );
+        assert_eq!(grid.resample((1, 3), Bilinear).unwrap().cells, vec![2.0; 3]);
+        assert_eq!(
+            grid.resample((2, 1), Bilinear).unwrap().cells,
+            vec![0.0, 4.0]
+        );
+        let grid = VecGrid::from_raw_parts(Rect::new((1, 2)).translate((-3, 5)), vec![1.0, 3.0]);
+        assert_eq!(
+            grid.unwrap().resample((1, 4), Bilinear).unwrap().cells,
+            vec![1.0, 1.5, 2.5, 3.0]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn connectivity() {
+        #[rustfmt::skip]
+        let grid = VecGrid::from_raw_parts(Rect::new((4, 3)), vec![
+            1, 0, 0, 1,
+            0, 1, 0, 1,
+            0, 0, 0, 0,
+        ]).unwrap();
+
+        let four = grid.connected_components(Connectivity::Four, |&cell| cell == 1);
+        #[rustfmt::skip]
+        assert_eq!(four.cells, vec![
+            Some(0), None,    None, Some(1),
+            None,    Some(2), None, Some(1),
+            None,    None,    None, None,
+        ]);
+        let eight = grid.connected_components(Connectivity::Eight, |&cell| cell == 1);
+        assert_eq!(eight.get((1, 1)), Some(&Some(0)));
+        assert_eq!(eight.get((3, 1)), Some(&Some(1)));
+
+        let reached = grid.reachable_from((0, 0), Connectivity::Eight, |_, &cell| cell == 1);
+        assert_eq!(reached.value_counts()[&true], 2);
+        let reached = grid.reachable_from((0, 0), Connectivity::Four, |_, &cell| cell == 1);
+        assert_eq!(reached.value_counts()[&true], 1);
+        assert_eq!(Connectivity::Four.offsets().len(), 4);
+        assert_eq!(Connectivity::Eight.neighbors((0, 0)).count(), 8);
+    }
+
+    #[test]
+    fn flood_fill() {
+        #[rustfmt::skip]
+        let mut grid = VecGrid::from_raw_parts(Rect::new((4, 3)), vec![
+            0, 0, 1, 0,
+            1, 1, 0, 0,
+            0, 1, 0, 0,
+        ]).unwrap();
+        assert_eq!(
+            grid.flood_fill((3, 0), Connectivity::Four, |&cell| cell == 0, 5),
+            5
+        );
+        #[rustfmt::skip]
+        assert_eq!(grid.cells, vec![
+            0, 0, 1, 5,
+            1, 1, 5, 5,
+            0, 1, 5, 5,
+        ]);
+        assert_eq!(
+            grid.flood_fill((0, 0), Connectivity::Eight, |&cell| cell == 0, 7),
+            2
+        );
+        assert_eq!(
+            grid.flood_fill((0, 0), Connectivity::Eight, |&cell| cell == 0, 7),
+            0
+        );
+        assert_eq!(grid.flood_fill((9, 9), Connectivity::Eight, |_| true, 7), 0);
+    }
+
+    #[test]
+    fn scanline_matches_breadth_first() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..50 {
+            let bounds = Rect::new((rng.gen_range(1..24), rng.gen_range(1..24))).translate((-3, 2));
+            let blobs = VecGrid::with_generator(bounds, |_: Coord| rng.gen_bool(0.6));
+            let start = Coord::new(
+                rng.gen_range(bounds.left..bounds.right),
+                rng.gen_range(bounds.top..bounds.bottom),
+            );
+            for &connectivity in [Connectivity::Four, Connectivity::Eight].iter() {
+                let mut expected = VecGrid::filled(bounds, false);
+                blobs.flood_region(
+                    start,
+                    connectivity.as_ref(),
+                    |_, &open| open,
+                    &mut expected,
+                    |_| {},
+                );
+                let expected_count = expected.cells.iter().filter(|&&cell| cell).count();
+
+                assert_eq!(
+                    blobs.reachable_from(start, connectivity, |_, &open| open),
+                    expected
+                );
+                let mut filled = blobs.map(|&open| open as u8);
+                assert_eq!(
+                    filled.flood_fill(start, connectivity, |&cell| cell == 1, 2),
+                    expected_count
+                );
+                assert_eq!(filled.threshold(|_, &cell| cell == 2), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn trace_boundary() {
+        #[rustfmt::skip]
+        let grid = VecGrid::from_raw_parts(Rect::new((5, 4)), vec![
+            0, 0, 0, 0, 0,
+            0, 1, 1, 1, 0,
+            0, 1, 1, 0, 0,
+            0, 0, 0, 0, 1,
+        ]).unwrap();
+        let in_region = |_, &cell: &i32| cell == 1;
+
+        let coords =
+            |coords: &[(i32, i32)]| coords.iter().map(|&c| Coord::from(c)).collect::<Vec<_>>();
+        assert_eq!(
+            grid.trace_boundary((2, 2), in_region),
+            Some(coords(&[(1, 2), (1, 1), (2, 1), (3, 1), (2, 2)]))
+        );
+        // Touching the grid edge, and a single cell.
+        assert_eq!(
+            grid.trace_boundary((4, 3), in_region),
+            Some(coords(&[(4, 3)]))
+        );
+        assert_eq!(grid.trace_boundary((0, 0), in_region), None);
+
+        // The perimeter passes through the middle of a line twice.
+        let line = VecGrid::filled(Rect::new((3, 1)), 1);
+        assert_eq!(
+            line.trace_boundary((2, 0), in_region),
+            Some(coords(&[(0, 0), (1, 0), (2, 0), (1, 0)]))
+        );
+    }
+
+    #[test]
+    fn largest_rect() {
+        #[rustfmt::skip]
+        let grid = VecGrid::from_raw_parts(Rect::new((5, 4)).translate((1, 1)), vec![
+            1, 0, 1, 1, 0,
+            1, 1, 1, 1, 0,
+            0, 1, 1, 1, 1,
+            1, 1, 0, 1, 1,
+        ]).unwrap();
+        let matches = |_, &cell: &i32| cell == 1;
+        assert_eq!(
+            grid.largest_rect(matches),
+            Some(Rect::with_corners((2, 2), (5, 4)))
+        );
+        assert_eq!(grid.largest_rect(|_, &cell| cell == 2), None);
+
+        // A brute force search over every rect agrees on the area.
+        let best_area = grid
+            .bounds
+            .iter()
+            .flat_map(|a| {
+                grid.bounds
+                    .iter()
+                    .map(move |b| Rect::with_corners(a, b + Coord::ONE))
+            })
+            .filter(|rect| {
+                rect.iter()
+                    .all(|coord| grid.get(coord).is_some_and(|cell| matches(coord, cell)))
+            })
+            .map(|rect| rect.area())
+            .max();
+        assert_eq!(best_area, Some(6));
+    }
+
+    #[test]
+    fn decompose_rects() {
+        #[rustfmt::skip]
+        let grid = VecGrid::from_raw_parts(Rect::new((4, 3)), vec![
+            1, 1, 0, 1,
+            1, 1, 0, 1,
+            0, 1, 1, 1,
+        ]).unwrap();
+        let rects = grid.decompose_rects(|_, &cell| cell == 1);
+        assert_eq!(
+            rects,
+            vec![
+                Rect::with_corners((0, 0), (2, 2)),
+                Rect::with_corners((3, 0), (4, 3)),
+                Rect::with_corners((1, 2), (3, 3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn bounding_box() {
+        #[rustfmt::skip]
+        let grid = VecGrid::from_raw_parts(Rect::new((5, 4)).translate((-2, 1)), vec![
+            0, 0, 0, 0, 0,
+            0, 0, 1, 0, 0,
+            0, 1, 0, 0, 1,
+            0, 0, 0, 0, 0,
+        ]).unwrap();
+        assert_eq!(
+            grid.bounding_box(|_, &cell| cell == 1),
+            Some(Rect::with_corners((-1, 2), (3, 4)))
+        );
+        assert_eq!(
+            grid.bounding_box(|coord, _| coord == Coord::new(0, 4)),
+            Some(Rect::with_corners((0, 4), (1, 5)))
+        );
+        assert_eq!(grid.bounding_box(|_, &cell| cell == 2), None);
+        assert_eq!(
+            VecGrid::<i32>::new(Rect::new((0, 3))).bounding_box(|_, _| true),
+            None
+        );
+    }
+
+    #[test]
+    fn rotate_region_cw() {
+        let mut grid = VecGrid::with_generator(Rect::new((4, 3)), |(x, y)| x + y * 4);
+        grid.rotate_region_cw(Rect::with_corners((1, 1), (3, 3)))
+            .unwrap();
+        #[rustfmt::skip]
+        assert_eq!(grid.cells, vec![
+            0, 1,  2, 3,
+            4, 9,  5, 7,
+            8, 10, 6, 11,
+        ]);
+
+        let mut grid = VecGrid::with_generator(Rect::new((3, 3)), |(x, y)| x + y * 3);
+        grid.rotate_region_cw(grid.bounds).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(grid.cells, vec![
+            6, 3, 0,
+            7, 4, 1,
+            8, 5, 2,
+        ]);
+
+        let rect = Rect::with_corners((0, 0), (2, 3));
+        assert_eq!(grid.rotate_region_cw(rect), Err(GridError::NotSquare(rect)));
+        let outside = Rect::with_corners((1, 1), (4, 4));
+        assert_eq!(
+            grid.rotate_region_cw(outside),
+            Err(GridError::OutOfBounds {
+                coord: Coord::new(3, 3),
+                bounds: grid.bounds
+            })
+        );
+    }
+
+    #[test]
+    fn mirror_region() {
+        let mut grid = VecGrid::with_generator(Rect::new((6, 3)), |(x, y)| x + y * 6);
+        let region = Rect::with_corners((1, 1), (6, 3));
+        grid.mirror_region_horizontal(region).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(grid.cells, vec![
+            0,  1,  2,  3,  4,  5,
+            6,  11, 10, 9,  8,  7,
+            12, 17, 16, 15, 14, 13,
+        ]);
+        grid.mirror_region_vertical(region).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(grid.cells, vec![
+            0,  1,  2,  3,  4,  5,
+            6,  17, 16, 15, 14, 13,
+            12, 11, 10, 9,  8,  7,
+        ]);
+        assert!(grid.mirror_region_vertical(Rect::new((7, 1))).is_err());
+    }
+
+    #[test]
+    fn get_or_and_get_copied() {
+        let grid = VecGrid::with_generator(Rect::new((2, 2)), |(x, y)| x + y * 2);
+        assert_eq!(grid.get_or((1, 1), &-1), &3);
+        assert_eq!(grid.get_or((-5, 0), &-1), &-1);
+        assert_eq!(grid.get_copied((0, 1)), Some(2));
+        assert_eq!(grid.get_copied((0, -1)), None);
+    }
+
+    #[test]
+    fn iter_rect() {
+        let mut grid =
+            VecGrid::with_generator(Rect::new((4, 3)).translate((-1, 1)), |(x, y)| x + y * 10);
+        let cells = grid
+            .iter_rect(Rect::with_corners((1, 0), (5, 3)))
+            .map(|(coord, &cell)| (coord, cell))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cells,
+            vec![
+                (Coord::new(1, 1), 11),
+                (Coord::new(2, 1), 12),
+                (Coord::new(1, 2), 21),
+                (Coord::new(2, 2), 22),
+            ]
+        );
+        assert_eq!(
+            grid.iter_rect(Rect::new((2, 2)).translate((10, 10)))
+                .count(),
+            0
+        );
+        assert_eq!(
+            grid.iter_rect(Rect::new((100, 100)).translate((-50, -50)))
+                .count(),
+            12
+        );
+
+        for (coord, cell) in grid.iter_rect_mut(Rect::with_corners((-1, 3), (1, 10))) {
+            *cell = coord.x * 100;
+        }
+        assert_eq!(grid.row_sums(), vec![42, 82, -37]);
+    }
+
+    #[test]
+    fn crop_and_resize() {
+        let bounds = Rect::new((4, 3)).translate((-1, -1));
+        let mut grid = VecGrid::with_generator(bounds, |(x, y)| x + y * 10);
+        assert_eq!(
+            grid.crop(Rect::with_corners((0, 0), (4, 2))),
+            Err(GridError::OutOfBounds {
+                coord: Coord::new(3, 1),
+                bounds
+            })
+        );
+        grid.crop(Rect::with_corners((0, 0), (3, 2))).unwrap();
+        assert_eq!(grid.bounds, Rect::with_corners((0, 0), (3, 2)));
+        assert_eq!(grid.cells, vec![0, 1, 2, 10, 11, 12]);
+
+        grid.resize(Rect::with_corners((-1, 1), (2, 3)), -1);
+        assert_eq!(grid.cells, vec![-1, 10, 11, -1, -1, -1]);
+    }
+
+    #[test]
+    fn copy_region_overlapping() {
+        let bounds = Rect::new((6, 5)).translate((-2, 3));
+        let grid = VecGrid::with_generator(bounds, |(x, y)| x + y * 10);
+        let src = Rect::new((3, 2)).translate((-1, 4));
+        for &offset in [(1, 0), (-1, 0), (0, 1), (0, -1), (2, 1), (-1, -1)].iter() {
+            let offset = Coord::from(offset);
+            let mut copied = grid.clone();
+            assert_eq!(copied.copy_region(src, src.offset() + offset), Ok(6));
+            let mut expected = grid.clone();
+            for coord in src.iter() {
+                expected.set(coord + offset, *grid.get(coord).unwrap());
+            }
+            assert_eq!(copied, expected, "offset {}", offset);
+        }
+    }
+
+    #[test]
+    fn copy_region_clips_destination() {
+        let mut grid = VecGrid::with_generator(Rect::new((4, 3)), |(x, y)| x + y * 10);
+        assert_eq!(grid.copy_region(Rect::new((4, 2)), (2, 2)), Ok(2));
+        assert_eq!(grid.cells, vec![0, 1, 2, 3, 10, 11, 12, 13, 20, 21, 0, 1]);
+        assert_eq!(grid.copy_region(Rect::new((2, 2)), (-2, 5)), Ok(0));
+
+        let before = grid.clone();
+        assert_eq!(
+            grid.copy_region(Rect::new((2, 2)).translate((3, 0)), (0, 0)),
+            Err(GridError::OutOfBounds {
+                coord: Coord::new(4, 1),
+                bounds: grid.bounds,
+            })
+        );
+        assert_eq!(grid, before);
+    }
+
+    #[test]
+    fn overlays() {
+        let bounds = Rect::new((4, 2)).translate((1, -1));
+        let mut display = VecGrid::filled(bounds, '.');
+        let items = VecGrid::from_raw_parts(bounds, "  $   ! ".chars().collect()).unwrap();
+        let transparent = |_, cell: &mut char, &item: &char| {
+            if item != ' ' {
+                *cell = item;
+            }
+        };
+        display.overlay(&items, transparent).unwrap();
+        assert_eq!(display.cells.iter().collect::<String>(), "..$...!.");
+
+        let mut visited = Vec::new();
+        let actors =
+            VecGrid::from_raw_parts(Rect::new((2, 2)).translate((-9, 9)), vec![0, 1, 2, 3])
+                .unwrap();
+        let count = display.overlay_at(&actors, (4, -2), |coord, cell, &actor| {
+            visited.push((coord, actor));
+            *cell = char::from(b'0' + actor);
+        });
+        assert_eq!(count, 1);
+        assert_eq!(visited, vec![(Coord::new(4, -1), 2)]);
+        assert_eq!(display.overlay_at(&actors, (5, 0), |_, _, _| ()), 0);
+        assert_eq!(display.cells.iter().collect::<String>(), "..$2..!.");
+
+        assert_eq!(
+            display.overlay(&actors, |_, _, _| ()),
+            Err(GridError::BoundsMismatch {
+                expected: bounds,
+                received: actors.bounds,
+            })
+        );
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut grid =
+            VecGrid::with_generator(Rect::new((3, 2)).translate((-1, 4)), |(x, y)| x + y * 10);
+        let [a, b] = grid
+            .get_many_mut([Coord::new(1, 5), Coord::new(-1, 4)])
+            .unwrap();
+        mem::swap(a, b);
+        let [a, b, c] = grid
+            .get_many_mut([Coord::new(0, 4), Coord::new(0, 5), Coord::new(1, 4)])
+            .unwrap();
+        *c = (*a + *b) / 2;
+        assert_eq!(grid.cells, vec![51, 40, 45, 49, 50, 39]);
+        assert_eq!(grid.get_many_mut([]), Ok([]));
+
+        let bounds = grid.bounds;
+        assert_eq!(
+            grid.get_many_mut([Coord::new(0, 4), Coord::new(2, 4), Coord::new(0, 4)]),
+            Err(GetManyError {
+                index: 1,
+                error: GridError::OutOfBounds {
+                    coord: Coord::new(2, 4),
+                    bounds,
+                },
+            })
+        );
+        let error = grid
+            .get_many_mut([Coord::new(0, 4), Coord::new(1, 4), Coord::new(0, 4)])
+            .unwrap_err();
+        assert_eq!(
+            error,
+            GetManyError {
+                index: 2,
+                error: GridError::AlreadyVisited(Coord::new(0, 4)),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "coordinate 2: the cell at (0, 4) was already visited"
+        );
+    }
+
+    #[test]
+    fn padding() {
+        let bounds = Rect::new((3, 2)).translate((1, 1));
+        let grid = VecGrid::from_raw_parts(bounds, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        let padded = grid.padded(1, PadMode::Constant(0)).unwrap();
+        assert_eq!(padded.bounds, Rect::new((5, 4)));
+        #[rustfmt::skip]
+        assert_eq!(padded.cells, vec![
+            0, 0, 0, 0, 0,
+            0, 1, 2, 3, 0,
+            0, 4, 5, 6, 0,
+            0, 0, 0, 0, 0,
+        ]);
+        #[rustfmt::skip]
+        assert_eq!(grid.padded(1, PadMode::ClampEdge).unwrap().cells, vec![
+            1, 1, 2, 3, 3,
+            1, 1, 2, 3, 3,
+            4, 4, 5, 6, 6,
+            4, 4, 5, 6, 6,
+        ]);
+        #[rustfmt::skip]
+        assert_eq!(grid.padded(1, PadMode::Wrap).unwrap().cells, vec![
+            6, 4, 5, 6, 4,
+            3, 1, 2, 3, 1,
+            6, 4, 5, 6, 4,
+            3, 1, 2, 3, 1,
+        ]);
+
+        // Margins wider than the grid wrap around more than once.
+        let wrapped = grid.padded(2, PadMode::Wrap).unwrap();
+        assert_eq!(wrapped.bounds, Rect::new((7, 6)).translate((-1, -1)));
+        assert_eq!(wrapped.get((-1, -1)), Some(&2));
+        assert_eq!(wrapped.get((5, -1)), Some(&2));
+        assert_eq!(wrapped.get((-1, 4)), Some(&5));
+        assert_eq!(wrapped.get((5, 4)), Some(&5));
+        assert_eq!(wrapped.get((0, 1)), Some(&3));
+
+        for padded in [padded, wrapped.unpadded(1).unwrap()].iter() {
+            assert_eq!(padded.unpadded(1), Ok(grid.clone()));
+            assert_eq!(padded.bounds.expand(-1), grid.bounds);
+        }
+        assert_eq!(grid.padded(0, PadMode::Wrap), Ok(grid.clone()));
+        assert_eq!(
+            grid.padded(-1, PadMode::Constant(0)),
+            Err(GridError::InvalidMargin(-1))
+        );
+        assert_eq!(grid.unpadded(2), Err(GridError::InvalidMargin(2)));
+        assert_eq!(grid.unpadded(1).unwrap().cells, vec![]);
+
+        let empty = VecGrid::filled(Rect::new((0, 2)), 0);
+        assert_eq!(empty.padded(1, PadMode::ClampEdge), Err(GridError::Empty));
+        let padded = empty.padded(1, PadMode::Constant(7)).unwrap();
+        assert_eq!(padded.bounds, Rect::new((2, 4)).translate((-1, -1)));
+        assert_eq!(padded.count_value(&7), 8);
+    }
+
+    #[test]
+    fn negative_origin() {
+        let mut grid = VecGrid::filled(Rect::new((16, 16)), 0);
+        grid.set_origin((-8, -8));
+        assert_eq!(grid.origin(), Coord::new(-8, -8));
+        assert_eq!(grid.bounds(), Rect::with_corners((-8, -8), (8, 8)));
+        assert!(grid.set((-8, -8), 1));
+        assert!(grid.set((-1, 3), 2));
+        assert!(grid.set((7, 7), 3));
+        assert!(!grid.set((8, 0), 4));
+        assert_eq!(grid.get((-1, 3)), Some(&2));
+        assert_eq!(grid.cells[0], 1);
+        assert_eq!(
+            grid.iter()
+                .filter(|(_, &cell)| cell != 0)
+                .collect::<Vec<_>>(),
+            vec![
+                (Coord::new(-8, -8), &1),
+                (Coord::new(-1, 3), &2),
+                (Coord::new(7, 7), &3),
+            ]
+        );
+
+        let mut stamp = VecGrid::filled(Rect::with_corners((-10, 2), (0, 4)), 5);
+        assert_eq!(grid.blit(&stamp), 16);
+        assert_eq!(grid.get((-1, 3)), Some(&5));
+        assert_eq!(grid.get((-8, 2)), Some(&5));
+        assert_eq!(grid.get((0, 3)), Some(&0));
+        stamp.set_origin((100, 100));
+        assert_eq!(grid.blit(&stamp), 0);
+
+        grid.crop(Rect::with_corners((-2, 2), (1, 4))).unwrap();
+        assert_eq!(grid.cells, vec![5, 5, 0, 5, 5, 0]);
+        let text = grid.map(|cell| cell.to_string());
+        let mut moved = text.clone();
+        moved.set_origin((0, 0));
+        assert_eq!(text.to_string(), "550\n550\n");
+        assert_eq!(text.to_string(), moved.to_string());
+    }
+
+    #[test]
+    fn render_to_matches_display() {
+        use std::fmt::Write;
+
+        let grid = VecGrid::with_generator(Rect::new((3, 2)).translate((-1, 4)), |(x, y)| {
+            format!("{}{} ", x, y)
+        });
+        let mut rendered = String::new();
+        grid.render_to(&mut rendered, |f, _, s| f.write_str(s))
+            .unwrap();
+        assert_eq!(rendered, grid.to_string());
+        assert_eq!(rendered, "-14 04 14 \n-15 05 15 \n");
+
+        let mut coords = String::new();
+        grid.render_to(&mut coords, |f, coord, _| write!(f, "{}", coord.x))
+            .unwrap();
+        assert_eq!(coords, "-101\n-101\n");
     }
 }
@@ -0,0 +1,563 @@
+use core::{fmt, marker::PhantomData, ptr, slice};
+
+use crate::{
+    coord::Coord,
+    grid::{Grid, GridError, GridMut, IterCell, IterCellMut},
+    patterns::Rect,
+    vecgrid::VecGrid,
+};
+
+/// A read-only view into a rectangular region of a [`VecGrid`].
+///
+/// Coordinates are local to the view, with `(0, 0)` being the view's
+/// `(left, top)` corner, so a view can't be used to read cells outside of its
+/// region.
+pub struct GridView<'a, T> {
+    /// Points to the view's first cell. Cell `(x, y)` of the view lives at
+    /// offset `x + y * stride`.
+    ptr: *const T,
+    /// Distance between the start of consecutive rows.
+    stride: usize,
+    dimensions: Coord,
+    marker: PhantomData<&'a T>,
+}
+
+// SAFETY: A `GridView` is equivalent to a set of `&[T]` row slices.
+unsafe impl<'a, T: Sync> Send for GridView<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for GridView<'a, T> {}
+
+// Derived impls would needlessly require `T: Clone`.
+impl<'a, T> Clone for GridView<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for GridView<'a, T> {}
+
+impl<'a, T> GridView<'a, T> {
+    /// Views the entirety of `grid`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid's bounds don't match its number of cells.
+    pub(crate) fn from_grid(grid: &'a VecGrid<T>) -> Self {
+        let dimensions = grid_dimensions(grid, grid.cells.len());
+        Self {
+            ptr: grid.cells.as_ptr(),
+            stride: dimensions.x as usize,
+            dimensions,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn dimensions(&self) -> Coord {
+        self.dimensions
+    }
+
+    /// The region covered by the view, in local coordinates.
+    pub fn bounds(&self) -> Rect {
+        Rect::new(self.dimensions)
+    }
+
+    pub fn get<C: Into<Coord>>(&self, coord: C) -> Option<&'a T> {
+        let offset = cell_offset(self.dimensions, self.stride, coord.into())?;
+        // SAFETY: The offset is within the view.
+        Some(unsafe { &*self.ptr.add(offset) })
+    }
+
+    /// Returns an iterator over each row of the view, from top to bottom.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &'a [T]> + ExactSizeIterator {
+        let view = *self;
+        (0..view.dimensions.y as usize).map(move |y| {
+            // SAFETY: Each row lies within the view.
+            unsafe {
+                slice::from_raw_parts(view.ptr.add(y * view.stride), view.dimensions.x as usize)
+            }
+        })
+    }
+
+    /// Returns an iterator over all cells in the view, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = IterCell<'a, T>> {
+        self.rows().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .map(move |(x, cell)| (Coord::new(x as i32, y as i32), cell))
+        })
+    }
+
+    /// Returns an iterator over the cells specified by the coords iterator.
+    pub fn selection_iter<I>(
+        &self,
+        coords: I,
+    ) -> impl Iterator<Item = Result<IterCell<'a, T>, GridError>>
+    where
+        I: IntoIterator,
+        I::Item: Into<Coord>,
+    {
+        let view = *self;
+        coords.into_iter().map(move |coord| {
+            let coord = coord.into();
+            view.get(coord)
+                .map(|cell| (coord, cell))
+                .ok_or(GridError::OutOfBounds {
+                    coord,
+                    bounds: view.bounds(),
+                })
+        })
+    }
+
+    /// Returns a view into a region of this view, given in this view's local
+    /// coordinates, or `None` if `rect` doesn't fit inside of it.
+    pub fn view(&self, rect: Rect) -> Option<GridView<'a, T>> {
+        let offset = region_offset(self.dimensions, self.stride, rect)?;
+        Some(Self {
+            // SAFETY: The region lies within the view.
+            ptr: unsafe { self.ptr.add(offset) },
+            stride: self.stride,
+            dimensions: rect.dimensions(),
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<'a, T> Grid<T> for GridView<'a, T> {
+    fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        GridView::get(self, coord)
+    }
+
+    fn bounds(&self) -> Rect {
+        GridView::bounds(self)
+    }
+
+    fn iter<'b>(&'b self) -> impl Iterator<Item = IterCell<'b, T>>
+    where
+        T: 'b,
+    {
+        GridView::iter(self)
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for GridView<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.rows()).finish()
+    }
+}
+
+/// A mutable view into a rectangular region of a [`VecGrid`].
+///
+/// Like [`GridView`], coordinates are local to the view. A mutable view can be
+/// split into two disjoint views with
+/// [`split_horizontal`](GridViewMut::split_horizontal) or
+/// [`split_vertical`](GridViewMut::split_vertical), the 2D analogue of
+/// [`slice::split_at_mut`].
+pub struct GridViewMut<'a, T> {
+    /// Points to the view's first cell. Cell `(x, y)` of the view lives at
+    /// offset `x + y * stride`.
+    ptr: *mut T,
+    /// Distance between the start of consecutive rows.
+    stride: usize,
+    dimensions: Coord,
+    marker: PhantomData<&'a mut T>,
+}
+
+// SAFETY: A `GridViewMut` is equivalent to a set of disjoint `&mut [T]` row
+// slices.
+unsafe impl<'a, T: Send> Send for GridViewMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for GridViewMut<'a, T> {}
+
+impl<'a, T> GridViewMut<'a, T> {
+    /// Mutably views the entirety of `grid`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid's bounds don't match its number of cells.
+    pub(crate) fn from_grid(grid: &'a mut VecGrid<T>) -> Self {
+        let dimensions = grid_dimensions(grid, grid.cells.len());
+        Self {
+            ptr: grid.cells.as_mut_ptr(),
+            stride: dimensions.x as usize,
+            dimensions,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn dimensions(&self) -> Coord {
+        self.dimensions
+    }
+
+    /// The region covered by the view, in local coordinates.
+    pub fn bounds(&self) -> Rect {
+        Rect::new(self.dimensions)
+    }
+
+    /// Reborrows this view as a read-only [`GridView`].
+    pub fn as_view(&self) -> GridView<'_, T> {
+        GridView {
+            ptr: self.ptr,
+            stride: self.stride,
+            dimensions: self.dimensions,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        self.as_view().get(coord)
+    }
+
+    pub fn get_mut<C: Into<Coord>>(&mut self, coord: C) -> Option<&mut T> {
+        let offset = cell_offset(self.dimensions, self.stride, coord.into())?;
+        // SAFETY: The offset is within the view, which we have unique access to.
+        Some(unsafe { &mut *self.ptr.add(offset) })
+    }
+
+    /// Returns an iterator over each row of the view, from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.as_view().rows()
+    }
+
+    /// Returns a mutable iterator over each row of the view, from top to
+    /// bottom.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        let (ptr, stride, width) = (self.ptr, self.stride, self.dimensions.x as usize);
+        (0..self.dimensions.y as usize).map(move |y| {
+            // SAFETY: Rows lie within the view and never overlap each other.
+            unsafe { slice::from_raw_parts_mut(ptr.add(y * stride), width) }
+        })
+    }
+
+    /// Returns an iterator over all cells in the view, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = IterCell<'_, T>> {
+        self.as_view().iter()
+    }
+
+    /// Returns a mutable iterator over all cells in the view, in row-major
+    /// order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        self.rows_mut().enumerate().flat_map(|(y, row)| {
+            row.iter_mut()
+                .enumerate()
+                .map(move |(x, cell)| (Coord::new(x as i32, y as i32), cell))
+        })
+    }
+
+    /// Returns a mutable view into a region of this view, given in this view's
+    /// local coordinates, or `None` if `rect` doesn't fit inside of it.
+    pub fn view_mut(&mut self, rect: Rect) -> Option<GridViewMut<'_, T>> {
+        // SAFETY: The subview mutably borrows `self` for its lifetime.
+        unsafe { self.region(rect) }
+    }
+
+    /// Splits the view into the columns left of `at` and the columns from `at`
+    /// onwards, matching
+    /// [`Rect::partition_horizontal`](crate::patterns::Rect::partition_horizontal).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is negative or greater than the view width.
+    pub fn split_horizontal(self, at: i32) -> (Self, Self) {
+        assert!(
+            0 <= at && at <= self.dimensions.x,
+            "split at column {} is outside of the view width {}",
+            at,
+            self.dimensions.x
+        );
+        let (left, right) = self.bounds().partition_horizontal(at);
+        self.split(left, right)
+    }
+
+    /// Splits the view into the rows above `at` and the rows from `at`
+    /// onwards, matching
+    /// [`Rect::partition_vertical`](crate::patterns::Rect::partition_vertical).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is negative or greater than the view height.
+    pub fn split_vertical(self, at: i32) -> (Self, Self) {
+        assert!(
+            0 <= at && at <= self.dimensions.y,
+            "split at row {} is outside of the view height {}",
+            at,
+            self.dimensions.y
+        );
+        let (bottom, top) = self.bounds().partition_vertical(at);
+        self.split(top, bottom)
+    }
+
+    /// Splits the view into two views of the given disjoint regions.
+    fn split(self, a: Rect, b: Rect) -> (Self, Self) {
+        // SAFETY: The caller guarantees that `a` and `b` don't overlap, and
+        // `self` is consumed.
+        unsafe { (self.region(a).unwrap(), self.region(b).unwrap()) }
+    }
+
+    /// Creates a view of a region of this view, or `None` if `rect` doesn't
+    /// fit inside of it.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure the new view isn't used to access cells
+    /// alongside any other view of the same cells.
+    unsafe fn region(&self, rect: Rect) -> Option<Self> {
+        let offset = region_offset(self.dimensions, self.stride, rect)?;
+        Some(Self {
+            ptr: self.ptr.add(offset),
+            stride: self.stride,
+            dimensions: rect.dimensions(),
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<'a, T> Grid<T> for GridViewMut<'a, T> {
+    fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        GridViewMut::get(self, coord)
+    }
+
+    fn bounds(&self) -> Rect {
+        GridViewMut::bounds(self)
+    }
+
+    fn iter<'b>(&'b self) -> impl Iterator<Item = IterCell<'b, T>>
+    where
+        T: 'b,
+    {
+        GridViewMut::iter(self)
+    }
+}
+
+impl<'a, T> GridMut<T> for GridViewMut<'a, T> {
+    fn get_mut<C: Into<Coord>>(&mut self, coord: C) -> Option<&mut T> {
+        GridViewMut::get_mut(self, coord)
+    }
+
+    fn copy<C1, C2>(&mut self, src: C1, dest: C2) -> bool
+    where
+        T: Copy,
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        let dest = dest.into();
+        match self.get_copied(src) {
+            Some(value) if self.contains(dest) => self.set(dest, value),
+            _ => false,
+        }
+    }
+
+    fn swap<C1, C2>(&mut self, coord1: C1, coord2: C2) -> bool
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        let offset1 = cell_offset(self.dimensions, self.stride, coord1.into());
+        let offset2 = cell_offset(self.dimensions, self.stride, coord2.into());
+        if let (Some(offset1), Some(offset2)) = (offset1, offset2) {
+            // SAFETY: Both offsets are within the view, which we have unique
+            // access to. `ptr::swap` allows the two cells to be the same.
+            unsafe { ptr::swap(self.ptr.add(offset1), self.ptr.add(offset2)) };
+            true
+        } else {
+            false
+        }
+    }
+
+    fn mov(&mut self, src: Coord, dest: Coord) -> Option<T>
+    where
+        T: Default,
+    {
+        if !(self.contains(src) && self.contains(dest)) {
+            return None;
+        }
+        let src_value = self.take(src).unwrap();
+        self.replace(dest, src_value)
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for GridViewMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_view().fmt(f)
+    }
+}
+
+impl<T> VecGrid<T> {
+    /// Returns a read-only view into the region of the grid covered by
+    /// `rect`, or `None` if `rect` extends outside of the grid bounds.
+    pub fn view(&self, rect: Rect) -> Option<GridView<'_, T>> {
+        GridView::from_grid(self).view(rect.translate(self.bounds.offset().negate()))
+    }
+
+    /// Returns a mutable view into the region of the grid covered by `rect`,
+    /// or `None` if `rect` extends outside of the grid bounds.
+    pub fn view_mut(&mut self, rect: Rect) -> Option<GridViewMut<'_, T>> {
+        let rect = rect.translate(self.bounds.offset().negate());
+        // SAFETY: The region mutably borrows the grid for its lifetime.
+        unsafe { GridViewMut::from_grid(self).region(rect) }
+    }
+}
+
+/// The dimensions of `grid`, treating malformed bounds as empty.
+///
+/// Views trust their dimensions to stay within the backing storage, so this
+/// panics if they would cover anything other than exactly its `len` cells,
+/// which can only happen if the public fields of the grid were set to
+/// disagree.
+fn grid_dimensions<T>(grid: &VecGrid<T>, len: usize) -> Coord {
+    let dimensions = grid.bounds.dimensions();
+    let dimensions = Coord::new(dimensions.x.max(0), dimensions.y.max(0));
+    let area = (dimensions.x as usize).checked_mul(dimensions.y as usize);
+    assert!(
+        area == Some(len),
+        "grid bounds {:?} don't match its {} cells",
+        grid.bounds,
+        len
+    );
+    dimensions
+}
+
+/// Returns the offset of `coord` within a view, if it's in bounds.
+fn cell_offset(dimensions: Coord, stride: usize, coord: Coord) -> Option<usize> {
+    if !Rect::new(dimensions).contains(coord) {
+        return None;
+    }
+    Some(coord.y as usize * stride + coord.x as usize)
+}
+
+/// Returns the offset of the first cell of `rect` within a view, if `rect` fits
+/// inside of it.
+fn region_offset(dimensions: Coord, stride: usize, rect: Rect) -> Option<usize> {
+    if rect.left < 0
+        || rect.top < 0
+        || rect.right > dimensions.x
+        || rect.bottom > dimensions.y
+        || rect.width() < 0
+        || rect.height() < 0
+    {
+        return None;
+    }
+    // An empty region may sit on the far edge of the view, where its first
+    // cell would be past the end of the storage. It never reads any cells, so
+    // it can start anywhere.
+    if rect.area() == 0 {
+        return Some(0);
+    }
+    Some(rect.top as usize * stride + rect.left as usize)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    fn coord_grid() -> VecGrid<Coord> {
+        VecGrid::with_generator(Rect::new((6, 5)), |coord: Coord| coord)
+    }
+
+    #[test]
+    fn view_local_coords() {
+        let grid = coord_grid();
+        let view = grid.view(Rect::with_corners((2, 1), (5, 3))).unwrap();
+        assert_eq!(view.dimensions(), Coord::new(3, 2));
+        assert_eq!(view.get((0, 0)), Some(&Coord::new(2, 1)));
+        assert_eq!(view.get((2, 1)), Some(&Coord::new(4, 2)));
+        assert_eq!(view.get((3, 0)), None);
+        assert_eq!(view.get((-1, 0)), None);
+        assert!(view
+            .iter()
+            .all(|(coord, cell)| *cell == coord + Coord::new(2, 1)));
+        assert_eq!(view.iter().count(), 6);
+        let rows = view.rows().collect::<Vec<_>>();
+        assert_eq!(rows[1], &[(2, 2).into(), (3, 2).into(), (4, 2).into()][..]);
+    }
+
+    #[test]
+    fn nested_views() {
+        let grid = coord_grid();
+        let outer = grid.view(Rect::with_corners((1, 1), (6, 5))).unwrap();
+        let inner = outer.view(Rect::with_corners((1, 1), (3, 4))).unwrap();
+        assert_eq!(inner.get((0, 0)), Some(&Coord::new(2, 2)));
+        assert_eq!(inner.get((1, 2)), Some(&Coord::new(3, 4)));
+        assert!(outer.view(Rect::new((6, 1))).is_none());
+    }
+
+    #[test]
+    fn out_of_bounds_views() {
+        let grid = coord_grid();
+        assert!(grid.view(Rect::new((6, 5))).is_some());
+        assert!(grid.view(Rect::new((7, 5))).is_none());
+        assert!(grid.view(Rect::with_corners((-1, 0), (2, 2))).is_none());
+        let empty = grid.view(Rect::with_corners((6, 5), (6, 5))).unwrap();
+        assert_eq!(empty.iter().count(), 0);
+        assert!(empty.view(Rect::new((0, 0))).is_some());
+        let column = grid.view(Rect::with_corners((6, 0), (6, 5))).unwrap();
+        assert_eq!(column.rows().map(|row| row.len()).sum::<usize>(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "don't match")]
+    fn mismatched_bounds() {
+        let mut grid = coord_grid();
+        grid.bounds = Rect::new((1000, 1000));
+        grid.rows().nth(500);
+    }
+
+    #[test]
+    fn offset_grid_view() {
+        let grid = VecGrid::with_generator(Rect::with_corners((-3, -3), (3, 3)), |c: Coord| c);
+        let view = grid.view(Rect::with_corners((-1, -1), (2, 2))).unwrap();
+        assert_eq!(view.get((1, 1)), Some(&Coord::ZERO));
+        let results = view
+            .selection_iter(vec![Coord::ZERO, Coord::new(3, 3)])
+            .collect::<Vec<_>>();
+        assert_eq!(results[0], Ok((Coord::ZERO, &Coord::new(-1, -1))));
+        assert_eq!(
+            results[1],
+            Err(GridError::OutOfBounds {
+                coord: Coord::new(3, 3),
+                bounds: view.bounds()
+            })
+        );
+    }
+
+    #[test]
+    fn view_mut() {
+        let mut grid: VecGrid<i32> = VecGrid::new(Rect::with_corners((-2, -2), (4, 4)));
+        let mut view = grid.view_mut(Rect::with_corners((0, 0), (3, 2))).unwrap();
+        for (coord, cell) in view.iter_mut() {
+            *cell = coord.x + coord.y * 10;
+        }
+        *view.get_mut((2, 1)).unwrap() = 99;
+        assert!(view.get_mut((3, 0)).is_none());
+        assert_eq!(grid.get((1, 1)), Some(&11));
+        assert_eq!(grid.get((2, 1)), Some(&99));
+        assert_eq!(grid.get((3, 1)), Some(&0));
+        assert_eq!(grid.iter().filter(|(_coord, &cell)| cell != 0).count(), 5);
+    }
+
+    #[test]
+    fn split_views() {
+        let mut grid: VecGrid<char> = VecGrid::filled(Rect::new((5, 4)), '.');
+        let view = grid.view_mut(Rect::new((5, 4))).unwrap();
+        let (top, bottom) = view.split_vertical(1);
+        let (mut left, mut right) = bottom.split_horizontal(2);
+        assert_eq!(top.dimensions(), Coord::new(5, 1));
+        assert_eq!(left.dimensions(), Coord::new(2, 3));
+        assert_eq!(right.dimensions(), Coord::new(3, 3));
+        std::thread::scope(|scope| {
+            scope.spawn(|| left.iter_mut().for_each(|(_coord, cell)| *cell = 'L'));
+            scope.spawn(|| right.rows_mut().for_each(|row| row.fill('R')));
+        });
+        let rows = grid
+            .rows()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![".....", "LLRRR", "LLRRR", "LLRRR"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_out_of_range() {
+        let mut grid: VecGrid<u8> = VecGrid::new(Rect::new((2, 2)));
+        grid.view_mut(Rect::new((2, 2)))
+            .unwrap()
+            .split_horizontal(3);
+    }
+}
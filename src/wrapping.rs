@@ -0,0 +1,331 @@
+use alloc::vec::Vec;
+
+use crate::{
+    coord::Coord,
+    grid::{cheapest_path, Grid, GridError, GridMut, IterCell, IterCellMut},
+    patterns::{Connectivity, Rect},
+    vecgrid::VecGrid,
+};
+
+/// A [`VecGrid`] whose edges wrap around, as if it were a torus.
+///
+/// Every coordinate-taking operation wraps coordinates into the grid bounds
+/// (using euclidean modulo, so negative coordinates wrap as well), which makes
+/// code written against the [`Grid`] trait toroidal just by swapping the grid
+/// type. Iterators yield the wrapped coordinate of each cell; on grids
+/// narrower than a neighborhood, the same cell can be yielded more than once.
+///
+/// Use [`inner`](WrappingGrid::inner) or
+/// [`into_inner`](WrappingGrid::into_inner) to reach the non-wrapping grid.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WrappingGrid<T>(VecGrid<T>);
+
+impl<T> Grid<T> for WrappingGrid<T> {
+    /// Returns the cell at `coord` after wrapping it, which is only `None`
+    /// when the grid has zero area.
+    fn get<C: Into<Coord>>(&self, coord: C) -> Option<&T> {
+        self.0.get(self.0.wrap_coord(coord.into())?)
+    }
+
+    fn bounds(&self) -> Rect {
+        self.0.bounds
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = IterCell<'a, T>>
+    where
+        T: 'a,
+    {
+        self.0.iter()
+    }
+
+    /// Returns an iterator over the cells specified by the coords iterator,
+    /// yielding the wrapped coordinate of each. Coords only yield a
+    /// [`GridError::OutOfBounds`] when the grid has zero area.
+    fn selection_iter<'a, I>(
+        &'a self,
+        coords: I,
+    ) -> impl Iterator<Item = Result<IterCell<'a, T>, GridError>>
+    where
+        T: 'a,
+        I: IntoIterator,
+        I::Item: Into<Coord>,
+    {
+        self.0
+            .selection_iter(coords.into_iter().map(move |coord| self.wrap(coord.into())))
+    }
+
+    /// Like [`Grid::path_with_budget`], after wrapping `start` and `goal`, but
+    /// estimates the remaining distance the short way around each axis, so
+    /// that paths across the seams aren't given up on.
+    fn path_with_budget<C1, C2, F>(
+        &self,
+        start: C1,
+        goal: C2,
+        passable: F,
+        max_cost: u32,
+    ) -> Option<Vec<Coord>>
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+        F: Fn(Coord, &T) -> bool,
+    {
+        let goal = self.wrap(goal.into());
+        if !self.0.contains(goal) {
+            return None;
+        }
+        let dimensions = self.0.bounds.dimensions();
+        let cost = |coord, cell: &T| if passable(coord, cell) { Some(1) } else { None };
+        let distance = |coord: Coord| {
+            let delta = goal - coord;
+            let around = |delta: i32, len: i32| {
+                let delta = delta.unsigned_abs();
+                delta.min(len as u32 - delta)
+            };
+            around(delta.x, dimensions.x) + around(delta.y, dimensions.y)
+        };
+        cheapest_path(
+            self,
+            self.wrap(start.into()),
+            |coord| coord == goal,
+            cost,
+            distance,
+            max_cost,
+        )
+        .map(|(path, _)| path)
+    }
+}
+
+impl<T> GridMut<T> for WrappingGrid<T> {
+    fn get_mut<C: Into<Coord>>(&mut self, coord: C) -> Option<&mut T> {
+        let coord = self.0.wrap_coord(coord.into())?;
+        self.0.get_mut(coord)
+    }
+
+    fn copy<C1, C2>(&mut self, src: C1, dest: C2) -> bool
+    where
+        T: Copy,
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        let (src, dest) = (self.wrap(src.into()), self.wrap(dest.into()));
+        self.0.copy(src, dest)
+    }
+
+    fn swap<C1, C2>(&mut self, coord1: C1, coord2: C2) -> bool
+    where
+        C1: Into<Coord>,
+        C2: Into<Coord>,
+    {
+        let (coord1, coord2) = (self.wrap(coord1.into()), self.wrap(coord2.into()));
+        self.0.swap(coord1, coord2)
+    }
+
+    fn mov(&mut self, src: Coord, dest: Coord) -> Option<T>
+    where
+        T: Default,
+    {
+        let (src, dest) = (self.wrap(src), self.wrap(dest));
+        self.0.mov(src, dest)
+    }
+}
+
+impl<T> WrappingGrid<T> {
+    pub fn new(grid: VecGrid<T>) -> Self {
+        Self(grid)
+    }
+
+    /// Returns the underlying grid, whose operations don't wrap.
+    pub fn inner(&self) -> &VecGrid<T> {
+        &self.0
+    }
+
+    /// Mutable version of [`inner`](WrappingGrid::inner).
+    pub fn inner_mut(&mut self) -> &mut VecGrid<T> {
+        &mut self.0
+    }
+
+    pub fn into_inner(self) -> VecGrid<T> {
+        self.0
+    }
+
+    /// Returns a mutable iterator over the cells specified by the coords
+    /// iterator, following the rules of [`VecGrid::selection_iter_mut`] after
+    /// wrapping each coord. Two coords which wrap to the same cell count as
+    /// repeat visits, so the second yields a [`GridError::AlreadyVisited`].
+    pub fn selection_iter_mut<I>(
+        &mut self,
+        coords: I,
+    ) -> impl Iterator<Item = Result<IterCellMut<'_, T>, GridError>>
+    where
+        I: IntoIterator,
+        I::Item: Into<Coord>,
+    {
+        let bounds = self.0.bounds;
+        self.0
+            .selection_iter_mut(coords.into_iter().map(move |coord| {
+                let coord = coord.into();
+                VecGrid::<T>::wrap_coord_with_bounds(bounds, coord).unwrap_or(coord)
+            }))
+    }
+
+    /// Returns a mutable iterator over the Moore neighborhood of `coord`,
+    /// wrapping around the edges. Neighbors which wrap onto a cell that was
+    /// already yielded are skipped.
+    pub fn neighbors_mut<C: Into<Coord>>(
+        &mut self,
+        coord: C,
+    ) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        self.selection_iter_mut(Connectivity::Eight.neighbors(coord))
+            .filter_map(Result::ok)
+    }
+
+    /// Returns an iterator over the cells covered by `rect`, wrapping around
+    /// the edges, in row-major order. Unlike [`VecGrid::iter_rect`], `rect`
+    /// may extend past the grid, or be larger than it.
+    pub fn iter_rect(&self, rect: Rect) -> impl Iterator<Item = IterCell<'_, T>> {
+        self.selection_iter(rect.iter()).filter_map(Result::ok)
+    }
+
+    /// Mutable version of [`iter_rect`](WrappingGrid::iter_rect). Each cell
+    /// is only yielded the first time `rect` covers it.
+    pub fn iter_rect_mut(&mut self, rect: Rect) -> impl Iterator<Item = IterCellMut<'_, T>> {
+        self.selection_iter_mut(rect.iter()).filter_map(Result::ok)
+    }
+
+    /// Wraps `coord` into the grid bounds, leaving it as it is if the grid has
+    /// zero area.
+    fn wrap(&self, coord: Coord) -> Coord {
+        self.0.wrap_coord(coord).unwrap_or(coord)
+    }
+}
+
+impl<T> From<VecGrid<T>> for WrappingGrid<T> {
+    fn from(grid: VecGrid<T>) -> Self {
+        Self(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automata::LifeRule;
+
+    #[test]
+    fn wraps_coords() {
+        let bounds = Rect::new((4, 3)).translate((-2, -1));
+        let mut grid = WrappingGrid::new(VecGrid::with_generator(bounds, |(x, y)| x + y * 10));
+        assert_eq!(grid.get((2, -1)), Some(&-12));
+        assert_eq!(grid.get((-3, 2)), Some(&-9));
+        assert_eq!(grid.get((-101, 300)), Some(&-1));
+        assert!(grid.set((6, 5), 100));
+        assert_eq!(grid.inner().get((-2, -1)), Some(&100));
+
+        let neighbors = grid
+            .ortho_neighbors((1, 1))
+            .map(|(coord, _)| coord)
+            .collect::<Vec<_>>();
+        assert!(neighbors.contains(&Coord::new(-2, 1)));
+        assert!(neighbors.contains(&Coord::new(1, -1)));
+        assert!(neighbors.iter().all(|&coord| bounds.contains(coord)));
+
+        assert_eq!(grid.iter_rect(Rect::new((6, 3))).count(), 18);
+        assert_eq!(grid.iter_rect_mut(Rect::new((6, 3))).count(), 12);
+    }
+
+    #[test]
+    fn selection_iter_mut_sees_wrapped_duplicates() {
+        let mut grid = WrappingGrid::from(VecGrid::filled(Rect::new((3, 3)), 0));
+        let results = grid
+            .selection_iter_mut(vec![Coord::new(-1, 0), Coord::new(2, 3)])
+            .map(|result| result.map(|(coord, _)| coord))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![
+                Ok(Coord::new(2, 0)),
+                Err(GridError::AlreadyVisited(Coord::new(2, 0)))
+            ]
+        );
+        for (_, cell) in grid.neighbors_mut((0, 0)) {
+            *cell += 1;
+        }
+        assert_eq!(grid.into_inner().cells, vec![0, 1, 1, 1, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn paths_cross_seams() {
+        let grid = WrappingGrid::new(VecGrid::filled(Rect::new((4, 4)), ()));
+        let (path, cost) = grid.dijkstra_path((0, 0), (3, 3), |_, _| Some(1)).unwrap();
+        assert_eq!(cost, 2);
+        assert_eq!(path.len(), 3);
+        assert!(path.iter().all(|&coord| grid.bounds().contains(coord)));
+
+        let (path, _) = grid
+            .astar_path(
+                (1, 1),
+                (1, 3),
+                |coord, _| {
+                    if coord == Coord::new(1, 2) {
+                        None
+                    } else {
+                        Some(1)
+                    }
+                },
+                |_| 0,
+            )
+            .unwrap();
+        assert_eq!(
+            path,
+            vec![Coord::new(1, 1), Coord::new(1, 0), Coord::new(1, 3)]
+        );
+
+        let path = grid
+            .path_to_nearest((0, 0), vec![Coord::new(2, 3)], |_, _| true)
+            .unwrap();
+        assert_eq!(path.len(), 4);
+        assert_eq!(path.last(), Some(&Coord::new(2, 3)));
+        assert_eq!(
+            grid.path_with_budget((0, 0), (3, 0), |_, _| true, 1),
+            Some(vec![Coord::new(0, 0), Coord::new(3, 0)])
+        );
+        assert_eq!(
+            grid.path_with_budget((4, 4), (-1, -1), |_, _| true, 2)
+                .map(|path| path.len()),
+            Some(3)
+        );
+        assert_eq!(grid.path_with_budget((0, 0), (2, 2), |_, _| true, 3), None);
+    }
+
+    /// Steps Life on any grid through the [`Grid`] trait alone.
+    fn step_life<G: Grid<bool>>(grid: &G) -> VecGrid<bool> {
+        VecGrid::with_generator(grid.bounds(), |coord: Coord| {
+            let alive = grid.neighbors(coord).filter(|(_, &alive)| alive).count();
+            LifeRule::CONWAY.next_state(*grid.get(coord).unwrap(), alive)
+        })
+    }
+
+    #[test]
+    fn glider_crosses_seam() {
+        let bounds = Rect::new((6, 6));
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let cells = |offset: Coord| {
+            let mut grid = VecGrid::new(bounds);
+            for &coord in glider.iter() {
+                let coord = grid.wrap_coord(Coord::from(coord) + offset).unwrap();
+                grid.set(coord, true);
+            }
+            grid
+        };
+
+        let start = cells(Coord::new(3, 3));
+        let mut grid = WrappingGrid::new(start.clone());
+        for generation in 1..=24 {
+            grid = WrappingGrid::new(step_life(&grid));
+            if generation % 4 == 0 {
+                let offset = Coord::new(3 + generation / 4, 3 + generation / 4);
+                assert_eq!(grid.inner(), &cells(offset));
+            }
+        }
+        assert_eq!(grid.into_inner(), start);
+    }
+}